@@ -25,18 +25,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("  Warning: {}", report.summary.warning);
     println!("  Stale: {}", report.summary.stale);
     println!("  Risky: {}", report.summary.risky);
+    println!("  Deprecated: {}", report.summary.deprecated);
     println!("  Average score: {:.1}", report.summary.average_health_score);
     println!();
     
-    // Show risky dependencies
+    // Show risky and deprecated dependencies
     let risky: Vec<_> = report
         .dependencies
         .iter()
-        .filter(|d| matches!(d.status, HealthStatus::Risky | HealthStatus::Stale))
+        .filter(|d| matches!(d.status, HealthStatus::Risky | HealthStatus::Stale | HealthStatus::Deprecated))
         .collect();
     
     if !risky.is_empty() {
-        println!("Risky/Stale Dependencies:");
+        println!("Risky/Stale/Deprecated Dependencies:");
         for dep in risky {
             println!(
                 "  - {} v{}: score {} ({})",
@@ -16,6 +16,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             community: 0.15,
             stability: 0.05,
             security: 0.0,
+            unsafe_code: 0.10,  // Share taken by unsafe usage when sources are analyzed
         })
         .staleness_thresholds(StalenessThresholds {
             stale_days: 180,    // 6 months instead of 1 year
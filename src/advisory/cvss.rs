@@ -0,0 +1,143 @@
+//! CVSS v3.x base score calculation
+
+/// Compute the base score (0.0-10.0) of a CVSS v3.0/v3.1 vector string.
+///
+/// Returns `None` if the vector is not a v3 vector or is missing base metrics.
+pub fn cvss_base_score(vector: &str) -> Option<f32> {
+    let mut parts = vector.split('/');
+    let prefix = parts.next()?;
+    if prefix != "CVSS:3.0" && prefix != "CVSS:3.1" {
+        return None;
+    }
+
+    let mut av = None;
+    let mut ac = None;
+    let mut pr = None;
+    let mut ui = None;
+    let mut scope_changed = None;
+    let mut c = None;
+    let mut i = None;
+    let mut a = None;
+
+    for part in parts {
+        let (metric, value) = part.split_once(':')?;
+        match metric {
+            "AV" => {
+                av = Some(match value {
+                    "N" => 0.85,
+                    "A" => 0.62,
+                    "L" => 0.55,
+                    "P" => 0.2,
+                    _ => return None,
+                })
+            }
+            "AC" => {
+                ac = Some(match value {
+                    "L" => 0.77,
+                    "H" => 0.44,
+                    _ => return None,
+                })
+            }
+            "PR" => pr = Some(value),
+            "UI" => {
+                ui = Some(match value {
+                    "N" => 0.85,
+                    "R" => 0.62,
+                    _ => return None,
+                })
+            }
+            "S" => {
+                scope_changed = Some(match value {
+                    "U" => false,
+                    "C" => true,
+                    _ => return None,
+                })
+            }
+            "C" => c = Some(impact_weight(value)?),
+            "I" => i = Some(impact_weight(value)?),
+            "A" => a = Some(impact_weight(value)?),
+            // Temporal and environmental metrics don't affect the base score
+            _ => {}
+        }
+    }
+
+    let scope_changed = scope_changed?;
+    let pr = match (pr?, scope_changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        ("H", false) => 0.27,
+        ("H", true) => 0.5,
+        _ => return None,
+    };
+
+    let iss = 1.0 - (1.0 - c?) * (1.0 - i?) * (1.0 - a?);
+    let impact = if scope_changed {
+        7.52 * (iss - 0.029) - 3.25 * (iss - 0.02f64).powi(15)
+    } else {
+        6.42 * iss
+    };
+    let exploitability = 8.22 * av? * ac? * pr * ui?;
+
+    if impact <= 0.0 {
+        return Some(0.0);
+    }
+
+    let score = if scope_changed {
+        round_up((1.08 * (impact + exploitability)).min(10.0))
+    } else {
+        round_up((impact + exploitability).min(10.0))
+    };
+
+    Some(score as f32)
+}
+
+fn impact_weight(value: &str) -> Option<f64> {
+    match value {
+        "H" => Some(0.56),
+        "L" => Some(0.22),
+        "N" => Some(0.0),
+        _ => None,
+    }
+}
+
+/// Round up to one decimal place, as defined in CVSS v3.1 Appendix A
+fn round_up(value: f64) -> f64 {
+    let int_input = (value * 100_000.0).round() as i64;
+    if int_input % 10_000 == 0 {
+        int_input as f64 / 100_000.0
+    } else {
+        ((int_input / 10_000) + 1) as f64 / 10.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cvss_base_score() {
+        assert_eq!(
+            cvss_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"),
+            Some(9.8)
+        );
+        assert_eq!(
+            cvss_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H"),
+            Some(10.0)
+        );
+        assert_eq!(
+            cvss_base_score("CVSS:3.0/AV:N/AC:H/PR:N/UI:N/S:U/C:N/I:N/A:H"),
+            Some(5.9)
+        );
+        assert_eq!(
+            cvss_base_score("CVSS:3.1/AV:L/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:N"),
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn test_cvss_invalid_vector() {
+        assert_eq!(cvss_base_score("AV:N/AC:L/Au:N/C:P/I:P/A:P"), None);
+        assert_eq!(cvss_base_score("CVSS:3.1/AV:N/AC:L"), None);
+    }
+}
@@ -0,0 +1,361 @@
+//! Offline vulnerability advisory databases
+
+mod cvss;
+pub mod osv;
+pub mod rustsec;
+
+pub use cvss::cvss_base_score;
+
+use crate::config::{cargo_home, AdvisoryConfig};
+use crate::error::{AuditError, Result};
+use crate::types::{Advisory, DependencySource};
+use semver::{Version, VersionReq};
+use std::collections::{HashMap, HashSet};
+use tracing::{debug, info};
+
+/// An advisory together with the version ranges it applies to
+#[derive(Debug, Clone)]
+pub struct AdvisoryRecord {
+    /// Name of the affected crate
+    pub package: String,
+    /// Advisory details reported to the user
+    pub advisory: Advisory,
+    /// Which versions of the package are affected
+    pub affected: AffectedVersions,
+    /// Informational kind (e.g. "unmaintained", "unsound"), if this is not a vulnerability
+    pub informational: Option<String>,
+    /// Crates the advisory recommends instead (linked from its description)
+    pub alternatives: Vec<String>,
+}
+
+/// Affected version description, in the form used by the source database
+#[derive(Debug, Clone)]
+pub enum AffectedVersions {
+    /// RustSec style: every version is affected unless it is patched or unaffected
+    Requirements {
+        patched: Vec<VersionReq>,
+        unaffected: Vec<VersionReq>,
+    },
+    /// OSV style: ordered SEMVER range events plus explicitly listed versions
+    Ranges {
+        ranges: Vec<Vec<RangeEvent>>,
+        versions: Vec<Version>,
+    },
+}
+
+/// A single OSV range event, sorted by version within its range
+#[derive(Debug, Clone)]
+pub enum RangeEvent {
+    Introduced(Version),
+    Fixed(Version),
+    LastAffected(Version),
+}
+
+impl AdvisoryRecord {
+    /// Whether the given version is affected by this advisory
+    pub fn affects(&self, version: &Version) -> bool {
+        match &self.affected {
+            AffectedVersions::Requirements { patched, unaffected } => {
+                !patched.iter().chain(unaffected).any(|req| req.matches(version))
+            }
+            AffectedVersions::Ranges { ranges, versions } => {
+                versions.contains(version)
+                    || ranges.iter().any(|events| range_affects(events, version))
+            }
+        }
+    }
+}
+
+/// Crate names linked from an advisory description (`crates.io/crates/<name>`, `lib.rs/crates/<name>`)
+pub fn crate_links(text: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for prefix in ["crates.io/crates/", "lib.rs/crates/"] {
+        for (position, _) in text.match_indices(prefix) {
+            let name: String = text[position + prefix.len()..]
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+                .collect();
+            if !name.is_empty() && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+/// Evaluate an OSV range: walk the sorted events, toggling affected state
+fn range_affects(events: &[RangeEvent], version: &Version) -> bool {
+    let mut affected = false;
+    for event in events {
+        match event {
+            RangeEvent::Introduced(v) if version >= v => affected = true,
+            RangeEvent::Fixed(v) if version >= v => affected = false,
+            RangeEvent::LastAffected(v) if version > v => affected = false,
+            _ => {}
+        }
+    }
+    affected
+}
+
+/// In-memory index of advisories keyed by crate name
+#[derive(Debug, Default)]
+pub struct AdvisoryDatabase {
+    records: HashMap<String, Vec<AdvisoryRecord>>,
+}
+
+impl AdvisoryDatabase {
+    /// Load all advisory sources enabled in the configuration
+    pub fn load(config: &AdvisoryConfig) -> Result<Self> {
+        let mut db = Self::default();
+
+        let rustsec_path = match &config.rustsec_db_path {
+            Some(path) => {
+                if !path.exists() {
+                    return Err(AuditError::config(format!(
+                        "RustSec advisory database not found at {}",
+                        path.display()
+                    )));
+                }
+                Some(path.clone())
+            }
+            None => cargo_home()
+                .map(|home| home.join("advisory-db"))
+                .filter(|path| path.is_dir()),
+        };
+
+        if let Some(path) = rustsec_path {
+            for record in rustsec::load_rustsec_db(&path)? {
+                db.insert(record);
+            }
+            info!("Loaded {} advisories from {}", db.len(), path.display());
+        } else {
+            debug!("No RustSec advisory database configured or found");
+        }
+
+        for path in &config.osv_paths {
+            let records = osv::load_osv(path)?;
+            info!("Loaded {} OSV advisories from {}", records.len(), path.display());
+            for record in records {
+                db.insert(record);
+            }
+        }
+
+        Ok(db)
+    }
+
+    /// Add an advisory to the index
+    pub fn insert(&mut self, record: AdvisoryRecord) {
+        self.records
+            .entry(record.package.clone())
+            .or_default()
+            .push(record);
+    }
+
+    /// Total number of advisories loaded
+    pub fn len(&self) -> usize {
+        self.records.values().map(Vec::len).sum()
+    }
+
+    /// Find vulnerabilities affecting a specific crate version.
+    ///
+    /// Both databases describe crates.io packages, so crates from any other
+    /// source never match, even when they share a name with a crates.io crate.
+    /// Informational advisories (unmaintained, unsound, ...) are not vulnerabilities
+    /// and are excluded. Advisories whose ID or any alias is in `ignored` are skipped.
+    /// The same issue published in several sources (matched by ID or alias) is only
+    /// reported once, preferring the source loaded first.
+    pub fn vulnerabilities(
+        &self,
+        name: &str,
+        version: &str,
+        source: &DependencySource,
+        ignored: &HashSet<String>,
+    ) -> Vec<Advisory> {
+        let Some(records) = self.records_for(name, source) else {
+            return Vec::new();
+        };
+
+        let Ok(version) = Version::parse(version) else {
+            debug!("Cannot match advisories for {} with invalid version {}", name, version);
+            return Vec::new();
+        };
+
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut found = Vec::new();
+
+        for record in records {
+            let advisory = &record.advisory;
+            let mut ids = std::iter::once(&advisory.id).chain(&advisory.aliases);
+
+            if record.informational.is_some()
+                || ids.clone().any(|id| ignored.contains(id))
+                || ids.any(|id| seen.contains(id.as_str()))
+                || !record.affects(&version)
+            {
+                continue;
+            }
+
+            seen.insert(&advisory.id);
+            seen.extend(advisory.aliases.iter().map(String::as_str));
+            found.push(advisory.clone());
+        }
+
+        found
+    }
+
+    /// Find an `informational = "unmaintained"` advisory covering a crate version.
+    ///
+    /// Only crates.io crates match. Advisories whose ID or any alias is in
+    /// `ignored` are skipped.
+    pub fn unmaintained(
+        &self,
+        name: &str,
+        version: &str,
+        source: &DependencySource,
+        ignored: &HashSet<String>,
+    ) -> Option<&AdvisoryRecord> {
+        let version = Version::parse(version).ok()?;
+        self.records_for(name, source)?.iter().find(|record| {
+            let advisory = &record.advisory;
+            record.informational.as_deref() == Some("unmaintained")
+                && !std::iter::once(&advisory.id)
+                    .chain(&advisory.aliases)
+                    .any(|id| ignored.contains(id))
+                && record.affects(&version)
+        })
+    }
+
+    /// Advisories for a crate, if it comes from crates.io
+    fn records_for(&self, name: &str, source: &DependencySource) -> Option<&Vec<AdvisoryRecord>> {
+        match source {
+            DependencySource::CratesIo => self.records.get(name),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AdvisorySource, Severity};
+
+    fn record(id: &str, patched: &str, informational: Option<&str>) -> AdvisoryRecord {
+        AdvisoryRecord {
+            package: "foo".to_string(),
+            advisory: Advisory {
+                id: id.to_string(),
+                source: AdvisorySource::RustSec,
+                title: None,
+                severity: Severity::High,
+                cvss: None,
+                cvss_score: None,
+                aliases: vec![format!("CVE-{}", id)],
+                url: None,
+                patched_versions: vec![patched.to_string()],
+            },
+            affected: AffectedVersions::Requirements {
+                patched: vec![VersionReq::parse(patched).unwrap()],
+                unaffected: Vec::new(),
+            },
+            informational: informational.map(String::from),
+            alternatives: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_vulnerabilities_matching() {
+        let mut db = AdvisoryDatabase::default();
+        db.insert(record("A", ">= 1.2.0", None));
+        db.insert(record("B", ">= 2.0.0", Some("unmaintained")));
+        assert_eq!(db.len(), 2);
+
+        let none = HashSet::new();
+        let crates_io = DependencySource::CratesIo;
+        let found = db.vulnerabilities("foo", "1.1.0", &crates_io, &none);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, "A");
+
+        assert!(db.vulnerabilities("foo", "1.2.0", &crates_io, &none).is_empty());
+        assert!(db.vulnerabilities("bar", "1.1.0", &crates_io, &none).is_empty());
+
+        let ignored = HashSet::from(["CVE-A".to_string()]);
+        assert!(db.vulnerabilities("foo", "1.1.0", &crates_io, &ignored).is_empty());
+    }
+
+    #[test]
+    fn test_other_sources_not_matched() {
+        let mut db = AdvisoryDatabase::default();
+        db.insert(record("A", ">= 1.2.0", None));
+        db.insert(record("B", ">= 9.0.0", Some("unmaintained")));
+
+        // A private crate or fork that happens to be called `foo`
+        let sources = [
+            DependencySource::Registry {
+                index_url: "sparse+https://registry.corp.example/index/".to_string(),
+                name: Some("corp".to_string()),
+            },
+            DependencySource::Git {
+                url: "https://github.com/someone/foo".to_string(),
+                branch: None,
+                tag: None,
+                rev: None,
+            },
+        ];
+        for source in &sources {
+            assert!(db.vulnerabilities("foo", "1.1.0", source, &HashSet::new()).is_empty());
+            assert!(db.unmaintained("foo", "1.1.0", source, &HashSet::new()).is_none());
+        }
+    }
+
+    #[test]
+    fn test_unmaintained() {
+        let mut db = AdvisoryDatabase::default();
+        db.insert(record("A", ">= 1.2.0", None));
+        let crates_io = DependencySource::CratesIo;
+        assert!(db.unmaintained("foo", "1.0.0", &crates_io, &HashSet::new()).is_none());
+
+        db.insert(record("B", ">= 9.0.0", Some("unmaintained")));
+        assert_eq!(
+            db.unmaintained("foo", "1.0.0", &crates_io, &HashSet::new()).unwrap().advisory.id,
+            "B"
+        );
+        let ignored = HashSet::from(["B".to_string()]);
+        assert!(db.unmaintained("foo", "1.0.0", &crates_io, &ignored).is_none());
+    }
+
+    #[test]
+    fn test_crate_links() {
+        let text = "## Possible Alternatives\n\n- [`once_cell`](https://crates.io/crates/once_cell)\n- [lazy](https://lib.rs/crates/std-lazy) and again https://crates.io/crates/once_cell";
+        assert_eq!(crate_links(text), vec!["once_cell", "std-lazy"]);
+    }
+
+    #[test]
+    fn test_vulnerabilities_deduplicated_by_alias() {
+        let mut db = AdvisoryDatabase::default();
+        db.insert(record("A", ">= 1.2.0", None));
+        let mut duplicate = record("GHSA-xxxx", ">= 1.2.0", None);
+        duplicate.advisory.source = AdvisorySource::Osv;
+        duplicate.advisory.aliases = vec!["A".to_string()];
+        db.insert(duplicate);
+
+        let found = db.vulnerabilities("foo", "1.0.0", &DependencySource::CratesIo, &HashSet::new());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].source, AdvisorySource::RustSec);
+    }
+
+    #[test]
+    fn test_range_affects() {
+        let v = |s: &str| Version::parse(s).unwrap();
+        let events = vec![
+            RangeEvent::Introduced(v("0.0.0")),
+            RangeEvent::Fixed(v("1.0.5")),
+            RangeEvent::Introduced(v("1.1.0")),
+            RangeEvent::LastAffected(v("1.1.3")),
+        ];
+
+        assert!(range_affects(&events, &v("0.9.0")));
+        assert!(!range_affects(&events, &v("1.0.5")));
+        assert!(range_affects(&events, &v("1.1.3")));
+        assert!(!range_affects(&events, &v("1.1.4")));
+    }
+}
@@ -0,0 +1,323 @@
+//! Load advisories in the OSV (Open Source Vulnerability) JSON format
+//!
+//! Accepts a single `.json` record, a directory of records (searched
+//! recursively), or a `.zip` archive such as the osv.dev `crates.io/all.zip`
+//! ecosystem export. Only `crates.io` ecosystem entries are kept.
+
+use super::{AdvisoryRecord, AffectedVersions, RangeEvent};
+use crate::error::{AuditError, Result};
+use crate::types::{Advisory, AdvisorySource, Severity};
+use semver::Version;
+use serde::Deserialize;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use tracing::{debug, warn};
+
+const CRATES_IO_ECOSYSTEM: &str = "crates.io";
+
+#[derive(Debug, Deserialize)]
+struct OsvRecord {
+    id: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    summary: Option<String>,
+    details: Option<String>,
+    withdrawn: Option<String>,
+    #[serde(default)]
+    severity: Vec<OsvSeverity>,
+    #[serde(default)]
+    affected: Vec<OsvAffected>,
+    #[serde(default)]
+    references: Vec<OsvReference>,
+    database_specific: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvSeverity {
+    #[serde(rename = "type")]
+    kind: String,
+    score: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvAffected {
+    package: OsvPackage,
+    #[serde(default)]
+    ranges: Vec<OsvRange>,
+    #[serde(default)]
+    versions: Vec<String>,
+    database_specific: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvPackage {
+    ecosystem: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvRange {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    events: Vec<OsvEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvEvent {
+    introduced: Option<String>,
+    fixed: Option<String>,
+    last_affected: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvReference {
+    #[serde(rename = "type")]
+    kind: String,
+    url: String,
+}
+
+/// Load OSV records from a file, directory or zip archive
+pub fn load_osv(path: &Path) -> Result<Vec<AdvisoryRecord>> {
+    if !path.exists() {
+        return Err(AuditError::config(format!(
+            "OSV advisory source not found at {}",
+            path.display()
+        )));
+    }
+
+    let mut records = Vec::new();
+
+    if path.is_dir() {
+        load_dir(path, &mut records)?;
+    } else if has_extension(path, "zip") {
+        load_zip(path, &mut records)?;
+    } else {
+        let content = fs::read_to_string(path)?;
+        records.extend(parse_osv(&content)?);
+    }
+
+    debug!("Loaded {} OSV advisories from {}", records.len(), path.display());
+    Ok(records)
+}
+
+fn load_dir(dir: &Path, records: &mut Vec<AdvisoryRecord>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            load_dir(&path, records)?;
+        } else if has_extension(&path, "json") {
+            let content = fs::read_to_string(&path)?;
+            match parse_osv(&content) {
+                Ok(parsed) => records.extend(parsed),
+                Err(e) => warn!("Failed to parse OSV record {}: {}", path.display(), e),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn load_zip(path: &Path, records: &mut Vec<AdvisoryRecord>) -> Result<()> {
+    let mut archive = zip::ZipArchive::new(File::open(path)?)
+        .map_err(|e| AuditError::parse(format!("Invalid OSV zip {}: {}", path.display(), e)))?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| AuditError::parse(format!("Invalid OSV zip {}: {}", path.display(), e)))?;
+        if !entry.is_file() || !entry.name().ends_with(".json") {
+            continue;
+        }
+
+        let mut content = String::new();
+        entry.read_to_string(&mut content)?;
+        match parse_osv(&content) {
+            Ok(parsed) => records.extend(parsed),
+            Err(e) => warn!("Failed to parse OSV record {}: {}", entry.name(), e),
+        }
+    }
+    Ok(())
+}
+
+/// Parse one OSV record into one advisory per affected crates.io package.
+///
+/// Withdrawn records produce no advisories.
+pub fn parse_osv(content: &str) -> Result<Vec<AdvisoryRecord>> {
+    let record: OsvRecord = serde_json::from_str(content)?;
+
+    if record.withdrawn.is_some() {
+        return Ok(Vec::new());
+    }
+
+    let cvss = record
+        .severity
+        .iter()
+        .find(|s| s.kind == "CVSS_V3")
+        .map(|s| s.score.clone());
+    let cvss_score = cvss.as_deref().and_then(super::cvss_base_score);
+    let severity = cvss_score
+        .map(Severity::from_cvss_score)
+        .or_else(|| database_severity(record.database_specific.as_ref()))
+        .unwrap_or(Severity::Unknown);
+
+    let title = record.summary.clone().or_else(|| {
+        record
+            .details
+            .as_deref()
+            .and_then(|d| d.lines().find(|l| !l.trim().is_empty()))
+            .map(|l| l.trim().to_string())
+    });
+
+    let url = record
+        .references
+        .iter()
+        .find(|r| r.kind == "ADVISORY")
+        .or_else(|| record.references.first())
+        .map(|r| r.url.clone());
+
+    let mut advisories = Vec::new();
+
+    for affected in &record.affected {
+        if affected.package.ecosystem != CRATES_IO_ECOSYSTEM {
+            continue;
+        }
+
+        let mut ranges = Vec::new();
+        let mut patched_versions = Vec::new();
+
+        for range in affected.ranges.iter().filter(|r| r.kind == "SEMVER") {
+            let mut events = Vec::new();
+            for event in &range.events {
+                if let Some(v) = &event.introduced {
+                    events.push(RangeEvent::Introduced(parse_version(v)?));
+                } else if let Some(v) = &event.fixed {
+                    events.push(RangeEvent::Fixed(parse_version(v)?));
+                    patched_versions.push(format!(">= {}", v));
+                } else if let Some(v) = &event.last_affected {
+                    events.push(RangeEvent::LastAffected(parse_version(v)?));
+                }
+            }
+            events.sort_by(|a, b| event_version(a).cmp(event_version(b)));
+            ranges.push(events);
+        }
+
+        let versions = affected
+            .versions
+            .iter()
+            .filter_map(|v| Version::parse(v).ok())
+            .collect();
+
+        let informational = affected
+            .database_specific
+            .as_ref()
+            .and_then(|d| d.get("informational"))
+            .and_then(|v| v.as_str())
+            .map(String::from);
+
+        advisories.push(AdvisoryRecord {
+            package: affected.package.name.clone(),
+            advisory: Advisory {
+                id: record.id.clone(),
+                source: AdvisorySource::Osv,
+                title: title.clone(),
+                severity,
+                cvss: cvss.clone(),
+                cvss_score,
+                aliases: record.aliases.clone(),
+                url: url.clone(),
+                patched_versions,
+            },
+            affected: AffectedVersions::Ranges { ranges, versions },
+            informational,
+            alternatives: record.details.as_deref().map(super::crate_links).unwrap_or_default(),
+        });
+    }
+
+    Ok(advisories)
+}
+
+/// OSV uses "0" for "all versions"; otherwise versions are full semver
+fn parse_version(v: &str) -> Result<Version> {
+    if v == "0" {
+        return Ok(Version::new(0, 0, 0));
+    }
+    Version::parse(v).map_err(|e| AuditError::parse(format!("Invalid OSV version '{}': {}", v, e)))
+}
+
+fn event_version(event: &RangeEvent) -> &Version {
+    match event {
+        RangeEvent::Introduced(v) | RangeEvent::Fixed(v) | RangeEvent::LastAffected(v) => v,
+    }
+}
+
+/// GHSA-style `database_specific.severity` ("LOW", "MODERATE", "HIGH", "CRITICAL")
+fn database_severity(database_specific: Option<&serde_json::Value>) -> Option<Severity> {
+    let severity = database_specific?.get("severity")?.as_str()?;
+    match severity.to_ascii_uppercase().as_str() {
+        "LOW" => Some(Severity::Low),
+        "MODERATE" | "MEDIUM" => Some(Severity::Medium),
+        "HIGH" => Some(Severity::High),
+        "CRITICAL" => Some(Severity::Critical),
+        _ => None,
+    }
+}
+
+fn has_extension(path: &Path, ext: &str) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some(ext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const SAMPLE: &str = r#"{
+        "id": "INTERNAL-2024-0001",
+        "aliases": ["CVE-2024-0001"],
+        "summary": "Heap overflow in frobnicate",
+        "severity": [{"type": "CVSS_V3", "score": "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"}],
+        "affected": [
+            {
+                "package": {"ecosystem": "crates.io", "name": "frob"},
+                "ranges": [{"type": "SEMVER", "events": [{"introduced": "0"}, {"fixed": "1.4.2"}]}]
+            },
+            {
+                "package": {"ecosystem": "npm", "name": "frob"},
+                "ranges": [{"type": "SEMVER", "events": [{"introduced": "0"}]}]
+            }
+        ],
+        "references": [{"type": "WEB", "url": "https://example.com/frob"}]
+    }"#;
+
+    #[test]
+    fn test_parse_osv() {
+        let records = parse_osv(SAMPLE).unwrap();
+        assert_eq!(records.len(), 1);
+
+        let record = &records[0];
+        assert_eq!(record.package, "frob");
+        assert_eq!(record.advisory.severity, Severity::Critical);
+        assert_eq!(record.advisory.patched_versions, vec![">= 1.4.2"]);
+        assert_eq!(record.advisory.url.as_deref(), Some("https://example.com/frob"));
+        assert!(record.affects(&Version::parse("1.4.1").unwrap()));
+        assert!(!record.affects(&Version::parse("1.4.2").unwrap()));
+    }
+
+    #[test]
+    fn test_load_osv_zip() {
+        let dir = tempfile::tempdir().unwrap();
+        let zip_path = dir.path().join("all.zip");
+
+        let mut writer = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        writer
+            .start_file::<_, ()>("INTERNAL-2024-0001.json", Default::default())
+            .unwrap();
+        writer.write_all(SAMPLE.as_bytes()).unwrap();
+        writer.finish().unwrap();
+
+        let records = load_osv(&zip_path).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].advisory.id, "INTERNAL-2024-0001");
+    }
+}
@@ -0,0 +1,235 @@
+//! Load advisories from a local clone of the RustSec advisory-db
+//!
+//! Advisories live under `crates/<name>/RUSTSEC-YYYY-NNNN.md`, with the
+//! machine-readable part in a fenced TOML front-matter block followed by a
+//! Markdown description whose first heading is the title.
+
+use super::{AdvisoryRecord, AffectedVersions};
+use crate::error::{AuditError, Result};
+use crate::types::{Advisory, AdvisorySource, Severity};
+use semver::VersionReq;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use tracing::{debug, warn};
+
+#[derive(Debug, Deserialize)]
+struct RustSecFile {
+    advisory: RustSecAdvisory,
+    #[serde(default)]
+    versions: RustSecVersions,
+}
+
+#[derive(Debug, Deserialize)]
+struct RustSecAdvisory {
+    id: String,
+    package: String,
+    title: Option<String>,
+    url: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    cvss: Option<String>,
+    informational: Option<String>,
+    withdrawn: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RustSecVersions {
+    #[serde(default)]
+    patched: Vec<String>,
+    #[serde(default)]
+    unaffected: Vec<String>,
+}
+
+/// Load every crate advisory from a RustSec advisory-db checkout
+pub fn load_rustsec_db(db_path: &Path) -> Result<Vec<AdvisoryRecord>> {
+    let crates_dir = db_path.join("crates");
+    if !crates_dir.is_dir() {
+        return Err(AuditError::config(format!(
+            "Not a RustSec advisory database (missing crates/ directory): {}",
+            db_path.display()
+        )));
+    }
+
+    let mut records = Vec::new();
+
+    for crate_entry in fs::read_dir(&crates_dir)? {
+        let crate_dir = crate_entry?.path();
+        if !crate_dir.is_dir() {
+            continue;
+        }
+
+        for entry in fs::read_dir(&crate_dir)? {
+            let path = entry?.path();
+            let is_advisory = matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("md") | Some("toml")
+            );
+            if !is_advisory {
+                continue;
+            }
+
+            let content = fs::read_to_string(&path)?;
+            match parse_advisory(&content) {
+                Ok(Some(record)) => records.push(record),
+                Ok(None) => debug!("Skipping withdrawn advisory {}", path.display()),
+                Err(e) => warn!("Failed to parse advisory {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    debug!(
+        "Loaded {} RustSec advisories from {}",
+        records.len(),
+        db_path.display()
+    );
+
+    Ok(records)
+}
+
+/// Parse a single advisory file (Markdown with TOML front matter, or legacy plain TOML).
+///
+/// Returns `None` for withdrawn advisories.
+pub fn parse_advisory(content: &str) -> Result<Option<AdvisoryRecord>> {
+    let (front_matter, body) = split_front_matter(content);
+    let file: RustSecFile = toml::from_str(front_matter)?;
+
+    if file.advisory.withdrawn.is_some() {
+        return Ok(None);
+    }
+
+    let title = file
+        .advisory
+        .title
+        .or_else(|| body.and_then(extract_title));
+
+    let cvss_score = file
+        .advisory
+        .cvss
+        .as_deref()
+        .and_then(super::cvss_base_score);
+    let severity = cvss_score
+        .map(Severity::from_cvss_score)
+        .unwrap_or(Severity::Unknown);
+
+    let patched = parse_requirements(&file.versions.patched)?;
+    let unaffected = parse_requirements(&file.versions.unaffected)?;
+
+    Ok(Some(AdvisoryRecord {
+        package: file.advisory.package,
+        advisory: Advisory {
+            id: file.advisory.id,
+            source: AdvisorySource::RustSec,
+            title,
+            severity,
+            cvss: file.advisory.cvss,
+            cvss_score,
+            aliases: file.advisory.aliases,
+            url: file.advisory.url,
+            patched_versions: file.versions.patched,
+        },
+        affected: AffectedVersions::Requirements { patched, unaffected },
+        informational: file.advisory.informational,
+        alternatives: body.map(super::crate_links).unwrap_or_default(),
+    }))
+}
+
+/// Split a Markdown advisory into its TOML front matter and body
+fn split_front_matter(content: &str) -> (&str, Option<&str>) {
+    let trimmed = content.trim_start();
+    let Some(rest) = trimmed.strip_prefix("```toml") else {
+        // Legacy advisories are plain TOML files
+        return (content, None);
+    };
+
+    match rest.find("\n```") {
+        Some(end) => (&rest[..end], Some(&rest[end + 4..])),
+        None => (rest, None),
+    }
+}
+
+/// Use the first Markdown heading of the body as the title
+fn extract_title(body: &str) -> Option<String> {
+    body.lines()
+        .map(str::trim)
+        .find_map(|line| line.strip_prefix("# "))
+        .map(|title| title.trim().to_string())
+}
+
+fn parse_requirements(reqs: &[String]) -> Result<Vec<VersionReq>> {
+    reqs.iter()
+        .map(|req| {
+            VersionReq::parse(req)
+                .map_err(|e| AuditError::parse(format!("Invalid version requirement '{}': {}", req, e)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semver::Version;
+
+    const SAMPLE: &str = r#"```toml
+[advisory]
+id = "RUSTSEC-2021-0078"
+package = "hyper"
+date = "2021-07-07"
+url = "https://github.com/hyperium/hyper/security/advisories/GHSA-6hfq-h8hq-87mf"
+categories = ["format-injection"]
+aliases = ["CVE-2021-32715", "GHSA-6hfq-h8hq-87mf"]
+cvss = "CVSS:3.1/AV:N/AC:H/PR:N/UI:N/S:U/C:N/I:L/A:N"
+
+[versions]
+patched = [">= 0.14.10"]
+unaffected = ["< 0.12.0"]
+```
+
+# Lenient `hyper` header parsing of `Content-Length` could allow request smuggling
+
+Details follow.
+"#;
+
+    #[test]
+    fn test_parse_markdown_advisory() {
+        let record = parse_advisory(SAMPLE).unwrap().unwrap();
+        assert_eq!(record.package, "hyper");
+        assert_eq!(record.advisory.id, "RUSTSEC-2021-0078");
+        assert_eq!(record.advisory.aliases.len(), 2);
+        assert_eq!(record.advisory.cvss_score, Some(3.7));
+        assert_eq!(record.advisory.severity, Severity::Low);
+        assert_eq!(
+            record.advisory.title.as_deref(),
+            Some("Lenient `hyper` header parsing of `Content-Length` could allow request smuggling")
+        );
+
+        assert!(record.affects(&Version::parse("0.14.9").unwrap()));
+        assert!(!record.affects(&Version::parse("0.14.10").unwrap()));
+        assert!(!record.affects(&Version::parse("0.11.27").unwrap()));
+    }
+
+    #[test]
+    fn test_parse_withdrawn_advisory() {
+        let content = r#"
+[advisory]
+id = "RUSTSEC-2020-0000"
+package = "foo"
+withdrawn = "2020-02-02"
+"#;
+        assert!(parse_advisory(content).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_load_rustsec_db() {
+        let dir = tempfile::tempdir().unwrap();
+        let crate_dir = dir.path().join("crates").join("hyper");
+        fs::create_dir_all(&crate_dir).unwrap();
+        fs::write(crate_dir.join("RUSTSEC-2021-0078.md"), SAMPLE).unwrap();
+        fs::write(crate_dir.join("README.txt"), "not an advisory").unwrap();
+
+        let records = load_rustsec_db(dir.path()).unwrap();
+        assert_eq!(records.len(), 1);
+
+        assert!(load_rustsec_db(&dir.path().join("crates")).is_err());
+    }
+}
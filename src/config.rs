@@ -1,11 +1,13 @@
 //! Configuration for audit behavior and scoring heuristics
 
+use crate::types::BuildCapability;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::time::Duration;
 
 /// Main configuration for the audit process
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AuditConfig {
     /// Scoring weights for health calculation
     pub scoring_weights: ScoringWeights,
@@ -19,6 +21,101 @@ pub struct AuditConfig {
     pub network: NetworkConfig,
     /// Dependencies to ignore in the audit
     pub ignored_dependencies: HashSet<String>,
+    /// Vulnerability advisory sources
+    #[serde(default)]
+    pub advisories: AdvisoryConfig,
+    /// Offline metadata sources on the local machine
+    #[serde(default)]
+    pub local_sources: LocalSourcesConfig,
+    /// Rules that fail `secure-audit check` when broken
+    #[serde(default)]
+    pub policy: PolicyConfig,
+    /// Previous JSON report to detect crate ownership changes against
+    #[serde(default)]
+    pub baseline_report: Option<PathBuf>,
+    /// Verification of declared repositories against published packages
+    #[serde(default)]
+    pub provenance: ProvenanceConfig,
+    /// Typosquatting and placeholder crate detection
+    #[serde(default)]
+    pub squatting: SquattingConfig,
+    /// Unmaintained and deprecated crate detection
+    #[serde(default)]
+    pub deprecation: DeprecationConfig,
+}
+
+/// Typosquatting and placeholder crate detection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SquattingConfig {
+    /// Run the checks for registry dependencies
+    pub enabled: bool,
+    /// Crates with fewer total downloads and no repository are flagged
+    pub min_downloads: u64,
+    /// `.crate` archives up to this size (bytes) count as near-empty
+    pub max_placeholder_size: u64,
+    /// Crates that are never flagged (e.g. internal crates named like popular ones)
+    pub allowed_crates: HashSet<String>,
+}
+
+impl Default for SquattingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_downloads: 1000,
+            max_placeholder_size: 2048,
+            allowed_crates: HashSet::new(),
+        }
+    }
+}
+
+/// Unmaintained and deprecated crate detection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeprecationConfig {
+    /// Mark explicitly abandoned or superseded crates as `Deprecated`
+    pub enabled: bool,
+    /// Look for deprecation notices at the top of the package README
+    pub scan_readme: bool,
+    /// Treat archived repositories as a deprecation signal
+    pub archived_repositories: bool,
+}
+
+impl Default for DeprecationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            scan_readme: true,
+            archived_repositories: true,
+        }
+    }
+}
+
+/// Repository provenance verification
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProvenanceConfig {
+    /// Check that each registry crate's repository contains it and that the
+    /// published files match the tagged or recorded commit. Costs one or two
+    /// forge API requests per dependency.
+    pub enabled: bool,
+}
+
+/// Policy rules evaluated for every dependency
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PolicyConfig {
+    /// Minimum score (0-10) per OpenSSF Scorecard check, e.g. `Dangerous-Workflow = 10`.
+    /// Inconclusive checks and repositories without a scorecard are not judged.
+    pub min_scorecard_checks: HashMap<String, i32>,
+    /// Build script capabilities that fail the check wherever they appear
+    pub deny_build_script_capabilities: Vec<BuildCapability>,
+    /// Build script capabilities that fail the check when the crate didn't have
+    /// them in the baseline report; crates added since count as having had none.
+    /// Audits without a baseline and build scripts it couldn't analyze are not judged.
+    pub deny_new_build_script_capabilities: Vec<BuildCapability>,
+    /// Fail the check when a crate in the baseline report gained a build script since then
+    pub deny_new_build_scripts: bool,
 }
 
 /// Weights for different components of the health score
@@ -34,6 +131,14 @@ pub struct ScoringWeights {
     pub stability: f32,
     /// Weight for security score (0.0-1.0)
     pub security: f32,
+    /// Share of the overall score given to `unsafe` usage when the sources were
+    /// analyzed (0.0-1.0); the other components are scaled down to make room
+    #[serde(default = "default_unsafe_weight")]
+    pub unsafe_code: f32,
+}
+
+fn default_unsafe_weight() -> f32 {
+    0.10
 }
 
 /// Thresholds for determining staleness
@@ -82,19 +187,185 @@ pub struct NetworkConfig {
     pub github_token: Option<String>,
     /// GitLab API token (optional)
     pub gitlab_token: Option<String>,
+    /// Codeberg API token (optional)
+    #[serde(default)]
+    pub gitea_token: Option<String>,
     /// Enable OpenSSF Scorecard checks (requires network)
     pub enable_openssf: bool,
+    /// Where crates.io metadata is read from
+    #[serde(default)]
+    pub crates_io_backend: CratesIoBackend,
+    /// How GitHub repository data is fetched
+    #[serde(default)]
+    pub github_backend: GitHubBackend,
+    /// Self-hosted forges (GitHub Enterprise, GitLab, Gitea/Forgejo) keyed by repository host
+    #[serde(default)]
+    pub forge_hosts: Vec<ForgeHost>,
+    /// On-disk cache of API responses
+    #[serde(default)]
+    pub cache: CacheConfig,
+    /// Never touch the network: metadata comes only from the response cache
+    /// (whatever its age), the local registry index and local git checkouts
+    #[serde(default)]
+    pub offline: bool,
+}
+
+/// On-disk cache of API responses, keyed by endpoint
+///
+/// Entries younger than their source's TTL are used as-is; older ones are
+/// revalidated with `If-None-Match`, which GitHub doesn't count against the
+/// rate limit. Lookups that can't change (a repository tree or file at a
+/// commit hash) never expire.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Read and write the cache
+    pub enabled: bool,
+    /// Cache directory (defaults to `rust_secure_dependency_audit` in the platform cache directory)
+    pub dir: Option<PathBuf>,
+    /// TTL of crates.io and alternative registry responses, in seconds
+    pub crates_io_ttl_secs: u64,
+    /// TTL of GitHub responses, in seconds
+    pub github_ttl_secs: u64,
+    /// TTL of GitLab responses, in seconds
+    pub gitlab_ttl_secs: u64,
+    /// TTL of Gitea/Forgejo/Codeberg responses, in seconds
+    pub gitea_ttl_secs: u64,
+    /// TTL of OpenSSF Scorecard results, in seconds (scorecards are recomputed weekly)
+    pub openssf_ttl_secs: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dir: None,
+            crates_io_ttl_secs: 24 * 60 * 60,
+            github_ttl_secs: 24 * 60 * 60,
+            gitlab_ttl_secs: 24 * 60 * 60,
+            gitea_ttl_secs: 24 * 60 * 60,
+            openssf_ttl_secs: 7 * 24 * 60 * 60,
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Cache directory, if one is configured or the platform has one
+    pub fn directory(&self) -> Option<PathBuf> {
+        self.dir
+            .clone()
+            .or_else(|| dirs::cache_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME"))))
+    }
+}
+
+/// API used for GitHub repository metadata
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GitHubBackend {
+    /// One GraphQL query per 100 repositories (needs a token; falls back to REST without one)
+    #[default]
+    GraphQl,
+    /// Several REST calls per repository
+    Rest,
+}
+
+/// Kind of API a repository host speaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeKind {
+    /// GitHub.com or GitHub Enterprise Server (REST v3)
+    GitHub,
+    /// GitLab.com or a self-managed GitLab instance (REST v4)
+    GitLab,
+    /// Codeberg or a self-hosted Gitea/Forgejo instance (REST v1)
+    Gitea,
+}
+
+/// A self-hosted repository host and the API serving it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForgeHost {
+    /// Host name as it appears in repository URLs (e.g. `gitlab.corp.example`)
+    pub host: String,
+    /// API flavour of the host
+    pub kind: ForgeKind,
+    /// API base URL. Defaults to `https://<host>/api/v3` for GitHub Enterprise,
+    /// `https://<host>/api/v4` for GitLab and `https://<host>/api/v1` for Gitea.
+    #[serde(default)]
+    pub api_url: Option<String>,
+    /// Token for this host only; the global `github_token`/`gitlab_token`/`gitea_token` are never sent here
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+impl ForgeHost {
+    /// API base URL without a trailing slash
+    pub fn api_base(&self) -> String {
+        match &self.api_url {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => match self.kind {
+                ForgeKind::GitHub => format!("https://{}/api/v3", self.host),
+                ForgeKind::GitLab => format!("https://{}/api/v4", self.host),
+                ForgeKind::Gitea => format!("https://{}/api/v1", self.host),
+            },
+        }
+    }
+}
+
+/// Source of crates.io crate metadata
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CratesIoBackend {
+    /// One request per crate to the crates.io HTTP API
+    #[default]
+    Api,
+    /// A downloaded crates.io database dump (`local_sources.db_dump_path`)
+    DbDump,
+    /// The local registry index and package manifests only; the API is asked
+    /// about crates the index doesn't know. Downloads and owners are unknown.
+    RegistryIndex,
+}
+
+/// Vulnerability advisory configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdvisoryConfig {
+    /// Path to a local clone of the RustSec advisory-db.
+    /// Falls back to `$CARGO_HOME/advisory-db` (where `cargo audit` keeps it) when unset.
+    pub rustsec_db_path: Option<PathBuf>,
+    /// OSV JSON sources: directories, single `.json` files or `.zip` archives
+    pub osv_paths: Vec<PathBuf>,
+    /// Advisory IDs or aliases (e.g. CVE numbers) to ignore
+    pub ignored_advisories: HashSet<String>,
 }
 
-impl Default for AuditConfig {
+/// Offline metadata sources read from the local machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LocalSourcesConfig {
+    /// Read version counts, yanked flags and latest versions from the local Cargo registry index
+    pub use_registry_index: bool,
+    /// Registry index directories to read (defaults to the crates.io indexes under `$CARGO_HOME/registry/index`)
+    pub registry_index_paths: Vec<PathBuf>,
+    /// Path to a crates.io `db-dump.tar.gz`, used when `network.crates_io_backend = "db-dump"`
+    pub db_dump_path: Option<PathBuf>,
+    /// Where to keep the lookup index built from the dump (defaults to `<dump>.index/`).
+    /// Must be new, empty or a previous dump index: it is wiped on rebuild.
+    pub db_dump_index_dir: Option<PathBuf>,
+    /// Read commit history from local checkouts of git and path dependencies
+    pub inspect_local_git: bool,
+    /// Parse each dependency's unpacked sources and count `unsafe` code
+    pub analyze_unsafe: bool,
+}
+
+impl Default for LocalSourcesConfig {
     fn default() -> Self {
         Self {
-            scoring_weights: ScoringWeights::default(),
-            staleness_thresholds: StalenessThresholds::default(),
-            license_policy: LicensePolicy::default(),
-            footprint_thresholds: FootprintThresholds::default(),
-            network: NetworkConfig::default(),
-            ignored_dependencies: HashSet::new(),
+            use_registry_index: true,
+            registry_index_paths: Vec::new(),
+            db_dump_path: None,
+            db_dump_index_dir: None,
+            inspect_local_git: true,
+            analyze_unsafe: false,
         }
     }
 }
@@ -107,6 +378,7 @@ impl Default for ScoringWeights {
             community: 0.15,
             stability: 0.10,
             security: 0.15,
+            unsafe_code: default_unsafe_weight(),
         }
     }
 }
@@ -121,6 +393,12 @@ impl ScoringWeights {
                 sum
             ));
         }
+        if !(0.0..=1.0).contains(&self.unsafe_code) {
+            return Err(format!(
+                "Unsafe code weight must be between 0.0 and 1.0, got {}",
+                self.unsafe_code
+            ));
+        }
         Ok(())
     }
 
@@ -175,7 +453,13 @@ impl Default for NetworkConfig {
             request_delay_ms: 100,
             github_token: std::env::var("GITHUB_TOKEN").ok(),
             gitlab_token: std::env::var("GITLAB_TOKEN").ok(),
+            gitea_token: std::env::var("CODEBERG_TOKEN").ok(),
             enable_openssf: true,
+            crates_io_backend: CratesIoBackend::default(),
+            github_backend: GitHubBackend::default(),
+            forge_hosts: Vec::new(),
+            cache: CacheConfig::default(),
+            offline: false,
         }
     }
 }
@@ -192,6 +476,13 @@ impl NetworkConfig {
     }
 }
 
+/// Locate Cargo's home directory (`$CARGO_HOME`, or `~/.cargo`)
+pub(crate) fn cargo_home() -> Option<PathBuf> {
+    std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")))
+}
+
 impl AuditConfig {
     /// Create a new builder for AuditConfig
     pub fn builder() -> AuditConfigBuilder {
@@ -208,6 +499,13 @@ pub struct AuditConfigBuilder {
     footprint_thresholds: Option<FootprintThresholds>,
     network: Option<NetworkConfig>,
     ignored_dependencies: HashSet<String>,
+    advisories: Option<AdvisoryConfig>,
+    local_sources: Option<LocalSourcesConfig>,
+    policy: Option<PolicyConfig>,
+    baseline_report: Option<PathBuf>,
+    provenance: Option<ProvenanceConfig>,
+    squatting: Option<SquattingConfig>,
+    deprecation: Option<DeprecationConfig>,
 }
 
 impl AuditConfigBuilder {
@@ -241,6 +539,41 @@ impl AuditConfigBuilder {
         self
     }
 
+    pub fn advisories(mut self, advisories: AdvisoryConfig) -> Self {
+        self.advisories = Some(advisories);
+        self
+    }
+
+    pub fn local_sources(mut self, local_sources: LocalSourcesConfig) -> Self {
+        self.local_sources = Some(local_sources);
+        self
+    }
+
+    pub fn policy(mut self, policy: PolicyConfig) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    pub fn baseline_report(mut self, path: PathBuf) -> Self {
+        self.baseline_report = Some(path);
+        self
+    }
+
+    pub fn provenance(mut self, provenance: ProvenanceConfig) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    pub fn squatting(mut self, squatting: SquattingConfig) -> Self {
+        self.squatting = Some(squatting);
+        self
+    }
+
+    pub fn deprecation(mut self, deprecation: DeprecationConfig) -> Self {
+        self.deprecation = Some(deprecation);
+        self
+    }
+
     pub fn build(self) -> AuditConfig {
         AuditConfig {
             scoring_weights: self.scoring_weights.unwrap_or_default(),
@@ -249,6 +582,13 @@ impl AuditConfigBuilder {
             footprint_thresholds: self.footprint_thresholds.unwrap_or_default(),
             network: self.network.unwrap_or_default(),
             ignored_dependencies: self.ignored_dependencies,
+            advisories: self.advisories.unwrap_or_default(),
+            local_sources: self.local_sources.unwrap_or_default(),
+            policy: self.policy.unwrap_or_default(),
+            baseline_report: self.baseline_report,
+            provenance: self.provenance.unwrap_or_default(),
+            squatting: self.squatting.unwrap_or_default(),
+            deprecation: self.deprecation.unwrap_or_default(),
         }
     }
 }
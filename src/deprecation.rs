@@ -0,0 +1,421 @@
+//! Unmaintained and deprecated crate detection
+//!
+//! Activity metrics lag behind reality: an abandoned crate keeps its stars and
+//! download counts for years. Abandonment is usually announced explicitly,
+//! though: in a RustSec `unmaintained` advisory, the manifest's maintenance
+//! badge, the crate description, a notice at the top of the README, or by
+//! archiving the repository. Any of these marks the crate as deprecated, and
+//! the first replacement a signal names is suggested.
+
+use crate::advisory::AdvisoryRecord;
+use crate::config::DeprecationConfig;
+use crate::metadata::CrateMetadata;
+use crate::types::{Deprecation, DeprecationKind, DeprecationSignal};
+use cargo_metadata::Package;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+/// Only the top of the README is scanned; notices further down are rarely about the crate itself
+const README_BYTES: u64 = 2048;
+
+/// Words that open a deprecation notice line ("**DEPRECATED**: ...", "No longer maintained.")
+const NOTICE_STARTS: &[&[&str]] = &[
+    &["deprecated"],
+    &["deprecation"],
+    &["unmaintained"],
+    &["no", "longer", "maintained"],
+];
+
+/// Subjects of "this crate is deprecated" sentences (the subject itself is optional)
+const SUBJECTS: &[&str] = &["crate", "project", "library", "repository", "repo", "package"];
+
+/// What follows "this crate" in a deprecation notice
+const STATUS_PHRASES: &[&str] = &[
+    "is deprecated",
+    "is now deprecated",
+    "has been deprecated",
+    "is unmaintained",
+    "is now unmaintained",
+    "is not maintained",
+    "is not actively maintained",
+    "is no longer maintained",
+    "is no longer actively maintained",
+    "is no longer being maintained",
+    "is no longer developed",
+    "is no longer supported",
+    "is abandoned",
+    "has been abandoned",
+    "is archived",
+    "has been archived",
+    "is discontinued",
+    "has been discontinued",
+    "is obsolete",
+    "is superseded",
+    "has been superseded",
+    "has been replaced",
+    "has been renamed",
+    "has moved",
+];
+
+/// Phrases followed by the name of the replacement
+const REPLACEMENT_PHRASES: &[&[&str]] = &[
+    &["in", "favor", "of"],
+    &["in", "favour", "of"],
+    &["superseded", "by"],
+    &["replaced", "by"],
+    &["renamed", "to"],
+    &["moved", "to"],
+    &["migrate", "to"],
+    &["switch", "to"],
+    &["successor", "is"],
+];
+
+/// Words that can't be a replacement crate
+const NOT_A_CRATE: &[&str] = &[
+    "this", "that", "it", "its", "our", "my", "your", "new", "another", "other", "something",
+    "instead", "crate", "crates", "library", "version", "please",
+];
+
+/// Combine all deprecation signals for a dependency.
+///
+/// Returns `None` when nothing indicates the crate is deprecated.
+pub fn detect_deprecation(
+    name: &str,
+    package: Option<&Package>,
+    crate_meta: Option<&CrateMetadata>,
+    unmaintained: Option<&AdvisoryRecord>,
+    archived: bool,
+    config: &DeprecationConfig,
+) -> Option<Deprecation> {
+    if !config.enabled {
+        return None;
+    }
+
+    let mut signals = Vec::new();
+    let mut replacements: Vec<String> = Vec::new();
+
+    if let Some(record) = unmaintained {
+        let advisory = &record.advisory;
+        signals.push(DeprecationSignal {
+            kind: DeprecationKind::UnmaintainedAdvisory,
+            message: match &advisory.title {
+                Some(title) => format!("{}: {}", advisory.id, title),
+                None => format!("{}: crate is unmaintained", advisory.id),
+            },
+        });
+        replacements.extend(record.alternatives.iter().cloned());
+    }
+
+    let manifest_path = package.map(|p| p.manifest_path.as_std_path());
+    if manifest_path.and_then(maintenance_status).as_deref() == Some("deprecated") {
+        signals.push(DeprecationSignal {
+            kind: DeprecationKind::MaintenanceBadge,
+            message: "Maintenance badge status is \"deprecated\"".to_string(),
+        });
+    }
+
+    // The registry describes the newest release, which is where a final
+    // deprecation notice gets published; fall back to the locked version's manifest
+    let description = crate_meta
+        .and_then(|meta| meta.description.as_deref())
+        .or_else(|| package.and_then(|p| p.description.as_deref()));
+    if let Some((message, replacement)) = description.and_then(description_notice) {
+        signals.push(DeprecationSignal {
+            kind: DeprecationKind::Description,
+            message: format!("Description: \"{}\"", message),
+        });
+        replacements.extend(replacement);
+    }
+
+    if config.scan_readme {
+        let readme = package.and_then(read_readme);
+        if let Some((message, replacement)) = readme.as_deref().and_then(readme_notice) {
+            signals.push(DeprecationSignal {
+                kind: DeprecationKind::Readme,
+                message: format!("README: \"{}\"", message),
+            });
+            replacements.extend(replacement);
+        }
+    }
+
+    if archived && config.archived_repositories {
+        signals.push(DeprecationSignal {
+            kind: DeprecationKind::ArchivedRepository,
+            message: "Source repository is archived".to_string(),
+        });
+    }
+
+    if signals.is_empty() {
+        return None;
+    }
+    let replacement = replacements
+        .into_iter()
+        .find(|r| !r.eq_ignore_ascii_case(name));
+    Some(Deprecation { signals, replacement })
+}
+
+/// `[badges] maintenance = { status = "..." }` from a package manifest
+fn maintenance_status(manifest_path: &Path) -> Option<String> {
+    let manifest: toml::Table = fs::read_to_string(manifest_path).ok()?.parse().ok()?;
+    manifest
+        .get("badges")?
+        .get("maintenance")?
+        .get("status")?
+        .as_str()
+        .map(String::from)
+}
+
+/// First few KB of the package README, if it is on disk
+fn read_readme(package: &Package) -> Option<String> {
+    let package_dir = package.manifest_path.parent()?.as_std_path();
+    let path = match &package.readme {
+        Some(readme) => package_dir.join(readme.as_std_path()),
+        None => package_dir.join("README.md"),
+    };
+    let mut bytes = Vec::new();
+    File::open(path).ok()?.take(README_BYTES).read_to_end(&mut bytes).ok()?;
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// A deprecation notice in a crate description, with the replacement it names.
+///
+/// A description that opens with "use X instead" counts as a notice on its
+/// own; further in, it usually points helper crates at their facade
+/// ("Serde traits only -- use the `serde` crate instead").
+fn description_notice(description: &str) -> Option<(String, Option<String>)> {
+    let words = words(description);
+    let opens_with_use = starts_with(&words, &["use"]) || starts_with(&words, &["please", "use"]);
+    let is_notice = is_notice(&words, true)
+        || words.last().is_some_and(|w| w.lower == "deprecated")
+        || (opens_with_use && use_instead(&words).is_some());
+    if !is_notice {
+        return None;
+    }
+    Some((excerpt(description), replacement(&words)))
+}
+
+/// A deprecation notice line at the top of a README, with the replacement it names.
+///
+/// List items are never notices on their own (feature lists mention
+/// "deprecated" and "unmaintained" too). The replacement is looked for on the
+/// notice line first, then in the rest of the scanned text.
+fn readme_notice(readme: &str) -> Option<(String, Option<String>)> {
+    let line = readme.lines().find(|line| {
+        let is_list_item = ["- ", "* ", "+ "].iter().any(|marker| line.trim_start().starts_with(marker));
+        is_notice(&words(line), !is_list_item)
+    })?;
+    let replacement = replacement(&words(line)).or_else(|| replacement(&words(readme)));
+    Some((excerpt(line), replacement))
+}
+
+struct Word<'a> {
+    text: &'a str,
+    lower: String,
+}
+
+/// Split text into words, dropping Markdown punctuation around them
+fn words(text: &str) -> Vec<Word<'_>> {
+    text.split(|c: char| c.is_whitespace() || "()[]`*\"'<>|#".contains(c))
+        .map(|w| w.trim_matches(|c: char| !(c.is_alphanumeric() || c == '_')))
+        .filter(|w| !w.is_empty())
+        .map(|text| Word { text, lower: text.to_lowercase() })
+        .collect()
+}
+
+fn starts_with(words: &[Word], phrase: &[&str]) -> bool {
+    words.len() >= phrase.len() && words.iter().zip(phrase).all(|(w, p)| w.lower == *p)
+}
+
+/// Whether the words contain a deprecation notice. `opening` allows notices
+/// that consist of a leading "Deprecated"/"Unmaintained" marker.
+fn is_notice(words: &[Word], opening: bool) -> bool {
+    if opening && NOTICE_STARTS.iter().any(|phrase| starts_with(words, phrase)) {
+        return true;
+    }
+    // "deprecated in favor of x", "deprecated, use x"
+    let deprecated_for = words.iter().enumerate().any(|(i, word)| {
+        let rest = &words[i + 1..];
+        word.lower == "deprecated"
+            && [&["in", "favor"][..], &["in", "favour"], &["use"], &["please"]]
+                .iter()
+                .any(|phrase| starts_with(rest, phrase))
+    });
+    if deprecated_for {
+        return true;
+    }
+    // "this crate is no longer maintained", "this is deprecated". Without a
+    // subject only "is ..." counts: "this has been replaced" is usually about
+    // something the previous sentence mentioned.
+    words.iter().enumerate().any(|(i, word)| {
+        if word.lower != "this" {
+            return false;
+        }
+        let rest = &words[i + 1..];
+        let (has_subject, rest) = match rest.first() {
+            Some(w) if SUBJECTS.contains(&w.lower.as_str()) => (true, &rest[1..]),
+            _ => (false, rest),
+        };
+        STATUS_PHRASES
+            .iter()
+            .filter(|status| has_subject || status.starts_with("is "))
+            .any(|status| {
+                let phrase: Vec<&str> = status.split(' ').collect();
+                starts_with(rest, &phrase)
+            })
+    })
+}
+
+/// The replacement named by the text, if any
+fn replacement(words: &[Word]) -> Option<String> {
+    for i in 0..words.len() {
+        for phrase in REPLACEMENT_PHRASES {
+            if starts_with(&words[i..], phrase) {
+                if let Some((candidate, _)) = candidate(&words[i + phrase.len()..]) {
+                    return Some(candidate);
+                }
+            }
+        }
+    }
+    use_instead(words)
+}
+
+/// "use x instead", "use the x crate instead"
+fn use_instead(words: &[Word]) -> Option<String> {
+    words.iter().enumerate().find_map(|(i, word)| {
+        if word.lower != "use" {
+            return None;
+        }
+        let (candidate, next) = candidate(&words[i + 1..])?;
+        let mut rest = &words[i + 1 + next..];
+        if rest.first().is_some_and(|w| w.lower == "crate") {
+            rest = &rest[1..];
+        }
+        (rest.first()?.lower == "instead").then_some(candidate)
+    })
+}
+
+/// A crate name or Rust path at the start of `words` (after an article), and
+/// the index of the word following it
+fn candidate(words: &[Word]) -> Option<(String, usize)> {
+    let skip = words
+        .first()
+        .filter(|w| matches!(w.lower.as_str(), "the" | "a" | "an"))
+        .map_or(0, |_| 1);
+    let word = words.get(skip)?;
+    let valid = word.text.len() >= 2
+        && word.text.starts_with(|c: char| c.is_ascii_alphabetic())
+        && word
+            .text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':'))
+        && !NOT_A_CRATE.contains(&word.lower.as_str());
+    valid.then(|| (word.text.to_string(), skip + 1))
+}
+
+/// Trimmed text for a report, cut at 120 characters
+fn excerpt(text: &str) -> String {
+    let text = text.trim().trim_start_matches(['#', '>', ' ']);
+    match text.char_indices().nth(120) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advisory::AffectedVersions;
+    use crate::types::{Advisory, AdvisorySource, Severity};
+
+    #[test]
+    fn test_description_notice() {
+        let notice = |text| description_notice(text).map(|(_, replacement)| replacement);
+
+        assert_eq!(notice("DEPRECATED: use `once_cell` instead"), Some(Some("once_cell".to_string())));
+        assert_eq!(
+            notice("A macro for lazy statics. Deprecated in favor of std::sync::LazyLock."),
+            Some(Some("std::sync::LazyLock".to_string()))
+        );
+        assert_eq!(notice("Use the tokio crate instead"), Some(Some("tokio".to_string())));
+        assert_eq!(notice("This crate is no longer maintained"), Some(None));
+        assert_eq!(notice("Error handling library (deprecated)"), Some(None));
+
+        assert_eq!(notice("A lint that finds deprecated APIs"), None);
+        assert_eq!(notice("Lists items deprecated in Rust 1.50 and later"), None);
+        assert_eq!(notice("Fork of the unmaintained foo crate"), None);
+        assert_eq!(notice("Easy to use async runtime"), None);
+        assert_eq!(notice("Traits only, no derive -- use the `serde` crate instead"), None);
+    }
+
+    #[test]
+    fn test_readme_notice() {
+        let readme = "# foo\n\n[![Crates.io](https://img.shields.io/crates/v/foo.svg)](https://crates.io/crates/foo)\n\n> **⚠️ DEPRECATED**: this crate has been superseded by [`bar`](https://crates.io/crates/bar).\n";
+        let (message, replacement) = readme_notice(readme).unwrap();
+        assert!(message.contains("DEPRECATED"));
+        assert_eq!(replacement.as_deref(), Some("bar"));
+
+        let readme = "# foo\n\nThis project is no longer maintained.\n\nPlease switch to baz.\n";
+        assert_eq!(readme_notice(readme).unwrap().1.as_deref(), Some("baz"));
+
+        let readme = "# foo\n\nA maintained fork of the unmaintained `bar` crate. Use foo instead of bar.\n";
+        assert!(readme_notice(readme).is_none());
+        let readme = "# foo\n\nFeatures:\n- **Unmaintained**: flags abandoned crates\n\nRelease 1.0 used a custom type. This has been replaced with a built-in one.\n";
+        assert!(readme_notice(readme).is_none());
+    }
+
+    #[test]
+    fn test_detect_deprecation() {
+        let config = DeprecationConfig::default();
+        assert!(detect_deprecation("foo", None, None, None, false, &config).is_none());
+
+        let record = AdvisoryRecord {
+            package: "foo".to_string(),
+            advisory: Advisory {
+                id: "RUSTSEC-2020-0001".to_string(),
+                source: AdvisorySource::RustSec,
+                title: Some("foo is unmaintained".to_string()),
+                severity: Severity::Unknown,
+                cvss: None,
+                cvss_score: None,
+                aliases: Vec::new(),
+                url: None,
+                patched_versions: Vec::new(),
+            },
+            affected: AffectedVersions::Requirements {
+                patched: Vec::new(),
+                unaffected: Vec::new(),
+            },
+            informational: Some("unmaintained".to_string()),
+            alternatives: vec!["foo".to_string(), "bar".to_string()],
+        };
+        let deprecation = detect_deprecation("foo", None, None, Some(&record), true, &config).unwrap();
+        let kinds: Vec<_> = deprecation.signals.iter().map(|s| s.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![DeprecationKind::UnmaintainedAdvisory, DeprecationKind::ArchivedRepository]
+        );
+        assert_eq!(deprecation.replacement.as_deref(), Some("bar"));
+
+        let no_archived = DeprecationConfig {
+            archived_repositories: false,
+            ..DeprecationConfig::default()
+        };
+        assert!(detect_deprecation("foo", None, None, None, true, &no_archived).is_none());
+    }
+
+    #[test]
+    fn test_maintenance_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = dir.path().join("Cargo.toml");
+        fs::write(
+            &manifest,
+            "[package]\nname = \"foo\"\n\n[badges]\nmaintenance = { status = \"deprecated\" }\n",
+        )
+        .unwrap();
+        assert_eq!(maintenance_status(&manifest).as_deref(), Some("deprecated"));
+
+        fs::write(&manifest, "[package]\nname = \"foo\"\n").unwrap();
+        assert!(maintenance_status(&manifest).is_none());
+    }
+}
@@ -0,0 +1,163 @@
+//! Duplicate-version detection across the resolved dependency graph
+//!
+//! Cargo resolves semver-incompatible requirements on the same crate to
+//! separate packages, so `syn 1.x` and `syn 2.x` are compiled side by side.
+//! Each extra version also drags in its own dependencies; the cost reported
+//! here is the number of packages reachable from the older versions that the
+//! newest one doesn't already pull in — roughly what unifying on the newest
+//! version would remove from the build.
+
+use crate::types::{DuplicateCrate, DuplicateVersion};
+use cargo_metadata::Metadata;
+use semver::Version;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// A resolved package and the packages it depends on (by index)
+struct Node {
+    name: String,
+    version: Version,
+    deps: Vec<usize>,
+}
+
+/// Report every crate resolved at more than one version
+pub fn find_duplicates(metadata: &Metadata) -> Vec<DuplicateCrate> {
+    let Some(resolve) = &metadata.resolve else {
+        return Vec::new();
+    };
+
+    let packages: HashMap<_, _> = metadata.packages.iter().map(|p| (&p.id, p)).collect();
+    let index: HashMap<_, _> = resolve
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (&node.id, i))
+        .collect();
+
+    let nodes: Vec<Node> = resolve
+        .nodes
+        .iter()
+        .filter_map(|node| {
+            let package = packages.get(&node.id)?;
+            Some(Node {
+                name: package.name.clone(),
+                version: package.version.clone(),
+                deps: node.deps.iter().filter_map(|d| index.get(&d.pkg).copied()).collect(),
+            })
+        })
+        .collect();
+
+    // Nodes without a package entry would shift indices; cargo never emits them
+    if nodes.len() != resolve.nodes.len() {
+        return Vec::new();
+    }
+
+    duplicates_in(&nodes)
+}
+
+fn duplicates_in(nodes: &[Node]) -> Vec<DuplicateCrate> {
+    let mut by_name: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, node) in nodes.iter().enumerate() {
+        by_name.entry(&node.name).or_default().push(i);
+    }
+
+    let mut dependents: HashMap<usize, BTreeSet<String>> = HashMap::new();
+    for node in nodes {
+        for &dep in &node.deps {
+            dependents
+                .entry(dep)
+                .or_default()
+                .insert(format!("{} {}", node.name, node.version));
+        }
+    }
+
+    by_name
+        .into_iter()
+        .filter(|(_, ids)| ids.len() > 1)
+        .map(|(name, mut ids)| {
+            ids.sort_by(|a, b| nodes[*a].version.cmp(&nodes[*b].version));
+            let (newest, older) = ids.split_last().expect("at least two versions");
+
+            let kept = reachable(nodes, &[*newest]);
+            let duplicated_crates = reachable(nodes, older).difference(&kept).count();
+
+            let versions = ids
+                .iter()
+                .map(|id| DuplicateVersion {
+                    version: nodes[*id].version.to_string(),
+                    dependents: dependents
+                        .get(id)
+                        .map(|d| d.iter().cloned().collect())
+                        .unwrap_or_default(),
+                })
+                .collect();
+
+            DuplicateCrate {
+                name: name.to_string(),
+                versions,
+                duplicated_crates,
+            }
+        })
+        .collect()
+}
+
+/// Packages reachable from `roots`, including the roots themselves
+fn reachable(nodes: &[Node], roots: &[usize]) -> BTreeSet<usize> {
+    let mut seen = BTreeSet::new();
+    let mut stack = roots.to_vec();
+    while let Some(id) = stack.pop() {
+        if seen.insert(id) {
+            stack.extend(&nodes[id].deps);
+        }
+    }
+    seen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str, version: &str, deps: &[usize]) -> Node {
+        Node {
+            name: name.to_string(),
+            version: Version::parse(version).unwrap(),
+            deps: deps.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_duplicates_in() {
+        let nodes = vec![
+            node("app", "0.1.0", &[1, 2, 5]),
+            node("serde_derive", "1.0.0", &[3]),
+            node("thiserror-impl", "1.0.0", &[4]),
+            node("syn", "1.0.109", &[6, 7]),
+            node("syn", "2.0.50", &[6, 8]),
+            node("clap", "4.0.0", &[4]),
+            node("proc-macro2", "1.0.78", &[]),
+            node("quote", "0.6.13", &[]),
+            node("quote", "1.0.35", &[]),
+        ];
+
+        let duplicates = duplicates_in(&nodes);
+        assert_eq!(duplicates.len(), 2);
+
+        let quote = &duplicates[0];
+        assert_eq!(quote.name, "quote");
+        assert_eq!(quote.duplicated_crates, 1);
+
+        let syn = &duplicates[1];
+        assert_eq!(syn.name, "syn");
+        assert_eq!(syn.versions[0].version, "1.0.109");
+        assert_eq!(syn.versions[0].dependents, vec!["serde_derive 1.0.0"]);
+        assert_eq!(syn.versions[1].version, "2.0.50");
+        assert_eq!(syn.versions[1].dependents, vec!["clap 4.0.0", "thiserror-impl 1.0.0"]);
+        // syn 1.x and its quote 0.6; proc-macro2 is shared with syn 2.x
+        assert_eq!(syn.duplicated_crates, 2);
+    }
+
+    #[test]
+    fn test_no_duplicates() {
+        let nodes = vec![node("app", "0.1.0", &[1]), node("log", "0.4.20", &[])];
+        assert!(duplicates_in(&nodes).is_empty());
+    }
+}
@@ -46,6 +46,9 @@ pub enum AuditError {
 
     #[error("Dependency not found: {0}")]
     DependencyNotFound(String),
+
+    #[error("Not available offline: {0}")]
+    Offline(String),
 }
 
 #[derive(Debug)]
@@ -82,4 +85,9 @@ impl AuditError {
     pub fn config(msg: impl Into<String>) -> Self {
         Self::ConfigError(msg.into())
     }
+
+    /// Create an error for a request that offline mode doesn't allow
+    pub fn offline(what: impl Into<String>) -> Self {
+        Self::Offline(what.into())
+    }
 }
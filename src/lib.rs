@@ -5,6 +5,7 @@
 //! - **License analysis**: Identify license risks and compliance issues
 //! - **Footprint estimation**: Evaluate dependency bloat for embedded/mobile projects
 //! - **Risk assessment**: Detect stale, unmaintained, or risky dependencies
+//! - **Vulnerability matching**: Check versions against an offline RustSec advisory-db
 //!
 //! ## Quick Start
 //!
@@ -32,18 +33,41 @@
 //! - Comprehensive license categorization (SPDX)
 //! - CLI tool with multiple output formats (JSON, Markdown)
 
+mod advisory;
 mod audit;
+mod build_script;
 mod config;
+mod deprecation;
+mod duplicates;
 mod error;
 mod footprint;
 mod license;
 mod metadata;
+mod msrv;
+mod native;
+mod outdated;
+mod ownership;
 mod parser;
+mod policy;
+mod provenance;
+mod registries;
 mod scoring;
+mod squatting;
 mod types;
+mod unsafe_code;
 
 // Re-export public API
 pub use audit::audit_project;
-pub use config::{AuditConfig, FootprintThresholds, LicensePolicy, NetworkConfig, ScoringWeights, StalenessThresholds};
+pub use config::{
+    AdvisoryConfig, AuditConfig, CacheConfig, CratesIoBackend, DeprecationConfig, FootprintThresholds,
+    ForgeHost, ForgeKind, GitHubBackend, LicensePolicy, LocalSourcesConfig, NetworkConfig,
+    PolicyConfig, ProvenanceConfig, ScoringWeights, SquattingConfig, StalenessThresholds,
+};
 pub use error::{AuditError, Result};
-pub use types::{AuditReport, DependencyHealth, HealthStatus, LicenseRisk};
+pub use types::{
+    Advisory, AdvisorySource, AuditReport, BuildCapability, BuildScriptAnalysis, BundledLicense,
+    DependencyHealth, Deprecation, DeprecationKind, DeprecationSignal, DuplicateCrate,
+    DuplicateVersion, HealthStatus, LicenseRisk,
+    Msrv, MsrvSource, NativeCode, OpenSSFCheck, Outdated, OwnershipChange, OwnershipChangeKind, PolicyViolation, Provenance,
+    ProvenanceStatus, Severity, SquattingFinding, SquattingKind, UnsafeUsage,
+};
@@ -1,7 +1,7 @@
 //! License analysis and risk categorization
 
 use crate::config::LicensePolicy;
-use crate::types::LicenseRisk;
+use crate::types::{BundledLicense, LicenseRisk};
 
 /// Analyze license and determine risk level
 pub fn analyze_license(
@@ -46,15 +46,11 @@ pub fn analyze_license(
     
     // Generate warnings based on policy
     match risk {
-        LicenseRisk::Copyleft => {
-            if policy.warn_on_copyleft {
-                warnings.push(format!("Copyleft license detected: {}", license_str));
-            }
+        LicenseRisk::Copyleft if policy.warn_on_copyleft => {
+            warnings.push(format!("Copyleft license detected: {}", license_str));
         }
-        LicenseRisk::Unknown => {
-            if policy.warn_on_unknown {
-                warnings.push(format!("Unknown license: {}", license_str));
-            }
+        LicenseRisk::Unknown if policy.warn_on_unknown => {
+            warnings.push(format!("Unknown license: {}", license_str));
         }
         LicenseRisk::Proprietary => {
             warnings.push(format!("Proprietary license detected: {}", license_str));
@@ -65,6 +61,43 @@ pub fn analyze_license(
     (risk, warnings)
 }
 
+/// Fold licenses of bundled native code into a crate's license analysis.
+///
+/// Each bundled license runs through the same policy as the crate's own
+/// license; the riskier category wins. Licenses that can't be categorized
+/// are reported but don't lower the crate's risk assessment.
+pub fn analyze_bundled_licenses(
+    risk: LicenseRisk,
+    bundled: &[BundledLicense],
+    policy: &LicensePolicy,
+) -> (LicenseRisk, Vec<String>) {
+    let mut risk = risk;
+    let mut warnings = Vec::new();
+
+    for entry in bundled {
+        let (bundled_risk, bundled_warnings) = analyze_license(Some(&entry.license), policy);
+        let evidence = entry.files.first().map(String::as_str).unwrap_or("unknown file");
+        warnings.extend(
+            bundled_warnings
+                .into_iter()
+                .map(|w| format!("Bundled native code ({}): {}", evidence, w)),
+        );
+        if risk_rank(bundled_risk) > risk_rank(risk) {
+            risk = bundled_risk;
+        }
+    }
+
+    (risk, warnings)
+}
+
+fn risk_rank(risk: LicenseRisk) -> u8 {
+    match risk {
+        LicenseRisk::Unknown | LicenseRisk::Permissive => 0,
+        LicenseRisk::Copyleft => 1,
+        LicenseRisk::Proprietary => 2,
+    }
+}
+
 /// Categorize a license into risk levels
 fn categorize_license(license: &str) -> LicenseRisk {
     let license_lower = license.to_lowercase();
@@ -100,6 +133,10 @@ fn is_permissive(license: &str) -> bool {
         "wtfpl",
         "zlib",
         "boost",
+        "bsl-1.0",
+        "openssl",
+        "curl",
+        "public-domain",
     ];
     
     permissive.iter().any(|&p| license.contains(p))
@@ -194,11 +231,41 @@ mod tests {
 
     #[test]
     fn test_analyze_with_policy() {
-        let mut policy = LicensePolicy::default();
-        policy.warn_on_copyleft = true;
+        let policy = LicensePolicy {
+            warn_on_copyleft: true,
+            ..Default::default()
+        };
         
         let (risk, warnings) = analyze_license(Some("GPL-3.0"), &policy);
         assert_eq!(risk, LicenseRisk::Copyleft);
         assert!(!warnings.is_empty());
     }
+
+    #[test]
+    fn test_analyze_bundled_licenses() {
+        let policy = LicensePolicy {
+            warn_on_copyleft: true,
+            ..Default::default()
+        };
+        let bundled = vec![
+            BundledLicense {
+                license: "OpenSSL".to_string(),
+                files: vec!["openssl/LICENSE".to_string()],
+            },
+            BundledLicense {
+                license: "LGPL-2.1".to_string(),
+                files: vec!["vendor/libfoo/COPYING".to_string()],
+            },
+        ];
+
+        let (risk, warnings) = analyze_bundled_licenses(LicenseRisk::Permissive, &bundled, &policy);
+        assert_eq!(risk, LicenseRisk::Copyleft);
+        assert_eq!(
+            warnings,
+            vec!["Bundled native code (vendor/libfoo/COPYING): Copyleft license detected: LGPL-2.1"]
+        );
+
+        let (risk, _) = analyze_bundled_licenses(LicenseRisk::Permissive, &bundled[..1], &policy);
+        assert_eq!(risk, LicenseRisk::Permissive);
+    }
 }
@@ -0,0 +1,446 @@
+//! On-disk cache of API responses
+//!
+//! Each source gets its own directory (`crates-io/`, `github/`, ...) in which a
+//! response is stored under the SHA-256 of its endpoint: `<key>.json` holds the
+//! status, validators and fetch time, `<key>.body` the raw body. Entries older
+//! than the source's TTL are revalidated with `If-None-Match` when the server
+//! sent an ETag; a `304 Not Modified` refreshes the entry without a download.
+//! Only successful responses are stored, and cache I/O errors never fail a
+//! request.
+//!
+//! In offline mode entries are served whatever their age, and a miss fails
+//! with [`AuditError::Offline`] instead of going to the network.
+
+use crate::config::{CacheConfig, NetworkConfig};
+use crate::error::{AuditError, Result};
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, ETAG, IF_NONE_MATCH, LINK};
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tracing::debug;
+
+/// API a cached response came from, which decides its TTL and directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheSource {
+    /// crates.io and alternative registries
+    CratesIo,
+    GitHub,
+    GitLab,
+    Gitea,
+    OpenSsf,
+}
+
+impl CacheSource {
+    fn dir_name(self) -> &'static str {
+        match self {
+            Self::CratesIo => "crates-io",
+            Self::GitHub => "github",
+            Self::GitLab => "gitlab",
+            Self::Gitea => "gitea",
+            Self::OpenSsf => "openssf",
+        }
+    }
+
+    fn ttl(self, config: &CacheConfig) -> Duration {
+        let secs = match self {
+            Self::CratesIo => config.crates_io_ttl_secs,
+            Self::GitHub => config.github_ttl_secs,
+            Self::GitLab => config.gitlab_ttl_secs,
+            Self::Gitea => config.gitea_ttl_secs,
+            Self::OpenSsf => config.openssf_ttl_secs,
+        };
+        Duration::from_secs(secs)
+    }
+}
+
+/// How long an entry is used without revalidation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheTtl {
+    For(Duration),
+    /// The response can't change (e.g. a tree at a commit hash)
+    Forever,
+}
+
+/// Whether a revision is a full commit hash
+fn is_commit_hash(rev: &str) -> bool {
+    rev.len() == 40 && rev.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// An HTTP response with its body read, from the network or the cache
+#[derive(Debug)]
+pub struct CachedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+impl CachedResponse {
+    async fn read(response: reqwest::Response) -> reqwest::Result<Self> {
+        Ok(Self {
+            status: response.status(),
+            headers: response.headers().clone(),
+            body: response.bytes().await?.to_vec(),
+        })
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.body
+    }
+}
+
+/// Metadata stored next to a cached body
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    /// Endpoint the entry belongs to, for inspecting the cache by hand
+    key: String,
+    status: u16,
+    etag: Option<String>,
+    /// Kept for pagination counts (GitHub contributors)
+    link: Option<String>,
+    fetched_at: DateTime<Utc>,
+}
+
+impl CacheEntry {
+    fn is_fresh(&self, ttl: CacheTtl) -> bool {
+        match ttl {
+            CacheTtl::Forever => true,
+            CacheTtl::For(ttl) => chrono::Duration::from_std(ttl)
+                .is_ok_and(|ttl| Utc::now() - self.fetched_at < ttl),
+        }
+    }
+
+    fn to_response(&self, body: Vec<u8>) -> CachedResponse {
+        let mut headers = HeaderMap::new();
+        for (name, value) in [(ETAG, &self.etag), (LINK, &self.link)] {
+            if let Some(value) = value.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+                headers.insert(name, value);
+            }
+        }
+        CachedResponse {
+            status: StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK),
+            headers,
+            body,
+        }
+    }
+}
+
+/// Directory holding one source's cached responses
+#[derive(Debug, Clone)]
+pub struct CacheStore {
+    dir: PathBuf,
+    ttl: Duration,
+    offline: bool,
+}
+
+impl CacheStore {
+    /// The store for `source`, or `None` when caching is disabled or there is no cache directory
+    pub fn for_source(config: &NetworkConfig, source: CacheSource) -> Option<Self> {
+        if !config.cache.enabled {
+            return None;
+        }
+        let dir = config.cache.directory()?.join(source.dir_name());
+        Some(Self {
+            dir,
+            ttl: source.ttl(&config.cache),
+            offline: config.offline,
+        })
+    }
+
+    /// Cached body for `key` if it is younger than the source's TTL (or of any age offline)
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        self.read(key)
+            .filter(|(entry, _)| self.offline || entry.is_fresh(CacheTtl::For(self.ttl)))
+            .map(|(_, body)| body)
+    }
+
+    /// Store a body under `key`
+    pub fn put(&self, key: &str, body: &[u8]) {
+        let entry = CacheEntry {
+            key: key.to_string(),
+            status: StatusCode::OK.as_u16(),
+            etag: None,
+            link: None,
+            fetched_at: Utc::now(),
+        };
+        self.write(key, &entry, body);
+    }
+
+    fn paths(&self, key: &str) -> (PathBuf, PathBuf) {
+        let hash = ring::digest::digest(&ring::digest::SHA256, key.as_bytes());
+        let name: String = hash.as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+        (
+            self.dir.join(format!("{}.json", name)),
+            self.dir.join(format!("{}.body", name)),
+        )
+    }
+
+    fn read(&self, key: &str) -> Option<(CacheEntry, Vec<u8>)> {
+        let (meta_path, body_path) = self.paths(key);
+        let entry: CacheEntry = serde_json::from_slice(&fs::read(meta_path).ok()?).ok()?;
+        // Two endpoints with the same hash are vanishingly unlikely, but cheap to rule out
+        if entry.key != key {
+            return None;
+        }
+        let body = fs::read(body_path).ok()?;
+        Some((entry, body))
+    }
+
+    fn write(&self, key: &str, entry: &CacheEntry, body: &[u8]) {
+        let (meta_path, body_path) = self.paths(key);
+        let result = fs::create_dir_all(&self.dir)
+            .and_then(|_| write_atomic(&body_path, body))
+            .and_then(|_| write_atomic(&meta_path, &serde_json::to_vec(entry)?));
+        if let Err(e) = result {
+            debug!("Could not write cache entry for {}: {}", key, e);
+        }
+    }
+}
+
+/// Write through a temporary file so concurrent readers never see half a file
+fn write_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let unique = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let tmp = path.with_extension(format!("tmp{}-{}", std::process::id(), unique));
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, path)
+}
+
+/// HTTP client whose GET requests go through the response cache
+#[derive(Debug, Clone)]
+pub struct CachedClient {
+    client: Client,
+    store: Option<CacheStore>,
+    ttl: Duration,
+    offline: bool,
+}
+
+impl CachedClient {
+    pub fn new(client: Client, source: CacheSource, config: &NetworkConfig) -> Self {
+        Self {
+            client,
+            store: CacheStore::for_source(config, source),
+            ttl: source.ttl(&config.cache),
+            offline: config.offline,
+        }
+    }
+
+    /// The source's TTL
+    pub fn ttl(&self) -> CacheTtl {
+        CacheTtl::For(self.ttl)
+    }
+
+    /// TTL for content at `rev`: trees and files at a commit hash never change,
+    /// while branches and tags can move
+    pub fn revision_ttl(&self, rev: &str) -> CacheTtl {
+        if is_commit_hash(rev) {
+            CacheTtl::Forever
+        } else {
+            self.ttl()
+        }
+    }
+
+    /// GET with the source's TTL
+    pub async fn get(&self, url: &str) -> Result<CachedResponse> {
+        self.get_with(url, None, self.ttl()).await
+    }
+
+    /// GET with an explicit `Accept` header and TTL
+    pub async fn get_with(
+        &self,
+        url: &str,
+        accept: Option<&str>,
+        ttl: CacheTtl,
+    ) -> Result<CachedResponse> {
+        let key = match accept {
+            Some(accept) => format!("{} ({})", url, accept),
+            None => url.to_string(),
+        };
+        let cached = match self.store.as_ref().and_then(|store| store.read(&key)) {
+            Some((entry, body)) if self.offline || entry.is_fresh(ttl) => {
+                debug!("Cache hit for {} (fetched {})", url, entry.fetched_at);
+                return Ok(entry.to_response(body));
+            }
+            cached => cached,
+        };
+        if self.offline {
+            return Err(AuditError::offline(url));
+        }
+
+        let mut request = self.client.get(url);
+        if let Some(accept) = accept {
+            request = request.header(ACCEPT, accept);
+        }
+        if let Some(etag) = cached.as_ref().and_then(|(entry, _)| entry.etag.as_deref()) {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        let response = request.send().await?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let (Some(store), Some((mut entry, body))) = (&self.store, cached) {
+                debug!("Revalidated cached response for {}", url);
+                entry.fetched_at = Utc::now();
+                store.write(&key, &entry, &body);
+                return Ok(entry.to_response(body));
+            }
+        }
+
+        let response = CachedResponse::read(response).await?;
+        if let Some(store) = self.store.as_ref().filter(|_| response.status.is_success()) {
+            let header = |name: &HeaderName| {
+                response
+                    .headers
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .map(String::from)
+            };
+            let entry = CacheEntry {
+                key: key.clone(),
+                status: response.status.as_u16(),
+                etag: header(&ETAG),
+                link: header(&LINK),
+                fetched_at: Utc::now(),
+            };
+            store.write(&key, &entry, &response.body);
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached_client(dir: &Path, ttl_secs: u64, offline: bool) -> CachedClient {
+        let config = NetworkConfig {
+            cache: CacheConfig {
+                dir: Some(dir.to_path_buf()),
+                crates_io_ttl_secs: ttl_secs,
+                ..CacheConfig::default()
+            },
+            offline,
+            ..NetworkConfig::default()
+        };
+        CachedClient::new(Client::new(), CacheSource::CratesIo, &config)
+    }
+
+    #[test]
+    fn test_is_commit_hash() {
+        assert!(is_commit_hash("0123456789abcdef0123456789abcdef01234567"));
+        assert!(!is_commit_hash("main"));
+        assert!(!is_commit_hash("v1.0.0"));
+    }
+
+    #[tokio::test]
+    async fn test_fresh_entries_skip_the_network() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/crates/demo")
+            .with_body(r#"{"name":"demo"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = cached_client(dir.path(), 3600, false);
+        let url = format!("{}/crates/demo", server.url());
+        for _ in 0..2 {
+            let response = client.get(&url).await.unwrap();
+            assert_eq!(response.text(), r#"{"name":"demo"}"#);
+        }
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_stale_entries_are_revalidated_with_etag() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut server = mockito::Server::new_async().await;
+        let url = format!("{}/repos/foo/bar", server.url());
+
+        let first = server
+            .mock("GET", "/repos/foo/bar")
+            .with_header("etag", "\"v1\"")
+            .with_body("original")
+            .expect(1)
+            .create_async()
+            .await;
+        let client = cached_client(dir.path(), 0, false);
+        assert_eq!(client.get(&url).await.unwrap().text(), "original");
+        first.assert_async().await;
+
+        let revalidation = server
+            .mock("GET", "/repos/foo/bar")
+            .match_header("if-none-match", "\"v1\"")
+            .with_status(304)
+            .expect(1)
+            .create_async()
+            .await;
+        let response = client.get(&url).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.text(), "original");
+        revalidation.assert_async().await;
+
+        // Immutable lookups never revalidate
+        let response = client.get_with(&url, None, CacheTtl::Forever).await.unwrap();
+        assert_eq!(response.text(), "original");
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_not_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/missing")
+            .with_status(404)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = cached_client(dir.path(), 3600, false);
+        let url = format!("{}/missing", server.url());
+        for _ in 0..2 {
+            assert_eq!(client.get(&url).await.unwrap().status(), StatusCode::NOT_FOUND);
+        }
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_offline_serves_stale_entries_and_never_fetches() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/crates/demo")
+            .with_body("cached")
+            .expect(1)
+            .create_async()
+            .await;
+        let url = format!("{}/crates/demo", server.url());
+        cached_client(dir.path(), 0, false).get(&url).await.unwrap();
+
+        let offline = cached_client(dir.path(), 0, true);
+        assert_eq!(offline.get(&url).await.unwrap().text(), "cached");
+        let missing = format!("{}/crates/other", server.url());
+        assert!(matches!(offline.get(&missing).await, Err(AuditError::Offline(_))));
+        mock.assert_async().await;
+    }
+}
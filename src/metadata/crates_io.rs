@@ -1,10 +1,13 @@
 //! Fetch metadata from crates.io
 
+use super::cache::{CacheSource, CacheTtl, CachedClient, CachedResponse};
 use crate::error::{AuditError, Result};
 use crate::config::NetworkConfig;
-use chrono::{DateTime, Utc};
+use crate::types::{DailyDownloads, VersionDownloads};
+use chrono::{DateTime, NaiveDate, Utc};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::time::Duration;
 use tracing::{debug, warn};
 
@@ -26,11 +29,94 @@ pub struct CrateMetadata {
     pub homepage: Option<String>,
     pub downloads: u64,
     pub recent_downloads: Option<u64>,
-    pub created_at: DateTime<Utc>,
-    pub updated_at: DateTime<Utc>,
+    /// When the crate was first published; `None` when the data comes from a
+    /// registry index without publish times
+    pub created_at: Option<DateTime<Utc>>,
+    /// When this version was last updated, `None` under the same conditions
+    pub updated_at: Option<DateTime<Utc>>,
     pub version_count: u32,
-    pub authors: Vec<String>,
+    /// Size of this version's `.crate` archive in bytes, when the registry reports it
+    pub crate_size: Option<u64>,
+    /// Crate owners, or `None` when the registry didn't report them
+    pub owners: Option<CrateOwners>,
+    /// User who published this version, when the registry recorded it
+    pub published_by: Option<String>,
+    /// Users who published versions released before this one
+    pub previous_publishers: Vec<String>,
     pub is_yanked: bool,
+    /// Highest stable version published
+    pub latest_version: Option<String>,
+    /// When this version was published
+    #[serde(default)]
+    pub published_at: Option<DateTime<Utc>>,
+    /// When `latest_version` was published
+    #[serde(default)]
+    pub latest_published_at: Option<DateTime<Utc>>,
+    /// Reverse dependencies and recent download history
+    pub usage: CrateUsage,
+}
+
+#[cfg(test)]
+impl CrateMetadata {
+    /// A crate published just now with nothing else known, for tests to
+    /// override the fields they exercise
+    pub(crate) fn fixture(name: &str, version: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            description: None,
+            license: None,
+            repository: None,
+            homepage: None,
+            downloads: 0,
+            recent_downloads: None,
+            created_at: Some(Utc::now()),
+            updated_at: Some(Utc::now()),
+            version_count: 1,
+            crate_size: None,
+            owners: None,
+            published_by: None,
+            previous_publishers: Vec::new(),
+            is_yanked: false,
+            latest_version: None,
+            published_at: None,
+            latest_published_at: None,
+            usage: CrateUsage::default(),
+        }
+    }
+}
+
+/// How much the ecosystem uses a crate
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CrateUsage {
+    /// Number of crates depending on this crate
+    pub reverse_dependencies: Option<u64>,
+    /// Daily downloads across all versions over the last 90 days, oldest first
+    pub daily_downloads: Vec<DailyDownloads>,
+    /// Downloads per version over the last 90 days, most downloaded first
+    pub version_downloads: Vec<VersionDownloads>,
+}
+
+/// Users and teams allowed to publish a crate
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrateOwners {
+    /// User logins
+    pub users: Vec<String>,
+    /// Team logins (e.g. `github:serde-rs:publish`)
+    pub teams: Vec<String>,
+}
+
+impl CrateOwners {
+    /// Split owner logins into users and teams (team logins carry a `github:` style prefix)
+    pub fn from_logins(logins: impl IntoIterator<Item = String>) -> Self {
+        let (teams, users) = logins.into_iter().partition(|login| login.contains(':'));
+        Self { users, teams }
+    }
+
+    /// Number of owners, counting each team once
+    pub fn count(&self) -> u32 {
+        (self.users.len() + self.teams.len()) as u32
+    }
 }
 
 /// Response from crates.io API for crate info
@@ -42,6 +128,7 @@ struct CratesIoResponse {
 }
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 struct CrateInfo {
     name: String,
     description: Option<String>,
@@ -49,12 +136,22 @@ struct CrateInfo {
     homepage: Option<String>,
     downloads: u64,
     recent_downloads: Option<u64>,
+    max_stable_version: Option<String>,
     created_at: String,
     updated_at: String,
 }
 
+/// Response from crates.io API for a single version
 #[derive(Debug, Deserialize)]
+struct VersionResponse {
+    version: VersionInfo,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 struct VersionInfo {
+    #[serde(default)]
+    id: u64,
     #[serde(rename = "num")]
     version: String,
     license: Option<String>,
@@ -62,9 +159,63 @@ struct VersionInfo {
     updated_at: String,
     downloads: u64,
     #[serde(default)]
-    authors: Vec<String>,
-    #[serde(default)]
     yanked: bool,
+    #[serde(default)]
+    published_by: Option<OwnerInfo>,
+    #[serde(default)]
+    crate_size: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OwnerUsersResponse {
+    users: Vec<OwnerInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OwnerTeamsResponse {
+    teams: Vec<OwnerInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OwnerInfo {
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReverseDependenciesResponse {
+    meta: TotalMeta,
+}
+
+#[derive(Debug, Deserialize)]
+struct TotalMeta {
+    total: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct DownloadsResponse {
+    version_downloads: Vec<VersionDownloadEntry>,
+    #[serde(default)]
+    meta: Option<DownloadsMeta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionDownloadEntry {
+    version: u64,
+    downloads: u64,
+    date: NaiveDate,
+}
+
+/// Downloads of versions outside the per-version breakdown
+#[derive(Debug, Deserialize)]
+struct DownloadsMeta {
+    #[serde(default)]
+    extra_downloads: Vec<ExtraDownloadEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExtraDownloadEntry {
+    downloads: u64,
+    date: NaiveDate,
 }
 
 /// Fetch metadata for a crate from crates.io
@@ -73,36 +224,96 @@ pub async fn fetch_crate_metadata(
     version: &str,
     config: &NetworkConfig,
 ) -> Result<CrateMetadata> {
-    debug!("Fetching metadata for {} v{}", crate_name, version);
+    fetch_crate_metadata_from("crates.io", CRATES_IO_API, None, crate_name, version, config).await
+}
+
+/// Fetch metadata for a crate from any registry implementing the crates.io web API
+/// (`api_base` is the `.../api/v1` root; `token` is sent as-is in `Authorization`)
+pub async fn fetch_crate_metadata_from(
+    service: &str,
+    api_base: &str,
+    token: Option<&str>,
+    crate_name: &str,
+    version: &str,
+    config: &NetworkConfig,
+) -> Result<CrateMetadata> {
+    debug!("Fetching metadata for {} v{} from {}", crate_name, version, service);
 
-    let client = build_client(config)?;
-    let url = format!("{}/crates/{}", CRATES_IO_API, crate_name);
+    let client = build_client(config, token)?;
+    let url = format!("{}/crates/{}", api_base.trim_end_matches('/'), crate_name);
 
-    let response = retry_request(&client, &url, config.max_retries, config.request_delay()).await?;
+    let response = retry_request(
+        &client,
+        &url,
+        service,
+        config.max_retries,
+        config.request_delay(),
+        client.ttl(),
+    )
+    .await?;
 
     if !response.status().is_success() {
         if response.status().as_u16() == 404 {
             return Err(AuditError::DependencyNotFound(crate_name.to_string()));
         }
         return Err(AuditError::api(
-            "crates.io",
+            service,
             format!("HTTP {}: {}", response.status(), crate_name),
         ));
     }
 
-    let data: CratesIoResponse = response.json().await?;
+    let data: CratesIoResponse = response.json()?;
+
+    // A version missing from the list (e.g. published after a cached listing)
+    // is looked up on its own. It can still be yanked, so it expires like the rest
+    let pinned = if data.versions.iter().any(|v| v.version == version) {
+        None
+    } else {
+        let url = format!("{}/{}", url, version);
+        fetch_json_with::<VersionResponse>(&client, &url, service, config, client.ttl())
+            .await
+            .map(|response| response.version)
+            .ok()
+    };
 
     // Find the specific version or use the latest
     let version_info = data
         .versions
         .iter()
         .find(|v| v.version == version)
+        .or(pinned.as_ref())
         .or_else(|| data.versions.first())
         .ok_or_else(|| AuditError::parse("No versions found for crate"))?;
 
     let created_at = parse_datetime(&data.crate_info.created_at)?;
     let updated_at = parse_datetime(&version_info.updated_at)?;
 
+    let published_at = parse_datetime(&version_info.created_at)?;
+    let previous_publishers = collect_publishers(
+        data.versions
+            .iter()
+            .filter(|v| parse_datetime(&v.created_at).is_ok_and(|at| at < published_at))
+            .filter_map(|v| v.published_by.as_ref().map(|p| p.login.clone())),
+    );
+
+    let owners = match fetch_owners(&client, api_base, service, crate_name, config).await {
+        Ok(owners) => Some(owners),
+        Err(e) => {
+            // Some alternative registries don't implement the owner endpoints
+            debug!("Could not fetch owners of {} from {}: {}", crate_name, service, e);
+            None
+        }
+    };
+
+    let usage = fetch_usage(&client, api_base, service, crate_name, &data.versions, config).await;
+
+    let latest_published_at = data
+        .crate_info
+        .max_stable_version
+        .as_ref()
+        .and_then(|latest| data.versions.iter().find(|v| &v.version == latest))
+        .and_then(|v| parse_datetime(&v.created_at).ok());
+
     Ok(CrateMetadata {
         name: data.crate_info.name,
         version: version_info.version.clone(),
@@ -112,45 +323,184 @@ pub async fn fetch_crate_metadata(
         homepage: data.crate_info.homepage,
         downloads: data.crate_info.downloads,
         recent_downloads: data.crate_info.recent_downloads,
-        created_at,
-        updated_at,
+        created_at: Some(created_at),
+        updated_at: Some(updated_at),
         version_count: data.versions.len() as u32,
-        authors: version_info.authors.clone(),
+        crate_size: version_info.crate_size,
+        owners,
+        published_by: version_info.published_by.as_ref().map(|p| p.login.clone()),
+        previous_publishers,
         is_yanked: version_info.yanked,
+        latest_version: data.crate_info.max_stable_version,
+        published_at: Some(published_at),
+        latest_published_at,
+        usage,
+    })
+}
+
+/// Deduplicate and sort publisher logins
+pub(super) fn collect_publishers(logins: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut publishers: Vec<String> = logins.into_iter().collect();
+    publishers.sort();
+    publishers.dedup();
+    publishers
+}
+
+/// Fetch the reverse-dependency count and 90-day download series.
+///
+/// Either part is left empty when its endpoint fails, as alternative
+/// registries rarely implement them.
+async fn fetch_usage(
+    client: &CachedClient,
+    api_base: &str,
+    service: &str,
+    crate_name: &str,
+    versions: &[VersionInfo],
+    config: &NetworkConfig,
+) -> CrateUsage {
+    let base = format!("{}/crates/{}", api_base.trim_end_matches('/'), crate_name);
+    let mut usage = CrateUsage::default();
+
+    match fetch_json::<ReverseDependenciesResponse>(
+        client,
+        &format!("{}/reverse_dependencies?per_page=1", base),
+        service,
+        config,
+    )
+    .await
+    {
+        Ok(response) => usage.reverse_dependencies = Some(response.meta.total),
+        Err(e) => debug!("Could not fetch reverse dependencies of {}: {}", crate_name, e),
+    }
+
+    match fetch_json::<DownloadsResponse>(client, &format!("{}/downloads", base), service, config).await {
+        Ok(response) => {
+            let numbers: HashMap<u64, &str> =
+                versions.iter().map(|v| (v.id, v.version.as_str())).collect();
+
+            let mut daily: BTreeMap<NaiveDate, u64> = BTreeMap::new();
+            let mut per_version: HashMap<&str, u64> = HashMap::new();
+            for entry in &response.version_downloads {
+                *daily.entry(entry.date).or_default() += entry.downloads;
+                if let Some(num) = numbers.get(&entry.version) {
+                    *per_version.entry(num).or_default() += entry.downloads;
+                }
+            }
+            for entry in response.meta.iter().flat_map(|m| &m.extra_downloads) {
+                *daily.entry(entry.date).or_default() += entry.downloads;
+            }
+
+            usage.daily_downloads = daily
+                .into_iter()
+                .map(|(date, downloads)| DailyDownloads { date, downloads })
+                .collect();
+            usage.version_downloads = per_version
+                .into_iter()
+                .map(|(version, downloads)| VersionDownloads {
+                    version: version.to_string(),
+                    downloads,
+                })
+                .collect();
+            usage
+                .version_downloads
+                .sort_by(|a, b| b.downloads.cmp(&a.downloads).then_with(|| a.version.cmp(&b.version)));
+        }
+        Err(e) => debug!("Could not fetch download history of {}: {}", crate_name, e),
+    }
+
+    usage
+}
+
+/// Fetch the user and team owners of a crate
+async fn fetch_owners(
+    client: &CachedClient,
+    api_base: &str,
+    service: &str,
+    crate_name: &str,
+    config: &NetworkConfig,
+) -> Result<CrateOwners> {
+    let base = format!("{}/crates/{}", api_base.trim_end_matches('/'), crate_name);
+
+    let users: OwnerUsersResponse =
+        fetch_json(client, &format!("{}/owner_user", base), service, config).await?;
+    let teams: OwnerTeamsResponse =
+        fetch_json(client, &format!("{}/owner_team", base), service, config).await?;
+
+    Ok(CrateOwners {
+        users: users.users.into_iter().map(|o| o.login).collect(),
+        teams: teams.teams.into_iter().map(|o| o.login).collect(),
     })
 }
 
+async fn fetch_json<T: serde::de::DeserializeOwned>(
+    client: &CachedClient,
+    url: &str,
+    service: &str,
+    config: &NetworkConfig,
+) -> Result<T> {
+    fetch_json_with(client, url, service, config, client.ttl()).await
+}
+
+async fn fetch_json_with<T: serde::de::DeserializeOwned>(
+    client: &CachedClient,
+    url: &str,
+    service: &str,
+    config: &NetworkConfig,
+    ttl: CacheTtl,
+) -> Result<T> {
+    let response =
+        retry_request(client, url, service, config.max_retries, config.request_delay(), ttl).await?;
+    if !response.status().is_success() {
+        return Err(AuditError::api(service, format!("HTTP {}: {}", response.status(), url)));
+    }
+    response.json()
+}
+
 /// Build HTTP client with proper configuration
-fn build_client(config: &NetworkConfig) -> Result<Client> {
-    Client::builder()
+pub(super) fn build_client(config: &NetworkConfig, token: Option<&str>) -> Result<CachedClient> {
+    let mut builder = Client::builder()
         .user_agent(USER_AGENT)
-        .timeout(config.timeout())
+        .timeout(config.timeout());
+
+    if let Some(token) = token {
+        let mut headers = reqwest::header::HeaderMap::new();
+        let value = token
+            .parse()
+            .map_err(|_| AuditError::config("Registry token contains invalid characters"))?;
+        headers.insert(reqwest::header::AUTHORIZATION, value);
+        builder = builder.default_headers(headers);
+    }
+
+    let client = builder
         .build()
-        .map_err(|e| AuditError::network(format!("Failed to build HTTP client: {}", e)))
+        .map_err(|e| AuditError::network(format!("Failed to build HTTP client: {}", e)))?;
+    Ok(CachedClient::new(client, CacheSource::CratesIo, config))
 }
 
 /// Retry a request with exponential backoff
-async fn retry_request(
-    client: &Client,
+pub(super) async fn retry_request(
+    client: &CachedClient,
     url: &str,
+    service: &str,
     max_retries: u32,
     base_delay: Duration,
-) -> Result<reqwest::Response> {
+    ttl: CacheTtl,
+) -> Result<CachedResponse> {
     let mut attempts = 0;
     let mut delay = base_delay;
 
     loop {
-        match client.get(url).send().await {
+        match client.get_with(url, None, ttl).await {
             Ok(response) => {
                 // Check for rate limiting
                 if response.status().as_u16() == 429 {
                     if attempts >= max_retries {
                         return Err(AuditError::RateLimitExceeded {
-                            service: "crates.io".to_string(),
+                            service: service.to_string(),
                             retry_after: Some(delay),
                         });
                     }
-                    warn!("Rate limited by crates.io, retrying after {:?}", delay);
+                    warn!("Rate limited by {}, retrying after {:?}", service, delay);
                     tokio::time::sleep(delay).await;
                     attempts += 1;
                     delay *= 2; // Exponential backoff
@@ -158,6 +508,7 @@ async fn retry_request(
                 }
                 return Ok(response);
             }
+            Err(e @ AuditError::Offline(_)) => return Err(e),
             Err(e) => {
                 if attempts >= max_retries {
                     return Err(AuditError::network(format!("Request failed: {}", e)));
@@ -181,6 +532,7 @@ fn parse_datetime(s: &str) -> Result<DateTime<Utc>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::CacheConfig;
 
     #[tokio::test]
     async fn test_fetch_serde_metadata() {
@@ -205,4 +557,86 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_fetch_crate_owners_and_usage() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/api/v1/crates/demo")
+            .with_body(
+                r#"{"crate":{"name":"demo","description":null,"repository":null,"homepage":null,
+                    "downloads":10,"recent_downloads":2,"max_stable_version":"1.0.0",
+                    "created_at":"2023-01-01T00:00:00Z","updated_at":"2024-01-01T00:00:00Z"},
+                   "versions":[{"id":30,"num":"1.0.0","license":"MIT","created_at":"2024-01-01T00:00:00Z",
+                    "updated_at":"2024-01-01T00:00:00Z","downloads":10,"yanked":false,
+                    "published_by":{"id":2,"login":"bob"}},
+                   {"id":20,"num":"0.9.0","license":"MIT","created_at":"2023-06-01T00:00:00Z",
+                    "updated_at":"2023-06-01T00:00:00Z","downloads":5,"yanked":false,
+                    "published_by":{"id":1,"login":"alice"}},
+                   {"num":"0.1.0","license":"MIT","created_at":"2023-01-01T00:00:00Z",
+                    "updated_at":"2023-01-01T00:00:00Z","downloads":5,"yanked":false,
+                    "published_by":null}]}"#,
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/v1/crates/demo/owner_user")
+            .with_body(r#"{"users":[{"id":1,"login":"alice","kind":"user"},{"id":2,"login":"bob","kind":"user"}]}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/v1/crates/demo/owner_team")
+            .with_body(r#"{"teams":[{"id":3,"login":"github:org:publish","kind":"team"}]}"#)
+            .create_async()
+            .await;
+
+        server
+            .mock("GET", "/api/v1/crates/demo/reverse_dependencies?per_page=1")
+            .with_body(r#"{"dependencies":[],"versions":[],"meta":{"total":42}}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/v1/crates/demo/downloads")
+            .with_body(
+                r#"{"version_downloads":[
+                    {"version":30,"downloads":7,"date":"2024-01-02"},
+                    {"version":20,"downloads":3,"date":"2024-01-02"},
+                    {"version":30,"downloads":5,"date":"2024-01-01"}],
+                   "meta":{"extra_downloads":[{"date":"2024-01-01","downloads":1}]}}"#,
+            )
+            .create_async()
+            .await;
+
+        let config = NetworkConfig {
+            cache: CacheConfig {
+                enabled: false,
+                ..CacheConfig::default()
+            },
+            ..NetworkConfig::default()
+        };
+        let api_base = format!("{}/api/v1", server.url());
+        let meta = fetch_crate_metadata_from("test", &api_base, None, "demo", "1.0.0", &config)
+            .await
+            .unwrap();
+        let owners = meta.owners.unwrap();
+        assert_eq!(owners.users, vec!["alice", "bob"]);
+        assert_eq!(owners.teams, vec!["github:org:publish"]);
+        assert_eq!(owners.count(), 3);
+        assert_eq!(meta.published_by.as_deref(), Some("bob"));
+        assert_eq!(meta.previous_publishers, vec!["alice"]);
+
+        assert_eq!(meta.usage.reverse_dependencies, Some(42));
+        let daily: Vec<u64> = meta.usage.daily_downloads.iter().map(|d| d.downloads).collect();
+        assert_eq!(daily, vec![6, 10]);
+        assert_eq!(meta.usage.version_downloads[0].version, "1.0.0");
+        assert_eq!(meta.usage.version_downloads[0].downloads, 12);
+    }
+
+    #[test]
+    fn test_owners_from_logins() {
+        let owners = CrateOwners::from_logins(vec!["alice".to_string(), "github:foo:maintainers".to_string()]);
+        assert_eq!(owners.users, vec!["alice"]);
+        assert_eq!(owners.teams, vec!["github:foo:maintainers"]);
+    }
+
 }
@@ -0,0 +1,550 @@
+//! Serve crate metadata from a crates.io database dump
+//!
+//! crates.io publishes a daily `db-dump.tar.gz` containing CSV exports of its
+//! tables. Parsing the full archive takes a while, so the first run joins the
+//! tables we need into one small JSON file per crate (sharded like the Cargo
+//! registry index) and later runs read only the files for audited crates.
+
+use super::crates_io::collect_publishers;
+use super::registry_index::index_path;
+use super::{CrateMetadata, CrateOwners, CrateUsage};
+use crate::error::{AuditError, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tracing::{debug, info};
+
+/// Marker written once an index build completes, describing the source dump
+const INDEX_INFO_FILE: &str = ".dump-info.json";
+
+/// `crate_owners.owner_kind` value for teams (users are 0)
+const OWNER_KIND_TEAM: i32 = 1;
+
+/// Joined per-crate record stored in the on-disk index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DumpCrate {
+    name: String,
+    description: Option<String>,
+    repository: Option<String>,
+    homepage: Option<String>,
+    downloads: u64,
+    recent_downloads: u64,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    /// User logins and team logins (`github:org:team`)
+    owners: Vec<String>,
+    versions: Vec<DumpVersion>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DumpVersion {
+    num: String,
+    created_at: DateTime<Utc>,
+    yanked: bool,
+    license: Option<String>,
+    downloads: u64,
+    published_by: Option<String>,
+    #[serde(default)]
+    crate_size: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct DumpInfo {
+    dump_path: PathBuf,
+    dump_size: u64,
+    dump_modified: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrateRow {
+    id: u64,
+    name: String,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    description: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    repository: Option<String>,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    homepage: Option<String>,
+    /// Only present in older dumps; newer ones use `crate_downloads.csv`
+    #[serde(default)]
+    downloads: Option<u64>,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrateDownloadsRow {
+    crate_id: u64,
+    downloads: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionRow {
+    id: u64,
+    crate_id: u64,
+    num: String,
+    created_at: String,
+    #[serde(deserialize_with = "pg_bool")]
+    yanked: bool,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    license: Option<String>,
+    downloads: u64,
+    #[serde(default)]
+    published_by: Option<u64>,
+    #[serde(default)]
+    crate_size: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionDownloadsRow {
+    version_id: u64,
+    downloads: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrateOwnerRow {
+    crate_id: u64,
+    owner_id: u64,
+    owner_kind: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserRow {
+    id: u64,
+    gh_login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TeamRow {
+    id: u64,
+    login: String,
+}
+
+/// Raw tables collected while streaming through the archive
+#[derive(Default)]
+struct DumpTables {
+    crates: Vec<CrateRow>,
+    crate_downloads: HashMap<u64, u64>,
+    versions: Vec<VersionRow>,
+    recent_version_downloads: HashMap<u64, u64>,
+    owners: Vec<CrateOwnerRow>,
+    users: HashMap<u64, String>,
+    teams: HashMap<u64, String>,
+}
+
+/// Offline crates.io metadata provider backed by a database dump
+#[derive(Debug, Clone)]
+pub struct DbDumpProvider {
+    index_dir: PathBuf,
+}
+
+impl DbDumpProvider {
+    /// Open a dump, building (or rebuilding) its lookup index if needed.
+    ///
+    /// The index defaults to `<dump>.index/` next to the archive.
+    pub fn open(dump_path: &Path, index_dir: Option<&Path>) -> Result<Self> {
+        if !dump_path.is_file() {
+            return Err(AuditError::config(format!(
+                "crates.io database dump not found at {}",
+                dump_path.display()
+            )));
+        }
+
+        let index_dir = index_dir
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| default_index_dir(dump_path));
+        let info = dump_info(dump_path)?;
+
+        if read_index_info(&index_dir).as_ref() != Some(&info) {
+            info!(
+                "Building crates.io dump index at {} (one-time, may take a few minutes)",
+                index_dir.display()
+            );
+            let count = build_index(dump_path, &index_dir)?;
+            fs::write(index_dir.join(INDEX_INFO_FILE), serde_json::to_vec(&info)?)?;
+            info!("Indexed {} crates from {}", count, dump_path.display());
+        } else {
+            debug!("Using existing crates.io dump index at {}", index_dir.display());
+        }
+
+        Ok(Self { index_dir })
+    }
+
+    /// Look up metadata for a crate version, mirroring the crates.io API provider
+    pub fn crate_metadata(&self, crate_name: &str, version: &str) -> Result<CrateMetadata> {
+        let path = self.index_dir.join(index_path(crate_name));
+        if !path.is_file() {
+            return Err(AuditError::DependencyNotFound(crate_name.to_string()));
+        }
+
+        let record: DumpCrate = serde_json::from_slice(&fs::read(&path)?)?;
+
+        // Find the specific version or use the latest
+        let version_info = record
+            .versions
+            .iter()
+            .find(|v| v.num == version)
+            .or_else(|| record.versions.iter().max_by_key(|v| v.created_at))
+            .ok_or_else(|| AuditError::parse("No versions found for crate"))?;
+
+        let latest = record
+            .versions
+            .iter()
+            .filter(|v| !v.yanked)
+            .filter_map(|v| semver::Version::parse(&v.num).ok().map(|parsed| (parsed, v)))
+            .filter(|(parsed, _)| parsed.pre.is_empty())
+            .max_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(CrateMetadata {
+            name: record.name.clone(),
+            version: version_info.num.clone(),
+            description: record.description.clone(),
+            license: version_info.license.clone(),
+            repository: record.repository.clone(),
+            homepage: record.homepage.clone(),
+            downloads: record.downloads,
+            recent_downloads: Some(record.recent_downloads),
+            created_at: Some(record.created_at),
+            updated_at: Some(version_info.created_at),
+            version_count: record.versions.len() as u32,
+            crate_size: version_info.crate_size,
+            owners: Some(CrateOwners::from_logins(record.owners.iter().cloned())),
+            published_by: version_info.published_by.clone(),
+            previous_publishers: collect_publishers(
+                record
+                    .versions
+                    .iter()
+                    .filter(|v| v.created_at < version_info.created_at)
+                    .filter_map(|v| v.published_by.clone()),
+            ),
+            is_yanked: version_info.yanked,
+            latest_version: latest.as_ref().map(|(parsed, _)| parsed.to_string()),
+            published_at: Some(version_info.created_at),
+            latest_published_at: latest.map(|(_, v)| v.created_at),
+            usage: CrateUsage::default(),
+        })
+    }
+}
+
+fn default_index_dir(dump_path: &Path) -> PathBuf {
+    let mut name = dump_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".index");
+    dump_path.with_file_name(name)
+}
+
+fn dump_info(dump_path: &Path) -> Result<DumpInfo> {
+    let meta = fs::metadata(dump_path)?;
+    let dump_modified = meta
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    Ok(DumpInfo {
+        dump_path: dump_path.canonicalize()?,
+        dump_size: meta.len(),
+        dump_modified,
+    })
+}
+
+fn read_index_info(index_dir: &Path) -> Option<DumpInfo> {
+    let content = fs::read(index_dir.join(INDEX_INFO_FILE)).ok()?;
+    serde_json::from_slice(&content).ok()
+}
+
+/// Stream the archive, join the tables and write one JSON record per crate
+fn build_index(dump_path: &Path, index_dir: &Path) -> Result<usize> {
+    let tables = read_tables(File::open(dump_path)?)?;
+
+    if tables.crates.is_empty() {
+        return Err(AuditError::parse(format!(
+            "No crates.csv found in database dump {}",
+            dump_path.display()
+        )));
+    }
+
+    prepare_index_dir(index_dir)?;
+
+    let mut recent_downloads: HashMap<u64, u64> = HashMap::new();
+    let mut versions: HashMap<u64, Vec<DumpVersion>> = HashMap::new();
+    for row in tables.versions {
+        *recent_downloads.entry(row.crate_id).or_default() +=
+            tables.recent_version_downloads.get(&row.id).copied().unwrap_or(0);
+
+        versions.entry(row.crate_id).or_default().push(DumpVersion {
+            num: row.num,
+            created_at: parse_dump_datetime(&row.created_at)?,
+            yanked: row.yanked,
+            license: row.license,
+            downloads: row.downloads,
+            published_by: row.published_by.and_then(|id| tables.users.get(&id).cloned()),
+            crate_size: row.crate_size,
+        });
+    }
+
+    let mut owners: HashMap<u64, Vec<String>> = HashMap::new();
+    for row in tables.owners {
+        let login = if row.owner_kind == OWNER_KIND_TEAM {
+            tables.teams.get(&row.owner_id)
+        } else {
+            tables.users.get(&row.owner_id)
+        };
+        if let Some(login) = login {
+            owners.entry(row.crate_id).or_default().push(login.clone());
+        }
+    }
+
+    let count = tables.crates.len();
+    for row in tables.crates {
+        let record = DumpCrate {
+            downloads: row
+                .downloads
+                .or_else(|| tables.crate_downloads.get(&row.id).copied())
+                .unwrap_or(0),
+            recent_downloads: recent_downloads.get(&row.id).copied().unwrap_or(0),
+            created_at: parse_dump_datetime(&row.created_at)?,
+            updated_at: parse_dump_datetime(&row.updated_at)?,
+            owners: owners.remove(&row.id).unwrap_or_default(),
+            versions: versions.remove(&row.id).unwrap_or_default(),
+            name: row.name,
+            description: row.description,
+            repository: row.repository,
+            homepage: row.homepage,
+        };
+
+        let path = index_dir.join(index_path(&record.name));
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_vec(&record)?)?;
+    }
+
+    Ok(count)
+}
+
+/// Empty the index directory, refusing to touch one this tool didn't create
+fn prepare_index_dir(index_dir: &Path) -> Result<()> {
+    if index_dir.exists() {
+        let owned = index_dir.join(INDEX_INFO_FILE).is_file();
+        if !owned && fs::read_dir(index_dir)?.next().is_some() {
+            return Err(AuditError::config(format!(
+                "db_dump_index_dir {} is not empty and is not a dump index; \
+                 choose an empty or new directory",
+                index_dir.display()
+            )));
+        }
+        fs::remove_dir_all(index_dir)?;
+    }
+    fs::create_dir_all(index_dir)?;
+
+    // Claim the directory before writing records, so an interrupted build can
+    // still be rebuilt; the real marker replaces this once the build completes
+    fs::write(index_dir.join(INDEX_INFO_FILE), b"{}")?;
+    Ok(())
+}
+
+/// Collect the CSV tables we need from a gzipped tarball
+fn read_tables(reader: impl Read) -> Result<DumpTables> {
+    let mut archive = tar::Archive::new(GzDecoder::new(reader));
+    let mut tables = DumpTables::default();
+
+    for entry in archive.entries()? {
+        let entry = entry?;
+        let file_name = entry
+            .path()?
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(String::from);
+
+        match file_name.as_deref() {
+            Some("crates.csv") => tables.crates = read_csv(entry)?,
+            Some("crate_downloads.csv") => {
+                tables.crate_downloads = read_csv::<CrateDownloadsRow>(entry)?
+                    .into_iter()
+                    .map(|r| (r.crate_id, r.downloads))
+                    .collect();
+            }
+            Some("versions.csv") => tables.versions = read_csv(entry)?,
+            Some("version_downloads.csv") => {
+                // The largest table by far: aggregate it without holding the rows
+                let downloads = &mut tables.recent_version_downloads;
+                for_each_csv_row(entry, |row: VersionDownloadsRow| {
+                    *downloads.entry(row.version_id).or_default() += row.downloads;
+                })?;
+            }
+            Some("crate_owners.csv") => tables.owners = read_csv(entry)?,
+            Some("users.csv") => {
+                tables.users = read_csv::<UserRow>(entry)?
+                    .into_iter()
+                    .map(|r| (r.id, r.gh_login))
+                    .collect();
+            }
+            Some("teams.csv") => {
+                tables.teams = read_csv::<TeamRow>(entry)?
+                    .into_iter()
+                    .map(|r| (r.id, r.login))
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+
+    Ok(tables)
+}
+
+fn read_csv<T: for<'de> Deserialize<'de>>(reader: impl Read) -> Result<Vec<T>> {
+    csv::Reader::from_reader(reader)
+        .deserialize()
+        .collect::<std::result::Result<Vec<T>, _>>()
+        .map_err(|e| AuditError::parse(format!("Invalid database dump CSV: {}", e)))
+}
+
+fn for_each_csv_row<T: for<'de> Deserialize<'de>>(
+    reader: impl Read,
+    mut f: impl FnMut(T),
+) -> Result<()> {
+    for row in csv::Reader::from_reader(reader).deserialize() {
+        f(row.map_err(|e| AuditError::parse(format!("Invalid database dump CSV: {}", e)))?);
+    }
+    Ok(())
+}
+
+/// Dump timestamps look like `2014-11-11 23:06:22.183727`, optionally with a `+00` suffix
+fn parse_dump_datetime(s: &str) -> Result<DateTime<Utc>> {
+    let trimmed = s.trim_end_matches("+00").trim_end_matches("+00:00");
+    NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%d %H:%M:%S%.f")
+        .map(|dt| dt.and_utc())
+        .or_else(|_| DateTime::parse_from_rfc3339(s).map(|dt| dt.with_timezone(&Utc)))
+        .map_err(|e| AuditError::parse(format!("Invalid dump datetime '{}': {}", s, e)))
+}
+
+/// PostgreSQL exports booleans as `t` / `f`
+fn pg_bool<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<bool, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    Ok(matches!(value.as_str(), "t" | "true" | "1"))
+}
+
+fn empty_as_none<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<String>, D::Error> {
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(value.filter(|s| !s.is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    fn write_dump(path: &Path) {
+        let files: [(&str, &str); 6] = [
+            (
+                "crates.csv",
+                "created_at,description,downloads,homepage,id,name,repository,updated_at\n\
+                 2019-01-01 00:00:00.000000,A test crate,5000,,1,foobar,https://github.com/foo/bar,2021-06-01 00:00:00.000000\n",
+            ),
+            (
+                "versions.csv",
+                "crate_id,created_at,downloads,id,license,num,published_by,yanked\n\
+                 1,2019-01-01 00:00:00.000000,1000,10,MIT,0.1.0,100,f\n\
+                 1,2021-06-01 00:00:00.000000,4000,11,MIT OR Apache-2.0,0.2.0,101,t\n",
+            ),
+            (
+                "version_downloads.csv",
+                "date,downloads,version_id\n2021-06-02,7,11\n2021-06-03,3,11\n",
+            ),
+            (
+                "crate_owners.csv",
+                "crate_id,created_at,created_by,owner_id,owner_kind\n1,2019-01-01 00:00:00,100,100,0\n1,2019-01-01 00:00:00,100,5,1\n",
+            ),
+            ("users.csv", "gh_avatar,gh_id,gh_login,id,name\n,1,alice,100,Alice\n,2,bob,101,Bob\n"),
+            ("teams.csv", "avatar,github_id,id,login,name,org_id\n,9,5,github:foo:maintainers,Maintainers,3\n"),
+        ];
+
+        let encoder = GzEncoder::new(File::create(path).unwrap(), Compression::fast());
+        let mut builder = tar::Builder::new(encoder);
+        for (name, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, format!("2024-01-01-020000/data/{}", name), content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn test_db_dump_lookup() {
+        let dir = tempfile::tempdir().unwrap();
+        let dump_path = dir.path().join("db-dump.tar.gz");
+        write_dump(&dump_path);
+
+        let provider = DbDumpProvider::open(&dump_path, None).unwrap();
+        assert!(dir.path().join("db-dump.tar.gz.index").join(INDEX_INFO_FILE).is_file());
+
+        let meta = provider.crate_metadata("foobar", "0.2.0").unwrap();
+        assert_eq!(meta.downloads, 5000);
+        assert_eq!(meta.recent_downloads, Some(10));
+        assert_eq!(meta.version_count, 2);
+        assert!(meta.is_yanked);
+        assert_eq!(meta.latest_version.as_deref(), Some("0.1.0"));
+        assert_eq!(meta.license.as_deref(), Some("MIT OR Apache-2.0"));
+        assert_eq!(meta.repository.as_deref(), Some("https://github.com/foo/bar"));
+
+        let record: DumpCrate = serde_json::from_slice(
+            &fs::read(provider.index_dir.join(index_path("foobar"))).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(record.owners, vec!["alice", "github:foo:maintainers"]);
+        assert_eq!(record.versions[1].published_by.as_deref(), Some("bob"));
+
+        assert!(matches!(
+            provider.crate_metadata("missing", "1.0.0"),
+            Err(AuditError::DependencyNotFound(_))
+        ));
+
+        // Reopening reuses the index
+        assert!(DbDumpProvider::open(&dump_path, None).is_ok());
+    }
+
+    #[test]
+    fn test_index_dir_must_be_ours_or_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let dump_path = dir.path().join("db-dump.tar.gz");
+        write_dump(&dump_path);
+
+        let foreign = dir.path().join("documents");
+        fs::create_dir(&foreign).unwrap();
+        fs::write(foreign.join("notes.txt"), "keep me").unwrap();
+        assert!(matches!(
+            DbDumpProvider::open(&dump_path, Some(&foreign)),
+            Err(AuditError::ConfigError(_))
+        ));
+        assert!(foreign.join("notes.txt").is_file());
+
+        let empty = dir.path().join("empty");
+        fs::create_dir(&empty).unwrap();
+        assert!(DbDumpProvider::open(&dump_path, Some(&empty)).is_ok());
+
+        // An interrupted build left its claim marker behind and can be redone
+        fs::write(empty.join(INDEX_INFO_FILE), b"{}").unwrap();
+        let provider = DbDumpProvider::open(&dump_path, Some(&empty)).unwrap();
+        assert!(provider.crate_metadata("foobar", "0.1.0").is_ok());
+    }
+
+    #[test]
+    fn test_parse_dump_datetime() {
+        assert!(parse_dump_datetime("2014-11-11 23:06:22.183727").is_ok());
+        assert!(parse_dump_datetime("2014-11-11 23:06:22+00").is_ok());
+        assert!(parse_dump_datetime("2014-11-11T23:06:22Z").is_ok());
+        assert!(parse_dump_datetime("yesterday").is_err());
+    }
+}
@@ -0,0 +1,180 @@
+//! Map repository URLs to the forge API that serves them
+//!
+//! github.com, gitlab.com and codeberg.org are built in; self-hosted GitHub
+//! Enterprise, GitLab and Gitea/Forgejo instances come from
+//! `NetworkConfig::forge_hosts`, each with its own token.
+
+use crate::config::{ForgeHost, ForgeKind, NetworkConfig};
+use std::collections::HashMap;
+
+const GITHUB_HOST: &str = "github.com";
+const GITHUB_API: &str = "https://api.github.com";
+const GITLAB_HOST: &str = "gitlab.com";
+const GITLAB_API: &str = "https://gitlab.com/api/v4";
+const CODEBERG_HOST: &str = "codeberg.org";
+const CODEBERG_API: &str = "https://codeberg.org/api/v1";
+
+/// Files of a repository at one revision: git blob hash keyed by path from the root
+pub type RepoTree = HashMap<String, String>;
+
+/// The API endpoint to use for one repository
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForgeEndpoint {
+    pub kind: ForgeKind,
+    /// Repository host, lowercased (`github.com`, `ghe.corp.example`)
+    pub host: String,
+    /// API base URL without a trailing slash
+    pub api_base: String,
+    pub token: Option<String>,
+    /// Repository path on the host (`owner/repo` or `group/subgroup/project`)
+    pub repo_path: String,
+}
+
+impl ForgeEndpoint {
+    /// Whether OpenSSF Scorecard publishes results for this host (github.com / gitlab.com only)
+    pub fn has_scorecard(&self) -> bool {
+        self.host == GITHUB_HOST || self.host == GITLAB_HOST
+    }
+}
+
+/// Resolve the forge API for a repository URL, if its host is known
+pub fn resolve_forge(repo_url: &str, config: &NetworkConfig) -> Option<ForgeEndpoint> {
+    let (host, repo_path) = split_repo_url(repo_url)?;
+    if repo_path.is_empty() {
+        return None;
+    }
+
+    if let Some(forge) = config
+        .forge_hosts
+        .iter()
+        .find(|f| f.host.eq_ignore_ascii_case(host))
+    {
+        return Some(endpoint(forge, host, repo_path));
+    }
+
+    let (kind, api_base, token) = if host.eq_ignore_ascii_case(GITHUB_HOST) {
+        (ForgeKind::GitHub, GITHUB_API, &config.github_token)
+    } else if host.eq_ignore_ascii_case(GITLAB_HOST) {
+        (ForgeKind::GitLab, GITLAB_API, &config.gitlab_token)
+    } else if host.eq_ignore_ascii_case(CODEBERG_HOST) {
+        (ForgeKind::Gitea, CODEBERG_API, &config.gitea_token)
+    } else {
+        return None;
+    };
+
+    Some(ForgeEndpoint {
+        kind,
+        host: host.to_ascii_lowercase(),
+        api_base: api_base.to_string(),
+        token: token.clone(),
+        repo_path: repo_path.to_string(),
+    })
+}
+
+fn endpoint(forge: &ForgeHost, host: &str, repo_path: &str) -> ForgeEndpoint {
+    ForgeEndpoint {
+        kind: forge.kind,
+        host: host.to_ascii_lowercase(),
+        api_base: forge.api_base(),
+        token: forge.token.clone(),
+        repo_path: repo_path.to_string(),
+    }
+}
+
+/// Split a repository URL into host and repository path.
+///
+/// Handles `https://host/owner/repo(.git)`, `git://host/...`, `ssh://git@host:22/...`
+/// and scp-style `git@host:owner/repo.git`.
+pub fn split_repo_url(url: &str) -> Option<(&str, &str)> {
+    let url = url.trim().trim_end_matches('/').trim_end_matches(".git");
+    let (rest, has_scheme) = match url.split_once("://") {
+        Some((_, rest)) => (rest, true),
+        None => (url, false),
+    };
+    let rest = rest.split_once('@').map_or(rest, |(_, after)| after);
+
+    let end = rest.find(['/', ':'])?;
+    let host = &rest[..end];
+    let mut path = &rest[end + 1..];
+
+    // Skip an explicit port (`host:8443/owner/repo`); scp-style URLs have no scheme
+    if has_scheme && rest[end..].starts_with(':') {
+        path = path.split_once('/').map_or("", |(_, p)| p);
+    }
+
+    // Drop trailing web UI segments such as `/-/tree/main` or `/tree/main`
+    let path = path.split("/-/").next().unwrap_or(path);
+    let path = path.split("/tree/").next().unwrap_or(path);
+
+    Some((host, path.trim_matches('/')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_repo_url() {
+        let cases = [
+            ("https://github.com/serde-rs/serde", ("github.com", "serde-rs/serde")),
+            ("https://github.com/serde-rs/serde.git", ("github.com", "serde-rs/serde")),
+            ("git@gitlab.com:group/sub/project.git", ("gitlab.com", "group/sub/project")),
+            ("ssh://git@gitlab.corp.example:2222/team/lib", ("gitlab.corp.example", "team/lib")),
+            ("https://gitlab.com/group/project/-/tree/main", ("gitlab.com", "group/project")),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(split_repo_url(url), Some(expected), "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_resolve_forge() {
+        let config = NetworkConfig {
+            github_token: Some("public".to_string()),
+            gitlab_token: None,
+            forge_hosts: vec![ForgeHost {
+                host: "ghe.corp.example".to_string(),
+                kind: ForgeKind::GitHub,
+                api_url: None,
+                token: Some("internal".to_string()),
+            }],
+            ..NetworkConfig::default()
+        };
+
+        let public = resolve_forge("https://github.com/serde-rs/serde", &config).unwrap();
+        assert_eq!(public.api_base, GITHUB_API);
+        assert_eq!(public.token.as_deref(), Some("public"));
+        assert!(public.has_scorecard());
+
+        let enterprise = resolve_forge("https://ghe.corp.example/team/lib", &config).unwrap();
+        assert_eq!(enterprise.kind, ForgeKind::GitHub);
+        assert_eq!(enterprise.api_base, "https://ghe.corp.example/api/v3");
+        assert_eq!(enterprise.token.as_deref(), Some("internal"));
+        assert_eq!(enterprise.repo_path, "team/lib");
+        assert!(!enterprise.has_scorecard());
+
+        // github.com routed through a configured host entry still has a scorecard
+        let config = NetworkConfig {
+            forge_hosts: vec![ForgeHost {
+                host: "github.com".to_string(),
+                kind: ForgeKind::GitHub,
+                api_url: None,
+                token: Some("proxy".to_string()),
+            }],
+            ..NetworkConfig::default()
+        };
+        let routed = resolve_forge("https://GitHub.com/serde-rs/serde", &config).unwrap();
+        assert_eq!(routed.api_base, "https://github.com/api/v3");
+        assert!(routed.has_scorecard());
+
+        assert!(resolve_forge("https://example.org/foo/bar", &config).is_none());
+    }
+
+    #[test]
+    fn test_forge_host_from_toml() {
+        let forge: ForgeHost =
+            toml::from_str("host = \"gitlab.corp.example\"\nkind = \"gitlab\"\n").unwrap();
+        assert_eq!(forge.kind, ForgeKind::GitLab);
+        assert_eq!(forge.api_base(), "https://gitlab.corp.example/api/v4");
+    }
+}
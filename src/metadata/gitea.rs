@@ -0,0 +1,366 @@
+//! Fetch metadata from Gitea, Forgejo and Codeberg repositories
+
+use super::cache::{CacheSource, CachedClient};
+use super::forge::{resolve_forge, ForgeEndpoint, RepoTree};
+use crate::config::{ForgeKind, NetworkConfig};
+use crate::error::{AuditError, Result};
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// Metadata from a Gitea-compatible forge for a repository
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GiteaMetadata {
+    pub name: String,
+    pub full_name: String,
+    pub description: Option<String>,
+    pub stars: u32,
+    pub forks: u32,
+    pub open_issues: u32,
+    pub is_archived: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Date of the latest commit on the default branch (falls back to `updated_at`)
+    pub pushed_at: DateTime<Utc>,
+    pub has_security_policy: Option<bool>,
+    pub default_branch: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaRepo {
+    name: String,
+    full_name: String,
+    #[serde(default)]
+    description: Option<String>,
+    stars_count: u32,
+    forks_count: u32,
+    #[serde(default)]
+    open_issues_count: u32,
+    archived: bool,
+    created_at: String,
+    updated_at: String,
+    default_branch: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaBranch {
+    commit: GiteaBranchCommit,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaBranchCommit {
+    timestamp: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaComparison {
+    total_commits: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaTree {
+    #[serde(default)]
+    tree: Vec<GiteaTreeEntry>,
+    #[serde(default)]
+    truncated: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaTreeEntry {
+    path: String,
+    #[serde(rename = "type")]
+    kind: String,
+    sha: String,
+}
+
+/// Upper bound on tree pages fetched for one repository
+const MAX_TREE_PAGES: u32 = 100;
+
+/// Fetch metadata for a Gitea, Forgejo or Codeberg repository
+pub async fn fetch_gitea_metadata(repo_url: &str, config: &NetworkConfig) -> Result<GiteaMetadata> {
+    let (forge, owner, repo) = parse_gitea_url(repo_url, config)?;
+    debug!("Fetching Gitea metadata for {}/{}", owner, repo);
+
+    let client = build_client(forge.token.as_deref(), config)?;
+    let repo_api = format!("{}/repos/{}/{}", forge.api_base, owner, repo);
+
+    let repo_data: GiteaRepo = fetch_json(&client, &repo_api).await?;
+
+    let created_at = parse_gitea_datetime(&repo_data.created_at)?;
+    let updated_at = parse_gitea_datetime(&repo_data.updated_at)?;
+
+    // The repository object has no push date, so read the default branch head
+    let pushed_at = match &repo_data.default_branch {
+        Some(branch) => {
+            let url = format!("{}/branches/{}", repo_api, urlencoding::encode(branch));
+            fetch_json::<GiteaBranch>(&client, &url)
+                .await
+                .ok()
+                .and_then(|b| parse_gitea_datetime(&b.commit.timestamp).ok())
+                .unwrap_or(updated_at)
+        }
+        None => updated_at,
+    };
+
+    let has_security_policy = check_security_policy(&client, &repo_api).await.ok();
+
+    Ok(GiteaMetadata {
+        name: repo_data.name,
+        full_name: repo_data.full_name,
+        description: repo_data.description,
+        stars: repo_data.stars_count,
+        forks: repo_data.forks_count,
+        open_issues: repo_data.open_issues_count,
+        is_archived: repo_data.archived,
+        created_at,
+        updated_at,
+        pushed_at,
+        has_security_policy,
+        default_branch: repo_data.default_branch,
+    })
+}
+
+/// Count the commits on `branch` that are not contained in the pinned `rev`
+pub async fn fetch_gitea_commits_behind(
+    repo_url: &str,
+    rev: &str,
+    branch: &str,
+    config: &NetworkConfig,
+) -> Result<u32> {
+    let (forge, owner, repo) = parse_gitea_url(repo_url, config)?;
+    debug!("Comparing {}/{} {}...{}", owner, repo, rev, branch);
+
+    let client = build_client(forge.token.as_deref(), config)?;
+    let url = format!(
+        "{}/repos/{}/{}/compare/{}...{}",
+        forge.api_base,
+        owner,
+        repo,
+        rev,
+        urlencoding::encode(branch)
+    );
+
+    let comparison: GiteaComparison = fetch_json(&client, &url).await?;
+    Ok(comparison.total_commits)
+}
+
+/// List the blob hash of every file in the repository at `rev` (commit, tag or branch).
+///
+/// Returns `None` when the repository or revision doesn't exist.
+pub async fn fetch_gitea_tree(repo_url: &str, rev: &str, config: &NetworkConfig) -> Result<Option<RepoTree>> {
+    let (forge, owner, repo) = parse_gitea_url(repo_url, config)?;
+    debug!("Fetching Gitea tree of {}/{} at {}", owner, repo, rev);
+
+    let client = build_client(forge.token.as_deref(), config)?;
+    let mut tree = RepoTree::new();
+
+    for page in 1..=MAX_TREE_PAGES {
+        let url = format!(
+            "{}/repos/{}/{}/git/trees/{}?recursive=true&page={}",
+            forge.api_base,
+            owner,
+            repo,
+            urlencoding::encode(rev),
+            page
+        );
+
+        let response = client.get_with(&url, None, client.revision_ttl(rev)).await?;
+        if matches!(response.status().as_u16(), 404 | 422) {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(AuditError::api("Gitea", format!("HTTP {}", response.status())));
+        }
+
+        let page: GiteaTree = response.json()?;
+        tree.extend(
+            page.tree
+                .into_iter()
+                .filter(|entry| entry.kind == "blob")
+                .map(|entry| (entry.path, entry.sha)),
+        );
+        if !page.truncated {
+            break;
+        }
+    }
+
+    Ok(Some(tree))
+}
+
+/// Read a file from the repository at `rev`, or `None` if it doesn't exist
+pub async fn fetch_gitea_file(
+    repo_url: &str,
+    rev: &str,
+    path: &str,
+    config: &NetworkConfig,
+) -> Result<Option<Vec<u8>>> {
+    let (forge, owner, repo) = parse_gitea_url(repo_url, config)?;
+    let client = build_client(forge.token.as_deref(), config)?;
+    let url = format!(
+        "{}/repos/{}/{}/raw/{}?ref={}",
+        forge.api_base,
+        owner,
+        repo,
+        path,
+        urlencoding::encode(rev)
+    );
+
+    let response = client.get_with(&url, None, client.revision_ttl(rev)).await?;
+    if response.status().as_u16() == 404 {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(AuditError::api("Gitea", format!("HTTP {}", response.status())));
+    }
+    Ok(Some(response.into_bytes()))
+}
+
+/// Resolve the API endpoint and owner/repo name for a Gitea-compatible URL
+fn parse_gitea_url(url: &str, config: &NetworkConfig) -> Result<(ForgeEndpoint, String, String)> {
+    let forge = resolve_forge(url, config)
+        .filter(|f| f.kind == ForgeKind::Gitea)
+        .ok_or_else(|| AuditError::parse(format!("Invalid Gitea URL: {}", url)))?;
+
+    let mut parts = forge.repo_path.split('/');
+    match (parts.next(), parts.next()) {
+        (Some(owner), Some(repo)) if !owner.is_empty() && !repo.is_empty() => {
+            let (owner, repo) = (owner.to_string(), repo.to_string());
+            Ok((forge, owner, repo))
+        }
+        _ => Err(AuditError::parse(format!("Invalid Gitea URL: {}", url))),
+    }
+}
+
+/// Build HTTP client with Gitea authentication if available
+fn build_client(token: Option<&str>, config: &NetworkConfig) -> Result<CachedClient> {
+    let mut builder = Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(config.timeout());
+
+    if let Some(token) = token {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            reqwest::header::AUTHORIZATION,
+            format!("token {}", token).parse().unwrap(),
+        );
+        builder = builder.default_headers(headers);
+    }
+
+    let client = builder.build()
+        .map_err(|e| AuditError::network(format!("Failed to build HTTP client: {}", e)))?;
+    Ok(CachedClient::new(client, CacheSource::Gitea, config))
+}
+
+async fn fetch_json<T: DeserializeOwned>(client: &CachedClient, url: &str) -> Result<T> {
+    let response = client.get(url).await?;
+
+    if !response.status().is_success() {
+        if response.status().as_u16() == 404 {
+            return Err(AuditError::api("Gitea", "Not found"));
+        }
+        return Err(AuditError::api(
+            "Gitea",
+            format!("HTTP {}", response.status()),
+        ));
+    }
+
+    response.json()
+}
+
+/// Check for SECURITY.md in the locations Gitea and GitHub recognise
+async fn check_security_policy(client: &CachedClient, repo_api: &str) -> Result<bool> {
+    let paths = ["SECURITY.md", ".gitea/SECURITY.md", ".github/SECURITY.md", "docs/SECURITY.md"];
+
+    for path in paths {
+        let url = format!("{}/contents/{}", repo_api, path);
+        debug!("Checking for security policy at {}", url);
+
+        let response = client.get(&url).await?;
+        if response.status().is_success() {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Parse Gitea datetime format (RFC 3339)
+fn parse_gitea_datetime(s: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| AuditError::parse(format!("Invalid Gitea datetime: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CacheConfig;
+    use crate::config::ForgeHost;
+
+    #[test]
+    fn test_parse_gitea_url() {
+        let config = NetworkConfig::default();
+        let (forge, owner, repo) =
+            parse_gitea_url("https://codeberg.org/forgejo/forgejo.git", &config).unwrap();
+        assert_eq!(forge.api_base, "https://codeberg.org/api/v1");
+        assert_eq!((owner.as_str(), repo.as_str()), ("forgejo", "forgejo"));
+
+        assert!(parse_gitea_url("https://github.com/serde-rs/serde", &config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_gitea_metadata() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/api/v1/repos/team/lib")
+            .with_body(
+                r#"{"name":"lib","full_name":"team/lib","description":"A library",
+                    "stars_count":12,"forks_count":2,"open_issues_count":5,"archived":false,
+                    "created_at":"2022-01-01T00:00:00Z","updated_at":"2024-05-01T00:00:00Z",
+                    "default_branch":"main"}"#,
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/v1/repos/team/lib/branches/main")
+            .with_body(r#"{"name":"main","commit":{"id":"abc","timestamp":"2024-04-20T10:00:00+02:00"}}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/v1/repos/team/lib/contents/.gitea/SECURITY.md")
+            .with_body("{}")
+            .create_async()
+            .await;
+        server
+            .mock("GET", mockito::Matcher::Any)
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let config = NetworkConfig {
+            forge_hosts: vec![ForgeHost {
+                host: "git.corp.example".to_string(),
+                kind: ForgeKind::Gitea,
+                api_url: Some(format!("{}/api/v1", server.url())),
+                token: None,
+            }],
+            cache: CacheConfig {
+                enabled: false,
+                ..CacheConfig::default()
+            },
+            ..NetworkConfig::default()
+        };
+
+        let meta = fetch_gitea_metadata("https://git.corp.example/team/lib", &config)
+            .await
+            .unwrap();
+        assert_eq!(meta.stars, 12);
+        assert_eq!(meta.open_issues, 5);
+        assert_eq!(meta.pushed_at.to_rfc3339(), "2024-04-20T08:00:00+00:00");
+        assert_eq!(meta.has_security_policy, Some(true));
+    }
+}
@@ -1,14 +1,16 @@
 //! Fetch metadata from GitHub repositories
 
-use crate::config::NetworkConfig;
+use super::cache::{CacheSource, CacheTtl, CachedClient, CachedResponse};
+use super::forge::{resolve_forge, ForgeEndpoint, RepoTree};
+use crate::config::{ForgeKind, NetworkConfig};
 use crate::error::{AuditError, Result};
 use chrono::{DateTime, Utc};
 use reqwest::Client;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use tracing::{debug, warn};
 
-const GITHUB_API: &str = "https://api.github.com";
 const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
 
 /// Metadata from GitHub for a repository
@@ -26,6 +28,10 @@ pub struct GitHubMetadata {
     pub pushed_at: DateTime<Utc>,
     pub contributors_count: Option<u32>,
     pub has_security_policy: Option<bool>,
+    pub default_branch: Option<String>,
+    /// Commits on the default branch (only provided by the GraphQL provider)
+    #[serde(default)]
+    pub commit_count: Option<u32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -40,6 +46,27 @@ struct GitHubRepo {
     created_at: String,
     updated_at: String,
     pushed_at: String,
+    default_branch: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubComparison {
+    ahead_by: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubTree {
+    tree: Vec<GitHubTreeEntry>,
+    #[serde(default)]
+    truncated: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubTreeEntry {
+    path: String,
+    #[serde(rename = "type")]
+    kind: String,
+    sha: String,
 }
 
 /// Fetch metadata for a GitHub repository
@@ -47,21 +74,23 @@ pub async fn fetch_github_metadata(
     repo_url: &str,
     config: &NetworkConfig,
 ) -> Result<GitHubMetadata> {
-    let (owner, repo) = parse_github_url(repo_url)?;
+    let (forge, owner, repo) = parse_github_url(repo_url, config)?;
     debug!("Fetching GitHub metadata for {}/{}", owner, repo);
 
-    let client = build_client(config)?;
-    let repo_url = format!("{}/repos/{}/{}", GITHUB_API, owner, repo);
+    let client = build_client(config, forge.token.as_deref())?;
+    let repo_url = format!("{}/repos/{}/{}", forge.api_base, owner, repo);
 
     // Fetch repository info
-    let repo_data = fetch_with_retry(&client, &repo_url, config).await?;
+    let repo_data: GitHubRepo = fetch_with_retry(&client, &repo_url, config).await?;
 
     // Optionally fetch contributors count (separate API call)
     let contributors_url = format!("{}/contributors?per_page=1", repo_url);
     let contributors_count = fetch_contributors_count(&client, &contributors_url, config).await.ok();
 
     // Check for SECURITY.md
-    let has_security_policy = check_security_policy(&client, &owner, &repo, config).await.ok();
+    let has_security_policy = check_security_policy(&client, &forge.api_base, &owner, &repo, config)
+        .await
+        .ok();
 
     let created_at = parse_github_datetime(&repo_data.created_at)?;
     let updated_at = parse_github_datetime(&repo_data.updated_at)?;
@@ -80,39 +109,138 @@ pub async fn fetch_github_metadata(
         pushed_at,
         contributors_count,
         has_security_policy,
+        default_branch: repo_data.default_branch,
+        commit_count: None,
     })
 }
 
-/// Parse GitHub URL to extract owner and repo name
-fn parse_github_url(url: &str) -> Result<(String, String)> {
-    // Handle various GitHub URL formats:
-    // - https://github.com/owner/repo
-    // - https://github.com/owner/repo.git
-    // - git://github.com/owner/repo
-    // - git@github.com:owner/repo.git
+/// Count the commits on `branch` that are not contained in the pinned `rev`
+pub async fn fetch_github_commits_behind(
+    repo_url: &str,
+    rev: &str,
+    branch: &str,
+    config: &NetworkConfig,
+) -> Result<u32> {
+    let (forge, owner, repo) = parse_github_url(repo_url, config)?;
+    debug!("Comparing {}/{} {}...{}", owner, repo, rev, branch);
+
+    let client = build_client(config, forge.token.as_deref())?;
+    let url = format!(
+        "{}/repos/{}/{}/compare/{}...{}",
+        forge.api_base,
+        owner,
+        repo,
+        rev,
+        urlencoding::encode(branch)
+    );
+
+    let comparison: GitHubComparison = fetch_with_retry(&client, &url, config).await?;
+    Ok(comparison.ahead_by)
+}
+
+/// List the blob hash of every file in the repository at `rev` (commit, tag or branch).
+///
+/// Returns `None` when the repository or revision doesn't exist.
+pub async fn fetch_github_tree(repo_url: &str, rev: &str, config: &NetworkConfig) -> Result<Option<RepoTree>> {
+    let (forge, owner, repo) = parse_github_url(repo_url, config)?;
+    debug!("Fetching GitHub tree of {}/{} at {}", owner, repo, rev);
+
+    let client = build_client(config, forge.token.as_deref())?;
+    let url = format!(
+        "{}/repos/{}/{}/git/trees/{}?recursive=1",
+        forge.api_base,
+        owner,
+        repo,
+        urlencoding::encode(rev)
+    );
 
-    let url = url.trim_end_matches(".git");
-    let url = url.trim_end_matches('/');
-
-    let parts: Vec<&str> = if url.contains("github.com:") {
-        // SSH format: git@github.com:owner/repo
-        url.split("github.com:").nth(1).unwrap_or("").split('/').collect()
-    } else if url.contains("github.com/") {
-        // HTTPS/Git format: https://github.com/owner/repo
-        url.split("github.com/").nth(1).unwrap_or("").split('/').collect()
-    } else {
-        return Err(AuditError::parse(format!("Invalid GitHub URL: {}", url)));
+    let Some(response) = fetch_optional(&client, &url, None, client.revision_ttl(rev)).await? else {
+        return Ok(None);
     };
+    let tree: GitHubTree = response.json()?;
+    if tree.truncated {
+        warn!("GitHub tree of {}/{} is truncated; some files will be reported missing", owner, repo);
+    }
 
-    if parts.len() >= 2 {
-        Ok((parts[0].to_string(), parts[1].to_string()))
-    } else {
-        Err(AuditError::parse(format!("Invalid GitHub URL: {}", url)))
+    Ok(Some(
+        tree.tree
+            .into_iter()
+            .filter(|entry| entry.kind == "blob")
+            .map(|entry| (entry.path, entry.sha))
+            .collect(),
+    ))
+}
+
+/// Read a file from the repository at `rev`, or `None` if it doesn't exist
+pub async fn fetch_github_file(
+    repo_url: &str,
+    rev: &str,
+    path: &str,
+    config: &NetworkConfig,
+) -> Result<Option<Vec<u8>>> {
+    let (forge, owner, repo) = parse_github_url(repo_url, config)?;
+    let client = build_client(config, forge.token.as_deref())?;
+    let url = format!(
+        "{}/repos/{}/{}/contents/{}?ref={}",
+        forge.api_base,
+        owner,
+        repo,
+        path,
+        urlencoding::encode(rev)
+    );
+
+    let ttl = client.revision_ttl(rev);
+    match fetch_optional(&client, &url, Some("application/vnd.github.raw"), ttl).await? {
+        Some(response) => Ok(Some(response.into_bytes())),
+        None => Ok(None),
+    }
+}
+
+/// GET a URL, mapping 404 and 422 (unknown ref) to `None`
+async fn fetch_optional(
+    client: &CachedClient,
+    url: &str,
+    accept: Option<&str>,
+    ttl: CacheTtl,
+) -> Result<Option<CachedResponse>> {
+    let response = client.get_with(url, accept, ttl).await?;
+
+    match response.status().as_u16() {
+        404 | 409 | 422 => Ok(None),
+        403 | 429 => Err(AuditError::RateLimitExceeded {
+            service: "GitHub".to_string(),
+            retry_after: None,
+        }),
+        _ if !response.status().is_success() => Err(AuditError::api(
+            "GitHub",
+            format!("HTTP {}", response.status()),
+        )),
+        _ => Ok(Some(response)),
+    }
+}
+
+/// Resolve the API endpoint and owner/repo name for a GitHub or GitHub Enterprise URL
+fn parse_github_url(url: &str, config: &NetworkConfig) -> Result<(ForgeEndpoint, String, String)> {
+    // Handles HTTPS, git:// and SSH URLs on github.com or any configured GitHub host:
+    // - https://github.com/owner/repo(.git)
+    // - git@github.com:owner/repo.git
+
+    let forge = resolve_forge(url, config)
+        .filter(|f| f.kind == ForgeKind::GitHub)
+        .ok_or_else(|| AuditError::parse(format!("Invalid GitHub URL: {}", url)))?;
+
+    let mut parts = forge.repo_path.split('/');
+    match (parts.next(), parts.next()) {
+        (Some(owner), Some(repo)) if !owner.is_empty() && !repo.is_empty() => {
+            let (owner, repo) = (owner.to_string(), repo.to_string());
+            Ok((forge, owner, repo))
+        }
+        _ => Err(AuditError::parse(format!("Invalid GitHub URL: {}", url))),
     }
 }
 
 /// Build HTTP client with GitHub authentication if available
-fn build_client(config: &NetworkConfig) -> Result<Client> {
+fn build_client(config: &NetworkConfig, token: Option<&str>) -> Result<CachedClient> {
     let mut builder = Client::builder()
         .user_agent(USER_AGENT)
         .timeout(config.timeout());
@@ -124,7 +252,7 @@ fn build_client(config: &NetworkConfig) -> Result<Client> {
         "application/vnd.github.v3+json".parse().unwrap(),
     );
 
-    if let Some(token) = &config.github_token {
+    if let Some(token) = token {
         headers.insert(
             reqwest::header::AUTHORIZATION,
             format!("token {}", token).parse().unwrap(),
@@ -133,21 +261,22 @@ fn build_client(config: &NetworkConfig) -> Result<Client> {
 
     builder = builder.default_headers(headers);
 
-    builder.build()
-        .map_err(|e| AuditError::network(format!("Failed to build HTTP client: {}", e)))
+    let client = builder.build()
+        .map_err(|e| AuditError::network(format!("Failed to build HTTP client: {}", e)))?;
+    Ok(CachedClient::new(client, CacheSource::GitHub, config))
 }
 
 /// Fetch data with retry logic
-async fn fetch_with_retry(
-    client: &Client,
+async fn fetch_with_retry<T: DeserializeOwned>(
+    client: &CachedClient,
     url: &str,
     config: &NetworkConfig,
-) -> Result<GitHubRepo> {
+) -> Result<T> {
     let mut attempts = 0;
     let mut delay = config.request_delay();
 
     loop {
-        match client.get(url).send().await {
+        match client.get(url).await {
             Ok(response) => {
                 // Check for rate limiting
                 if response.status().as_u16() == 403 {
@@ -171,7 +300,7 @@ async fn fetch_with_retry(
                 }
 
                 if response.status().as_u16() == 404 {
-                    return Err(AuditError::api("GitHub", "Repository not found"));
+                    return Err(AuditError::api("GitHub", "Not found"));
                 }
 
                 if !response.status().is_success() {
@@ -181,9 +310,10 @@ async fn fetch_with_retry(
                     ));
                 }
 
-                let data: GitHubRepo = response.json().await?;
+                let data: T = response.json()?;
                 return Ok(data);
             }
+            Err(e @ AuditError::Offline(_)) => return Err(e),
             Err(e) => {
                 if attempts >= config.max_retries {
                     return Err(AuditError::network(format!("GitHub request failed: {}", e)));
@@ -199,11 +329,11 @@ async fn fetch_with_retry(
 
 /// Fetch contributors count from Link header pagination
 async fn fetch_contributors_count(
-    client: &Client,
+    client: &CachedClient,
     url: &str,
     _config: &NetworkConfig,
 ) -> Result<u32> {
-    match client.get(url).send().await {
+    match client.get(url).await {
         Ok(response) => {
             if !response.status().is_success() {
                 return Ok(0);
@@ -220,7 +350,7 @@ async fn fetch_contributors_count(
             }
 
             // Fallback: count items in response
-            if let Ok(contributors) = response.json::<Vec<serde_json::Value>>().await {
+            if let Ok(contributors) = response.json::<Vec<serde_json::Value>>() {
                 Ok(contributors.len() as u32)
             } else {
                 Ok(0)
@@ -232,7 +362,8 @@ async fn fetch_contributors_count(
 
 /// Check for existence of SECURITY.md in common locations
 async fn check_security_policy(
-    client: &Client,
+    client: &CachedClient,
+    api_base: &str,
     owner: &str,
     repo: &str,
     config: &NetworkConfig,
@@ -241,7 +372,7 @@ async fn check_security_policy(
     let paths = ["SECURITY.md", ".github/SECURITY.md", "docs/SECURITY.md"];
     
     for path in paths {
-        let url = format!("{}/repos/{}/{}/contents/{}", GITHUB_API, owner, repo, path);
+        let url = format!("{}/repos/{}/{}/contents/{}", api_base, owner, repo, path);
         debug!("Checking for security policy at {}", url);
         
         // We use a HEAD request if possible, but GitHub API for contents usually returns JSON
@@ -251,32 +382,26 @@ async fn check_security_policy(
         // https://docs.github.com/en/rest/metrics/community?apiVersion=2022-11-28
         
         // Let's try the community profile endpoint first as it's cleaner
-        let community_url = format!("{}/repos/{}/{}/community/profile", GITHUB_API, owner, repo);
+        let community_url = format!("{}/repos/{}/{}/community/profile", api_base, owner, repo);
         
-        match client.get(&community_url).send().await {
-            Ok(resp) => {
-                if resp.status().is_success() {
-                    if let Ok(json) = resp.json::<serde_json::Value>().await {
-                        if let Some(files) = json.get("files") {
-                            if files.get("security_note").is_some() {
-                                return Ok(true);
-                            }
+        if let Ok(resp) = client.get(&community_url).await {
+            if resp.status().is_success() {
+                if let Ok(json) = resp.json::<serde_json::Value>() {
+                    if let Some(files) = json.get("files") {
+                        if files.get("security_note").is_some() {
+                            return Ok(true);
                         }
                     }
                 }
             }
-            Err(_) => {}
         }
         
         // Fallback to checking specific files if community profile fails or isn't available
         // (though community profile is the standard way now)
-        match client.get(&url).send().await {
-             Ok(resp) => {
-                 if resp.status().is_success() {
-                     return Ok(true);
-                 }
-             }
-             Err(_) => {}
+        if let Ok(resp) = client.get(&url).await {
+            if resp.status().is_success() {
+                return Ok(true);
+            }
         }
         
         tokio::time::sleep(config.request_delay()).await;
@@ -312,6 +437,7 @@ fn parse_github_datetime(s: &str) -> Result<DateTime<Utc>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::CacheConfig;
 
     #[test]
     fn test_parse_github_url() {
@@ -322,12 +448,56 @@ mod tests {
             ("git@github.com:serde-rs/serde.git", ("serde-rs", "serde")),
         ];
 
+        let config = NetworkConfig::default();
         for (url, expected) in test_cases {
-            let result = parse_github_url(url).unwrap();
-            assert_eq!(result, (expected.0.to_string(), expected.1.to_string()));
+            let (_, owner, repo) = parse_github_url(url, &config).unwrap();
+            assert_eq!((owner, repo), (expected.0.to_string(), expected.1.to_string()));
         }
     }
 
+    #[tokio::test]
+    async fn test_fetch_github_enterprise_metadata() {
+        let mut server = mockito::Server::new_async().await;
+        let repo = server
+            .mock("GET", "/repos/team/lib")
+            .match_header("authorization", "token internal")
+            .with_body(
+                r#"{"name":"lib","full_name":"team/lib","description":null,
+                    "stargazers_count":3,"forks_count":1,"open_issues_count":2,"archived":false,
+                    "created_at":"2023-01-01T00:00:00Z","updated_at":"2024-01-01T00:00:00Z",
+                    "pushed_at":"2024-01-02T00:00:00Z","default_branch":"main"}"#,
+            )
+            .create_async()
+            .await;
+        // Contributors and security policy lookups are best effort
+        server.mock("GET", mockito::Matcher::Any).with_status(404).create_async().await;
+
+        let config = NetworkConfig {
+            max_retries: 0,
+            request_delay_ms: 0,
+            github_token: Some("public".to_string()),
+            forge_hosts: vec![crate::config::ForgeHost {
+                host: "ghe.corp.example".to_string(),
+                kind: ForgeKind::GitHub,
+                api_url: Some(server.url()),
+                token: Some("internal".to_string()),
+            }],
+            cache: CacheConfig {
+                enabled: false,
+                ..CacheConfig::default()
+            },
+            ..NetworkConfig::default()
+        };
+
+        let meta = fetch_github_metadata("https://ghe.corp.example/team/lib.git", &config)
+            .await
+            .unwrap();
+        assert_eq!(meta.full_name, "team/lib");
+        assert_eq!(meta.stars, 3);
+        assert_eq!(meta.default_branch.as_deref(), Some("main"));
+        repo.assert_async().await;
+    }
+
     #[test]
     fn test_extract_last_page() {
         let link_header = r#"<https://api.github.com/repos/rust-lang/rust/contributors?page=2>; rel="next", <https://api.github.com/repos/rust-lang/rust/contributors?page=50>; rel="last""#;
@@ -0,0 +1,383 @@
+//! Batch GitHub repository lookups through the GraphQL API
+//!
+//! The REST provider needs a repository call, a contributors call and up to six
+//! security policy probes per crate. One GraphQL query fetches the repository
+//! fields for up to 100 repositories, so large audits stay well inside the hourly
+//! budget. GraphQL has no contributor count, so `contributors_count` is left
+//! unset and community scoring goes without it. GraphQL always requires a token;
+//! repositories on hosts without one are left to the REST provider.
+
+use super::cache::{CacheSource, CacheStore};
+use super::forge::{resolve_forge, ForgeEndpoint};
+use super::github::GitHubMetadata;
+use crate::config::{ForgeKind, NetworkConfig};
+use crate::error::{AuditError, Result};
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::{debug, warn};
+
+const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// GitHub's limit on top-level repository lookups per query we stay under
+const MAX_REPOS_PER_QUERY: usize = 100;
+
+const REPOSITORY_FIELDS: &str = "fragment RepoFields on Repository { \
+    name nameWithOwner description stargazerCount forkCount \
+    issues(states: OPEN) { totalCount } pullRequests(states: OPEN) { totalCount } \
+    isArchived createdAt updatedAt pushedAt isSecurityPolicyEnabled \
+    defaultBranchRef { name target { ... on Commit { history { totalCount } } } } }";
+
+/// A repository to look up, with the URLs it was requested under
+#[derive(Debug)]
+struct RepoRef {
+    urls: Vec<String>,
+    owner: String,
+    repo: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlResponse {
+    #[serde(default)]
+    data: Option<HashMap<String, Option<GraphQlRepository>>>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphQlRepository {
+    name: String,
+    name_with_owner: String,
+    description: Option<String>,
+    stargazer_count: u32,
+    fork_count: u32,
+    issues: TotalCount,
+    pull_requests: TotalCount,
+    is_archived: bool,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    pushed_at: Option<DateTime<Utc>>,
+    is_security_policy_enabled: Option<bool>,
+    default_branch_ref: Option<BranchRef>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TotalCount {
+    total_count: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct BranchRef {
+    name: String,
+    target: Option<BranchTarget>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BranchTarget {
+    history: Option<TotalCount>,
+}
+
+/// Fetch metadata for many GitHub repositories with as few requests as possible.
+///
+/// Returns metadata keyed by the repository URL as passed in. URLs that are not
+/// on a GitHub host with a token, or that the query could not resolve, are absent.
+pub async fn fetch_github_metadata_batch(
+    repo_urls: &[String],
+    config: &NetworkConfig,
+) -> HashMap<String, GitHubMetadata> {
+    // Group repositories by the endpoint (and token) that serves them
+    let mut groups: HashMap<(String, String), Vec<RepoRef>> = HashMap::new();
+    for url in repo_urls {
+        let Some(forge) = resolve_forge(url, config).filter(|f| f.kind == ForgeKind::GitHub) else {
+            continue;
+        };
+        let Some(token) = forge.token.clone() else {
+            continue;
+        };
+        let Some((owner, repo)) = owner_and_repo(&forge) else {
+            continue;
+        };
+        let repos = groups.entry((graphql_url(&forge.api_base), token)).or_default();
+        // Crates from one repository share a lookup; GitHub names are case-insensitive
+        match repos
+            .iter_mut()
+            .find(|r| r.owner.eq_ignore_ascii_case(&owner) && r.repo.eq_ignore_ascii_case(&repo))
+        {
+            Some(existing) => {
+                if !existing.urls.contains(url) {
+                    existing.urls.push(url.clone());
+                }
+            }
+            None => repos.push(RepoRef {
+                urls: vec![url.clone()],
+                owner,
+                repo,
+            }),
+        }
+    }
+
+    let mut results = HashMap::new();
+    for ((endpoint, token), repos) in groups {
+        for chunk in repos.chunks(MAX_REPOS_PER_QUERY) {
+            match query_chunk(&endpoint, &token, chunk, config).await {
+                Ok(found) => results.extend(found),
+                Err(e) => warn!("GitHub GraphQL batch of {} repositories failed: {}", chunk.len(), e),
+            }
+        }
+    }
+
+    debug!("Fetched {} repositories through GitHub GraphQL", results.len());
+    results
+}
+
+/// Run one query for up to 100 repositories
+async fn query_chunk(
+    endpoint: &str,
+    token: &str,
+    repos: &[RepoRef],
+    config: &NetworkConfig,
+) -> Result<HashMap<String, GitHubMetadata>> {
+    let query = build_query(repos);
+
+    // GraphQL responses carry no ETag, so a cached query is reused until it expires
+    let store = CacheStore::for_source(config, CacheSource::GitHub);
+    let cache_key = format!("{}\n{}", endpoint, query);
+    let parsed: GraphQlResponse = match store.as_ref().and_then(|s| s.get(&cache_key)) {
+        Some(body) => {
+            debug!("Using cached GitHub GraphQL results for {} repositories", repos.len());
+            serde_json::from_slice(&body)?
+        }
+        None => {
+            let body = post_query(endpoint, token, &query, config).await?;
+            let parsed: GraphQlResponse = serde_json::from_slice(&body)?;
+            if let Some(store) = store.as_ref().filter(|_| parsed.data.is_some()) {
+                store.put(&cache_key, &body);
+            }
+            parsed
+        }
+    };
+
+    for error in &parsed.errors {
+        // Unknown repositories come back as per-alias NOT_FOUND errors with null data
+        debug!("GitHub GraphQL: {}", error.message);
+    }
+
+    let data = parsed.data.ok_or_else(|| {
+        AuditError::api(
+            "GitHub",
+            parsed
+                .errors
+                .first()
+                .map(|e| e.message.clone())
+                .unwrap_or_else(|| "Empty GraphQL response".to_string()),
+        )
+    })?;
+
+    let mut results = HashMap::new();
+    for (index, repo_ref) in repos.iter().enumerate() {
+        if let Some(Some(repo)) = data.get(&alias(index)) {
+            let meta = to_metadata(repo);
+            for url in &repo_ref.urls {
+                results.insert(url.clone(), meta.clone());
+            }
+        }
+    }
+    Ok(results)
+}
+
+/// POST a query, returning the raw response body
+async fn post_query(endpoint: &str, token: &str, query: &str, config: &NetworkConfig) -> Result<Vec<u8>> {
+    if config.offline {
+        return Err(AuditError::offline(endpoint));
+    }
+
+    let client = Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(config.timeout())
+        .build()
+        .map_err(|e| AuditError::network(format!("Failed to build HTTP client: {}", e)))?;
+
+    let body = serde_json::json!({ "query": query });
+
+    let mut attempts = 0;
+    let mut delay = config.request_delay();
+    let response = loop {
+        let result = client
+            .post(endpoint)
+            .header(reqwest::header::AUTHORIZATION, format!("bearer {}", token))
+            .json(&body)
+            .send()
+            .await;
+        match result {
+            Ok(response) => break response,
+            Err(e) if attempts < config.max_retries => {
+                warn!("GitHub GraphQL request failed, retrying: {}", e);
+                tokio::time::sleep(delay).await;
+                attempts += 1;
+                delay *= 2;
+            }
+            Err(e) => return Err(AuditError::network(format!("GitHub GraphQL request failed: {}", e))),
+        }
+    };
+
+    let status = response.status().as_u16();
+    if status == 403 || status == 429 {
+        return Err(AuditError::RateLimitExceeded {
+            service: "GitHub".to_string(),
+            retry_after: None,
+        });
+    }
+    if !response.status().is_success() {
+        return Err(AuditError::api("GitHub", format!("HTTP {}", response.status())));
+    }
+
+    Ok(response.bytes().await?.to_vec())
+}
+
+fn build_query(repos: &[RepoRef]) -> String {
+    let mut query = String::from("query {");
+    for (index, RepoRef { owner, repo, .. }) in repos.iter().enumerate() {
+        // JSON string escaping is valid GraphQL string syntax
+        query.push_str(&format!(
+            " {}: repository(owner: {}, name: {}) {{ ...RepoFields }}",
+            alias(index),
+            serde_json::Value::from(owner.as_str()),
+            serde_json::Value::from(repo.as_str())
+        ));
+    }
+    query.push_str(" } ");
+    query.push_str(REPOSITORY_FIELDS);
+    query
+}
+
+fn to_metadata(repo: &GraphQlRepository) -> GitHubMetadata {
+    GitHubMetadata {
+        name: repo.name.clone(),
+        full_name: repo.name_with_owner.clone(),
+        description: repo.description.clone(),
+        stars: repo.stargazer_count,
+        forks: repo.fork_count,
+        // REST's open_issues_count includes pull requests
+        open_issues: repo.issues.total_count + repo.pull_requests.total_count,
+        is_archived: repo.is_archived,
+        created_at: repo.created_at,
+        updated_at: repo.updated_at,
+        // Empty repositories have never been pushed to
+        pushed_at: repo.pushed_at.unwrap_or(repo.created_at),
+        contributors_count: None,
+        has_security_policy: repo.is_security_policy_enabled,
+        default_branch: repo.default_branch_ref.as_ref().map(|b| b.name.clone()),
+        commit_count: repo
+            .default_branch_ref
+            .as_ref()
+            .and_then(|b| b.target.as_ref())
+            .and_then(|t| t.history.as_ref())
+            .map(|h| h.total_count),
+    }
+}
+
+fn alias(index: usize) -> String {
+    format!("r{}", index)
+}
+
+fn owner_and_repo(forge: &ForgeEndpoint) -> Option<(String, String)> {
+    let mut parts = forge.repo_path.split('/');
+    match (parts.next(), parts.next()) {
+        (Some(owner), Some(repo)) if !owner.is_empty() && !repo.is_empty() => {
+            Some((owner.to_string(), repo.to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// GraphQL lives at `/graphql` on api.github.com and `/api/graphql` on GitHub Enterprise
+fn graphql_url(api_base: &str) -> String {
+    match api_base.strip_suffix("/v3") {
+        Some(root) => format!("{}/graphql", root),
+        None => format!("{}/graphql", api_base),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CacheConfig;
+    use crate::config::ForgeHost;
+
+    #[test]
+    fn test_graphql_url() {
+        assert_eq!(graphql_url("https://api.github.com"), "https://api.github.com/graphql");
+        assert_eq!(graphql_url("https://ghe.corp.example/api/v3"), "https://ghe.corp.example/api/graphql");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_github_metadata_batch() {
+        let mut server = mockito::Server::new_async().await;
+        let graphql = server
+            .mock("POST", "/api/graphql")
+            .match_header("authorization", "bearer internal")
+            .match_body(mockito::Matcher::Regex(
+                // One lookup per repository, however many crates point at it
+                r#"r0: repository\(owner: \\"team\\", name: \\"lib\\"\).*r1: repository\([^)]*\) \{ \.\.\.RepoFields \} \} fragment"#
+                    .to_string(),
+            ))
+            .with_body(
+                r#"{"data":{
+                    "r0":{"name":"lib","nameWithOwner":"team/lib","description":"Internal",
+                          "stargazerCount":7,"forkCount":1,"issues":{"totalCount":3},
+                          "pullRequests":{"totalCount":2},"isArchived":false,
+                          "createdAt":"2022-01-01T00:00:00Z","updatedAt":"2024-01-01T00:00:00Z",
+                          "pushedAt":"2024-02-01T00:00:00Z","isSecurityPolicyEnabled":true,
+                          "defaultBranchRef":{"name":"main","target":{"history":{"totalCount":321}}}},
+                    "r1":null},
+                   "errors":[{"type":"NOT_FOUND","message":"Could not resolve to a Repository"}]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = NetworkConfig {
+            forge_hosts: vec![ForgeHost {
+                host: "ghe.corp.example".to_string(),
+                kind: ForgeKind::GitHub,
+                api_url: Some(format!("{}/api/v3", server.url())),
+                token: Some("internal".to_string()),
+            }],
+            cache: CacheConfig {
+                enabled: false,
+                ..CacheConfig::default()
+            },
+            ..NetworkConfig::default()
+        };
+
+        let urls = vec![
+            "https://ghe.corp.example/team/lib".to_string(),
+            "https://ghe.corp.example/team/gone".to_string(),
+            "https://ghe.corp.example/Team/lib".to_string(),
+            "https://ghe.corp.example/team/lib".to_string(),
+            "https://gitlab.com/group/project".to_string(),
+        ];
+        let results = fetch_github_metadata_batch(&urls, &config).await;
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results["https://ghe.corp.example/Team/lib"].full_name, "team/lib");
+        let meta = &results["https://ghe.corp.example/team/lib"];
+        assert_eq!(meta.full_name, "team/lib");
+        // Issues plus pull requests, as REST counts them
+        assert_eq!(meta.open_issues, 5);
+        // GraphQL has no contributor count
+        assert_eq!(meta.contributors_count, None);
+        assert_eq!(meta.commit_count, Some(321));
+        assert_eq!(meta.has_security_policy, Some(true));
+        assert_eq!(meta.default_branch.as_deref(), Some("main"));
+        graphql.assert_async().await;
+    }
+}
@@ -1,13 +1,14 @@
 //! Fetch metadata from GitLab repositories
 
-use crate::config::NetworkConfig;
+use super::cache::{CacheSource, CachedClient};
+use super::forge::{resolve_forge, ForgeEndpoint, RepoTree};
+use crate::config::{ForgeKind, NetworkConfig};
 use crate::error::{AuditError, Result};
 use chrono::{DateTime, Utc};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use tracing::debug;
 
-const GITLAB_API: &str = "https://gitlab.com/api/v4";
 const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
 
 /// Metadata from GitLab for a repository
@@ -22,6 +23,7 @@ pub struct GitLabMetadata {
     pub is_archived: bool,
     pub created_at: DateTime<Utc>,
     pub last_activity_at: DateTime<Utc>,
+    pub default_branch: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -36,23 +38,44 @@ struct GitLabProject {
     last_activity_at: String,
     #[serde(default)]
     open_issues_count: u32,
+    default_branch: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct GitLabComparison {
+    #[serde(default)]
+    commits: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabTreeEntry {
+    id: String,
+    path: String,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+/// Entries per page of the repository tree API (GitLab's maximum)
+const TREE_PAGE_SIZE: usize = 100;
+
+/// Upper bound on tree pages fetched for one repository
+const MAX_TREE_PAGES: u32 = 200;
+
 /// Fetch metadata for a GitLab repository
 pub async fn fetch_gitlab_metadata(
     repo_url: &str,
     config: &NetworkConfig,
 ) -> Result<GitLabMetadata> {
-    let project_path = parse_gitlab_url(repo_url)?;
-    debug!("Fetching GitLab metadata for {}", project_path);
+    let forge = parse_gitlab_url(repo_url, config)?;
+    debug!("Fetching GitLab metadata for {}", forge.repo_path);
 
-    let client = build_client(config)?;
+    let client = build_client(forge.token.as_deref(), config)?;
     
     // URL-encode the project path
-    let encoded_path = urlencoding::encode(&project_path);
-    let url = format!("{}/projects/{}", GITLAB_API, encoded_path);
+    let encoded_path = urlencoding::encode(&forge.repo_path);
+    let url = format!("{}/projects/{}", forge.api_base, encoded_path);
 
-    let response = client.get(&url).send().await?;
+    let response = client.get(&url).await?;
 
     if !response.status().is_success() {
         if response.status().as_u16() == 404 {
@@ -64,7 +87,7 @@ pub async fn fetch_gitlab_metadata(
         ));
     }
 
-    let project: GitLabProject = response.json().await?;
+    let project: GitLabProject = response.json()?;
 
     let created_at = parse_gitlab_datetime(&project.created_at)?;
     let last_activity_at = parse_gitlab_datetime(&project.last_activity_at)?;
@@ -79,43 +102,137 @@ pub async fn fetch_gitlab_metadata(
         is_archived: project.archived,
         created_at,
         last_activity_at,
+        default_branch: project.default_branch,
     })
 }
 
-/// Parse GitLab URL to extract project path
-fn parse_gitlab_url(url: &str) -> Result<String> {
-    // Handle various GitLab URL formats:
+/// Count the commits on `branch` that are not contained in the pinned `rev`
+pub async fn fetch_gitlab_commits_behind(
+    repo_url: &str,
+    rev: &str,
+    branch: &str,
+    config: &NetworkConfig,
+) -> Result<u32> {
+    let forge = parse_gitlab_url(repo_url, config)?;
+    debug!("Comparing {} {}...{}", forge.repo_path, rev, branch);
+
+    let client = build_client(forge.token.as_deref(), config)?;
+    let url = format!(
+        "{}/projects/{}/repository/compare?from={}&to={}&straight=false",
+        forge.api_base,
+        urlencoding::encode(&forge.repo_path),
+        urlencoding::encode(rev),
+        urlencoding::encode(branch)
+    );
+
+    let response = client.get(&url).await?;
+    if !response.status().is_success() {
+        return Err(AuditError::api(
+            "GitLab",
+            format!("HTTP {}", response.status()),
+        ));
+    }
+
+    let comparison: GitLabComparison = response.json()?;
+    Ok(comparison.commits.len() as u32)
+}
+
+/// List the blob hash of every file in the project at `rev` (commit, tag or branch).
+///
+/// Returns `None` when the project or revision doesn't exist.
+pub async fn fetch_gitlab_tree(repo_url: &str, rev: &str, config: &NetworkConfig) -> Result<Option<RepoTree>> {
+    let forge = parse_gitlab_url(repo_url, config)?;
+    debug!("Fetching GitLab tree of {} at {}", forge.repo_path, rev);
+
+    let client = build_client(forge.token.as_deref(), config)?;
+    let mut tree = RepoTree::new();
+
+    for page in 1..=MAX_TREE_PAGES {
+        let url = format!(
+            "{}/projects/{}/repository/tree?recursive=true&ref={}&per_page={}&page={}",
+            forge.api_base,
+            urlencoding::encode(&forge.repo_path),
+            urlencoding::encode(rev),
+            TREE_PAGE_SIZE,
+            page
+        );
+
+        let response = client.get_with(&url, None, client.revision_ttl(rev)).await?;
+        if response.status().as_u16() == 404 {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(AuditError::api(
+                "GitLab",
+                format!("HTTP {}", response.status()),
+            ));
+        }
+
+        let entries: Vec<GitLabTreeEntry> = response.json()?;
+        let last_page = entries.len() < TREE_PAGE_SIZE;
+        tree.extend(
+            entries
+                .into_iter()
+                .filter(|entry| entry.kind == "blob")
+                .map(|entry| (entry.path, entry.id)),
+        );
+        if last_page {
+            break;
+        }
+    }
+
+    Ok(Some(tree))
+}
+
+/// Read a file from the project at `rev`, or `None` if it doesn't exist
+pub async fn fetch_gitlab_file(
+    repo_url: &str,
+    rev: &str,
+    path: &str,
+    config: &NetworkConfig,
+) -> Result<Option<Vec<u8>>> {
+    let forge = parse_gitlab_url(repo_url, config)?;
+    let client = build_client(forge.token.as_deref(), config)?;
+    let url = format!(
+        "{}/projects/{}/repository/files/{}/raw?ref={}",
+        forge.api_base,
+        urlencoding::encode(&forge.repo_path),
+        urlencoding::encode(path),
+        urlencoding::encode(rev)
+    );
+
+    let response = client.get_with(&url, None, client.revision_ttl(rev)).await?;
+    if response.status().as_u16() == 404 {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(AuditError::api(
+            "GitLab",
+            format!("HTTP {}", response.status()),
+        ));
+    }
+    Ok(Some(response.into_bytes()))
+}
+
+/// Resolve the API endpoint and project path for a GitLab URL
+fn parse_gitlab_url(url: &str, config: &NetworkConfig) -> Result<ForgeEndpoint> {
+    // Handles gitlab.com and any configured self-managed GitLab host:
     // - https://gitlab.com/group/project
     // - https://gitlab.com/group/subgroup/project
     // - git@gitlab.com:group/project.git
 
-    let url = url.trim_end_matches(".git");
-    let url = url.trim_end_matches('/');
-
-    let path = if url.contains("gitlab.com:") {
-        // SSH format
-        url.split("gitlab.com:").nth(1).unwrap_or("")
-    } else if url.contains("gitlab.com/") {
-        // HTTPS format
-        url.split("gitlab.com/").nth(1).unwrap_or("")
-    } else {
-        return Err(AuditError::parse(format!("Invalid GitLab URL: {}", url)));
-    };
-
-    if path.is_empty() {
-        return Err(AuditError::parse(format!("Invalid GitLab URL: {}", url)));
-    }
-
-    Ok(path.to_string())
+    resolve_forge(url, config)
+        .filter(|f| f.kind == ForgeKind::GitLab)
+        .ok_or_else(|| AuditError::parse(format!("Invalid GitLab URL: {}", url)))
 }
 
 /// Build HTTP client with GitLab authentication if available
-fn build_client(config: &NetworkConfig) -> Result<Client> {
+fn build_client(token: Option<&str>, config: &NetworkConfig) -> Result<CachedClient> {
     let mut builder = Client::builder()
         .user_agent(USER_AGENT)
         .timeout(config.timeout());
 
-    if let Some(token) = &config.gitlab_token {
+    if let Some(token) = token {
         let mut headers = reqwest::header::HeaderMap::new();
         headers.insert(
             "PRIVATE-TOKEN",
@@ -124,8 +241,9 @@ fn build_client(config: &NetworkConfig) -> Result<Client> {
         builder = builder.default_headers(headers);
     }
 
-    builder.build()
-        .map_err(|e| AuditError::network(format!("Failed to build HTTP client: {}", e)))
+    let client = builder.build()
+        .map_err(|e| AuditError::network(format!("Failed to build HTTP client: {}", e)))?;
+    Ok(CachedClient::new(client, CacheSource::GitLab, config))
 }
 
 /// Parse GitLab datetime format (ISO 8601)
@@ -138,6 +256,7 @@ fn parse_gitlab_datetime(s: &str) -> Result<DateTime<Utc>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::CacheConfig;
 
     #[test]
     fn test_parse_gitlab_url() {
@@ -147,9 +266,52 @@ mod tests {
             ("git@gitlab.com:gitlab-org/gitlab.git", "gitlab-org/gitlab"),
         ];
 
+        let config = NetworkConfig::default();
         for (url, expected) in test_cases {
-            let result = parse_gitlab_url(url).unwrap();
-            assert_eq!(result, expected);
+            let result = parse_gitlab_url(url, &config).unwrap();
+            assert_eq!(result.repo_path, expected);
         }
     }
+
+    #[tokio::test]
+    async fn test_fetch_self_hosted_gitlab_metadata() {
+        let mut server = mockito::Server::new_async().await;
+        let project = server
+            .mock("GET", "/api/v4/projects/platform%2Fcrates%2Fcore-lib")
+            .match_header("private-token", "corp-token")
+            .with_body(
+                r#"{"name":"core-lib","path_with_namespace":"platform/crates/core-lib",
+                    "description":"Internal","star_count":4,"forks_count":0,"archived":false,
+                    "created_at":"2022-05-01T00:00:00Z","last_activity_at":"2024-03-01T00:00:00Z",
+                    "open_issues_count":7,"default_branch":"develop"}"#,
+            )
+            .create_async()
+            .await;
+
+        let config = NetworkConfig {
+            gitlab_token: Some("public-token".to_string()),
+            forge_hosts: vec![crate::config::ForgeHost {
+                host: "gitlab.corp.example".to_string(),
+                kind: ForgeKind::GitLab,
+                api_url: Some(format!("{}/api/v4", server.url())),
+                token: Some("corp-token".to_string()),
+            }],
+            cache: CacheConfig {
+                enabled: false,
+                ..CacheConfig::default()
+            },
+            ..NetworkConfig::default()
+        };
+
+        let meta = fetch_gitlab_metadata(
+            "git@gitlab.corp.example:platform/crates/core-lib.git",
+            &config,
+        )
+        .await
+        .unwrap();
+        assert_eq!(meta.path_with_namespace, "platform/crates/core-lib");
+        assert_eq!(meta.open_issues, 7);
+        assert_eq!(meta.default_branch.as_deref(), Some("develop"));
+        project.assert_async().await;
+    }
 }
@@ -0,0 +1,375 @@
+//! Read repository activity from local git checkouts
+//!
+//! Git dependencies are checked out by Cargo under `$CARGO_HOME/git/checkouts`
+//! and path dependencies live on disk, usually inside a git repository. Their
+//! commit history gives recency and contributor data without any API call.
+//! Cargo's bare clones under `$CARGO_HOME/git/db` also serve file trees for
+//! provenance checks. History is read through the `git` command-line tool.
+
+use super::forge::RepoTree;
+use crate::config::cargo_home;
+use crate::error::{AuditError, Result};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::debug;
+
+/// Window used for commit frequency and author counts
+const ACTIVITY_WINDOW_DAYS: i64 = 365;
+
+/// Activity summary for a local git repository
+#[derive(Debug, Clone)]
+pub struct LocalGitMetadata {
+    /// Date of the most recent commit touching the inspected path
+    pub last_commit_at: DateTime<Utc>,
+    /// Commits in the last 12 months
+    pub commits_last_year: u32,
+    /// Distinct author emails in the last 12 months
+    pub authors_last_year: u32,
+}
+
+impl LocalGitMetadata {
+    /// Average commits per month over the last 12 months
+    pub fn commits_per_month(&self) -> f32 {
+        self.commits_last_year as f32 / 12.0
+    }
+}
+
+/// Inspect the git history of `dir`.
+///
+/// With `scoped` set only commits touching `dir` are counted, so a path crate
+/// inside a monorepo is judged by its own history rather than the whole repository's.
+pub fn inspect_local_repository(dir: &Path, scoped: bool) -> Result<LocalGitMetadata> {
+    let pathspec: &[&str] = if scoped { &["--", "."] } else { &[] };
+
+    let last = git_log(dir, &["-1", "--format=%ct"], pathspec)?;
+    let last_commit_at = last
+        .lines()
+        .next()
+        .and_then(parse_timestamp)
+        .ok_or_else(|| AuditError::parse(format!("No commits found in {}", dir.display())))?;
+
+    let since = Utc::now() - Duration::days(ACTIVITY_WINDOW_DAYS);
+    let since_arg = format!("--since={}", since.timestamp());
+    let history = git_log(dir, &[&since_arg, "--format=%aE"], pathspec)?;
+
+    let mut commits_last_year = 0;
+    let mut authors = HashSet::new();
+    for email in history.lines().filter(|l| !l.is_empty()) {
+        commits_last_year += 1;
+        authors.insert(email.to_lowercase());
+    }
+
+    Ok(LocalGitMetadata {
+        last_commit_at,
+        commits_last_year,
+        authors_last_year: authors.len() as u32,
+    })
+}
+
+/// Find Cargo's checkout of a git dependency at `rev`.
+///
+/// Checkouts live in `$CARGO_HOME/git/checkouts/<repo>-<hash>/<short rev>/`.
+pub fn find_git_checkout(url: &str, rev: &str) -> Option<PathBuf> {
+    find_checkout_in(&cargo_home()?.join("git").join("checkouts"), url, rev)
+}
+
+fn find_checkout_in(checkouts: &Path, url: &str, rev: &str) -> Option<PathBuf> {
+    let prefix = repo_dir_prefix(url)?;
+
+    for repo_dir in fs::read_dir(checkouts).ok()?.flatten() {
+        let dir_name = repo_dir.file_name().to_string_lossy().to_lowercase();
+        if !dir_name.starts_with(&prefix) {
+            continue;
+        }
+        for checkout in fs::read_dir(repo_dir.path()).ok()?.flatten() {
+            let short = checkout.file_name().to_string_lossy().into_owned();
+            if !short.is_empty() && rev.starts_with(&short) {
+                return Some(checkout.path());
+            }
+        }
+    }
+
+    None
+}
+
+/// Find Cargo's bare clone of a repository in `$CARGO_HOME/git/db/<repo>-<hash>`.
+///
+/// Cargo keeps these for git dependencies; they hold every fetched commit and tag.
+/// The directory must be named exactly as Cargo names it for `url`, so another
+/// repository with the same (or a longer) name is never used.
+pub fn find_git_database(url: &str) -> Option<PathBuf> {
+    find_database_in(&cargo_home()?.join("git").join("db"), url)
+}
+
+fn find_database_in(db: &Path, url: &str) -> Option<PathBuf> {
+    cargo_git_dir_names(url)?
+        .into_iter()
+        .map(|name| db.join(name))
+        .find(|path| path.is_dir())
+}
+
+/// Directory names Cargo may use for a repository: `<repo>-<hash of the canonical URL>`,
+/// hashed as Cargo 1.85 and later do, then as earlier releases did
+fn cargo_git_dir_names(url: &str) -> Option<[String; 2]> {
+    let url = canonical_git_url(url)?;
+    let name = url.rsplit('/').next().filter(|n| !n.is_empty()).unwrap_or("_empty");
+
+    // `str` feeds a `Hasher` its bytes followed by 0xff
+    let mut bytes = url.as_bytes().to_vec();
+    bytes.push(0xff);
+    let (h1, h2) = sip_hash(&bytes, 1, 3, true);
+    let (legacy, _) = sip_hash(&bytes, 2, 4, false);
+
+    Some([h1.wrapping_mul(3).wrapping_add(h2), legacy].map(|hash| {
+        let hex: String = hash.to_le_bytes().iter().map(|b| format!("{:02x}", b)).collect();
+        format!("{}-{}", name, hex)
+    }))
+}
+
+/// Cargo's canonical form of a git URL: no query, fragment, trailing slash or
+/// `.git` suffix, and github.com paths lowercased over https
+fn canonical_git_url(url: &str) -> Option<String> {
+    let url = url.trim().trim_start_matches("git+");
+    let url = url.split(['?', '#']).next().unwrap_or(url);
+    let (scheme, rest) = url.split_once("://")?;
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let (mut scheme, host) = (scheme.to_lowercase(), host.to_lowercase());
+
+    let mut path = path.trim_end_matches('/').to_string();
+    if host == "github.com" {
+        scheme = "https".to_string();
+        path = path.to_lowercase();
+    }
+    if let Some(stripped) = path.strip_suffix(".git") {
+        path = stripped.to_string();
+    }
+    Some(format!("{}://{}/{}", scheme, host, path))
+}
+
+/// SipHash with zero keys: `c` compression and `d` finalization rounds, and
+/// the 128-bit variant when `wide` is set (the second half is 0 otherwise).
+/// Cargo hashes with SipHash-1-3-128, and used SipHash-2-4 before 1.85.
+fn sip_hash(data: &[u8], c: usize, d: usize, wide: bool) -> (u64, u64) {
+    fn rounds(v: &mut [u64; 4], n: usize) {
+        for _ in 0..n {
+            v[0] = v[0].wrapping_add(v[1]);
+            v[1] = v[1].rotate_left(13) ^ v[0];
+            v[0] = v[0].rotate_left(32);
+            v[2] = v[2].wrapping_add(v[3]);
+            v[3] = v[3].rotate_left(16) ^ v[2];
+            v[0] = v[0].wrapping_add(v[3]);
+            v[3] = v[3].rotate_left(21) ^ v[0];
+            v[2] = v[2].wrapping_add(v[1]);
+            v[1] = v[1].rotate_left(17) ^ v[2];
+            v[2] = v[2].rotate_left(32);
+        }
+    }
+
+    let mut v: [u64; 4] = [
+        0x736f6d6570736575,
+        0x646f72616e646f6d,
+        0x6c7967656e657261,
+        0x7465646279746573,
+    ];
+    if wide {
+        v[1] ^= 0xee;
+    }
+
+    // Little-endian words, the last one padded and tagged with the length
+    let mut chunks = data.chunks_exact(8);
+    let mut words: Vec<u64> = (&mut chunks).map(|w| u64::from_le_bytes(w.try_into().unwrap())).collect();
+    let mut last = (data.len() as u64) << 56;
+    for (i, byte) in chunks.remainder().iter().enumerate() {
+        last |= (*byte as u64) << (8 * i);
+    }
+    words.push(last);
+    for m in words {
+        v[3] ^= m;
+        rounds(&mut v, c);
+        v[0] ^= m;
+    }
+
+    v[2] ^= if wide { 0xee } else { 0xff };
+    rounds(&mut v, d);
+    let h1 = v[0] ^ v[1] ^ v[2] ^ v[3];
+    if !wide {
+        return (h1, 0);
+    }
+    v[1] ^= 0xdd;
+    rounds(&mut v, d);
+    (h1, v[0] ^ v[1] ^ v[2] ^ v[3])
+}
+
+/// List the blob hash of every file in the repository at `rev`, or `None` if
+/// the revision doesn't exist there
+pub fn read_git_tree(git_dir: &Path, rev: &str) -> Result<Option<RepoTree>> {
+    let Some(output) = git_show(git_dir, &["ls-tree", "-r", "-z", "--full-tree", rev])? else {
+        return Ok(None);
+    };
+
+    let mut tree = RepoTree::new();
+    for entry in output.split(|b| *b == 0).filter(|e| !e.is_empty()) {
+        // <mode> SP <type> SP <hash> TAB <path>
+        let entry = String::from_utf8_lossy(entry);
+        let Some((info, path)) = entry.split_once('\t') else {
+            continue;
+        };
+        let mut fields = info.split(' ');
+        if let (Some(_), Some("blob"), Some(hash)) = (fields.next(), fields.next(), fields.next()) {
+            tree.insert(path.to_string(), hash.to_string());
+        }
+    }
+    Ok(Some(tree))
+}
+
+/// Read a file from the repository at `rev`, or `None` if it doesn't exist
+pub fn read_git_file(git_dir: &Path, rev: &str, path: &str) -> Result<Option<Vec<u8>>> {
+    git_show(git_dir, &["cat-file", "blob", &format!("{}:{}", rev, path)])
+}
+
+/// Run a read-only git command, treating a non-zero exit (unknown object) as `None`
+fn git_show(git_dir: &Path, args: &[&str]) -> Result<Option<Vec<u8>>> {
+    debug!("Running git {:?} in {}", args, git_dir.display());
+
+    let output = Command::new("git").arg("--git-dir").arg(git_dir).args(args).output()?;
+    if !output.status.success() {
+        debug!("git {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr).trim());
+        return Ok(None);
+    }
+    Ok(Some(output.stdout))
+}
+
+/// Lowercase `<repo>-` prefix Cargo uses for checkout and database directories
+fn repo_dir_prefix(url: &str) -> Option<String> {
+    let repo_name = url
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .rsplit(['/', ':'])
+        .next()?
+        .to_lowercase();
+    Some(format!("{}-", repo_name))
+}
+
+fn git_log(dir: &Path, args: &[&str], pathspec: &[&str]) -> Result<String> {
+    debug!("Running git log {:?} in {}", args, dir.display());
+
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .arg("log")
+        .args(args)
+        .args(pathspec)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(AuditError::api(
+            "git",
+            format!(
+                "git log failed in {}: {}",
+                dir.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    Utc.timestamp_opt(s.trim().parse().ok()?, 0).single()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(dir: &Path, file: &str, email: &str, days_ago: i64) {
+        fs::write(dir.join(file), email).unwrap();
+        let date = format!("{} +0000", (Utc::now() - Duration::days(days_ago)).timestamp());
+        for args in [vec!["add", "-A"], vec!["commit", "-q", "-m", "change"]] {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(dir)
+                .args(&args)
+                .env("GIT_AUTHOR_NAME", "Test")
+                .env("GIT_AUTHOR_EMAIL", email)
+                .env("GIT_AUTHOR_DATE", &date)
+                .env("GIT_COMMITTER_NAME", "Test")
+                .env("GIT_COMMITTER_EMAIL", email)
+                .env("GIT_COMMITTER_DATE", &date)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+    }
+
+    #[test]
+    fn test_inspect_local_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        assert!(Command::new("git").args(["init", "-q"]).arg(repo).status().unwrap().success());
+        fs::create_dir(repo.join("sub")).unwrap();
+
+        commit(repo, "old.txt", "old@example.com", 800);
+        commit(repo, "a.txt", "alice@example.com", 100);
+        commit(&repo.join("sub"), "b.txt", "Bob@example.com", 20);
+        commit(repo, "c.txt", "bob@example.com", 10);
+
+        let meta = inspect_local_repository(repo, false).unwrap();
+        assert_eq!(meta.commits_last_year, 3);
+        assert_eq!(meta.authors_last_year, 2);
+        assert!(Utc::now().signed_duration_since(meta.last_commit_at).num_days() <= 11);
+
+        let sub = inspect_local_repository(&repo.join("sub"), true).unwrap();
+        assert_eq!(sub.commits_last_year, 1);
+        assert!(Utc::now().signed_duration_since(sub.last_commit_at).num_days() >= 19);
+
+        assert!(inspect_local_repository(&dir.path().join("missing"), false).is_err());
+    }
+
+    #[test]
+    fn test_find_checkout() {
+        let dir = tempfile::tempdir().unwrap();
+        let checkout = dir.path().join("serde-1a2b3c4d5e6f7a8b").join("abc1234");
+        fs::create_dir_all(&checkout).unwrap();
+
+        assert_eq!(
+            find_checkout_in(dir.path(), "https://github.com/serde-rs/serde.git", "abc1234def567"),
+            Some(checkout)
+        );
+        assert_eq!(
+            find_checkout_in(dir.path(), "https://github.com/serde-rs/serde", "fff0000"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_find_database() {
+        // Directory names Cargo 1.95 created for these git dependencies
+        let names = cargo_git_dir_names("file:///tmp/gh/src_serde").unwrap();
+        assert_eq!(names[0], "src_serde-2f5255450951f139");
+        // `std::hash::SipHasher`, as used by Cargo before 1.85
+        assert_eq!(names[1], "src_serde-7b35321e6fed6ce8");
+        let names = cargo_git_dir_names("file:///tmp/gh/src_serde-yaml/").unwrap();
+        assert_eq!(names[0], "src_serde-yaml-831187b4fc2f383a");
+        assert_eq!(
+            canonical_git_url("git+https://GitHub.com/Serde-RS/Serde.git?branch=main#abc").as_deref(),
+            Some("https://github.com/serde-rs/serde")
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let serde_url = "https://github.com/serde-rs/serde";
+        // Same-prefix and same-name repositories are not serde-rs/serde
+        for other in ["https://github.com/acatton/serde-yaml", "https://gitlab.com/fork/serde"] {
+            fs::create_dir(dir.path().join(&cargo_git_dir_names(other).unwrap()[0])).unwrap();
+        }
+        assert_eq!(find_database_in(dir.path(), serde_url), None);
+
+        let db = dir.path().join(&cargo_git_dir_names(serde_url).unwrap()[1]);
+        fs::create_dir(&db).unwrap();
+        assert_eq!(find_database_in(dir.path(), "https://github.com/serde-rs/serde.git"), Some(db));
+    }
+}
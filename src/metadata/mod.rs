@@ -1,10 +1,36 @@
+pub mod cache;
 pub mod crates_io;
+pub mod db_dump;
+pub mod forge;
+pub mod gitea;
 pub mod github;
+pub mod github_graphql;
 pub mod gitlab;
+pub mod local_git;
 pub mod openssf;
+pub mod registry;
+pub mod registry_index;
 
-pub use crates_io::{fetch_crate_metadata, CrateMetadata};
-pub use github::{fetch_github_metadata, GitHubMetadata};
-pub use gitlab::{fetch_gitlab_metadata, GitLabMetadata};
+pub use crates_io::{fetch_crate_metadata, CrateMetadata, CrateOwners, CrateUsage};
+pub use db_dump::DbDumpProvider;
+pub use forge::{resolve_forge, RepoTree};
+pub use gitea::{
+    fetch_gitea_commits_behind, fetch_gitea_file, fetch_gitea_metadata, fetch_gitea_tree, GiteaMetadata,
+};
+pub use github::{
+    fetch_github_commits_behind, fetch_github_file, fetch_github_metadata, fetch_github_tree,
+    GitHubMetadata,
+};
+pub use github_graphql::fetch_github_metadata_batch;
+pub use gitlab::{
+    fetch_gitlab_commits_behind, fetch_gitlab_file, fetch_gitlab_metadata, fetch_gitlab_tree,
+    GitLabMetadata,
+};
+pub use local_git::{
+    find_git_checkout, find_git_database, inspect_local_repository, read_git_file, read_git_tree,
+    LocalGitMetadata,
+};
+pub use registry::fetch_registry_metadata;
+pub use registry_index::{IndexVersion, RegistryIndexProvider};
 #[allow(unused_imports)]
 pub use openssf::OpenSSFClient;
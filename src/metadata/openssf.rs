@@ -1,4 +1,6 @@
+use super::cache::{CacheSource, CachedClient};
 use crate::config::NetworkConfig;
+use crate::types::OpenSSFCheck;
 use crate::{AuditError, Result};
 use reqwest::Client;
 use serde::Deserialize;
@@ -7,6 +9,7 @@ use tracing::{debug, warn};
 const OPENSSF_API_BASE: &str = "https://api.securityscorecards.dev";
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 pub struct ScorecardResponse {
     pub score: f32,
     pub date: String,
@@ -15,6 +18,7 @@ pub struct ScorecardResponse {
 }
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 pub struct RepoInfo {
     pub name: String,
     pub commit: String,
@@ -28,8 +32,19 @@ pub struct ScorecardCheck {
     pub details: Option<Vec<String>>,
 }
 
+impl From<ScorecardCheck> for OpenSSFCheck {
+    fn from(check: ScorecardCheck) -> Self {
+        Self {
+            name: check.name,
+            score: check.score,
+            reason: check.reason,
+            details: check.details.unwrap_or_default(),
+        }
+    }
+}
+
 pub struct OpenSSFClient {
-    client: Client,
+    client: CachedClient,
     config: NetworkConfig,
 }
 
@@ -44,7 +59,7 @@ impl OpenSSFClient {
             .map_err(|e| AuditError::network(format!("Failed to build HTTP client: {}", e)))?;
 
         Ok(Self {
-            client,
+            client: CachedClient::new(client, CacheSource::OpenSsf, config),
             config: config.clone(),
         })
     }
@@ -81,7 +96,7 @@ impl OpenSSFClient {
                 tokio::time::sleep(self.config.request_delay()).await;
             }
 
-            match self.client.get(&url).send().await {
+            match self.client.get(&url).await {
                 Ok(resp) => {
                     if resp.status() == 404 {
                         return Ok(None);
@@ -91,7 +106,7 @@ impl OpenSSFClient {
                         continue;
                     }
 
-                    match resp.json::<ScorecardResponse>().await {
+                    match resp.json::<ScorecardResponse>() {
                         Ok(data) => return Ok(Some(data)),
                         Err(e) => {
                             warn!("Failed to parse OpenSSF response: {}", e);
@@ -99,6 +114,7 @@ impl OpenSSFClient {
                         }
                     }
                 }
+                Err(e @ AuditError::Offline(_)) => return Err(e),
                 Err(e) => {
                     warn!("OpenSSF request failed: {}", e);
                 }
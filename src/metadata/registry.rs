@@ -0,0 +1,174 @@
+//! Fetch metadata for crates published to alternative registries
+//!
+//! Version data comes from the registry index: fetched over HTTP for sparse
+//! registries (authenticated with the registry's token), or read from Cargo's
+//! local index cache for git registries. If the registry advertises a
+//! crates.io-compatible web API in its `config.json`, it is used for the
+//! remaining fields (description, license, repository, downloads).
+
+use super::crates_io::{build_client, fetch_crate_metadata_from, retry_request};
+use super::registry_index::{
+    index_url_path, parse_json_lines, summarize, IndexConfig, IndexSummary, RegistryIndexProvider,
+};
+use super::CrateMetadata;
+use crate::config::NetworkConfig;
+use crate::error::{AuditError, Result};
+use crate::registries::RegistryDefinition;
+use tracing::debug;
+
+/// Fetch metadata for a crate from an alternative registry
+pub async fn fetch_registry_metadata(
+    registry: &RegistryDefinition,
+    crate_name: &str,
+    version: &str,
+    config: &NetworkConfig,
+) -> Result<CrateMetadata> {
+    let local = RegistryIndexProvider::for_registry(&registry.index_url);
+    let token = registry.token.as_deref();
+
+    let summary = if registry.is_sparse() {
+        match fetch_sparse_summary(registry, crate_name, version, config).await {
+            Ok(summary) => summary,
+            Err(e) => {
+                debug!("Sparse index fetch failed for {}: {}", crate_name, e);
+                local.summary(crate_name, version)
+            }
+        }
+    } else {
+        local.summary(crate_name, version)
+    };
+
+    let index_config = match local.index_config() {
+        Some(index_config) => Some(index_config),
+        None if registry.is_sparse() => fetch_sparse_config(registry, config).await.ok(),
+        None => None,
+    };
+
+    if let Some(api) = index_config.and_then(|c| c.api) {
+        let api_base = format!("{}/api/v1", api.trim_end_matches('/'));
+        match fetch_crate_metadata_from(&registry.name, &api_base, token, crate_name, version, config)
+            .await
+        {
+            Ok(mut meta) => {
+                if let Some(summary) = &summary {
+                    summary.apply_to(&mut meta);
+                }
+                return Ok(meta);
+            }
+            Err(e) => debug!("Registry API lookup failed for {}: {}", crate_name, e),
+        }
+    }
+
+    summary
+        .map(|s| s.to_crate_metadata(crate_name, version))
+        .ok_or_else(|| {
+            AuditError::api(
+                registry.name.clone(),
+                format!("No usable index or API data for {}", crate_name),
+            )
+        })
+}
+
+/// Fetch and summarize a crate's file from a sparse index
+async fn fetch_sparse_summary(
+    registry: &RegistryDefinition,
+    crate_name: &str,
+    version: &str,
+    config: &NetworkConfig,
+) -> Result<Option<IndexSummary>> {
+    let url = format!("{}/{}", sparse_base(registry), index_url_path(crate_name));
+    let body = sparse_get(registry, &url, config).await?;
+    Ok(summarize(&parse_json_lines(&body), version))
+}
+
+/// Fetch a sparse registry's `config.json`
+async fn fetch_sparse_config(
+    registry: &RegistryDefinition,
+    config: &NetworkConfig,
+) -> Result<IndexConfig> {
+    let url = format!("{}/config.json", sparse_base(registry));
+    let body = sparse_get(registry, &url, config).await?;
+    Ok(serde_json::from_str(&body)?)
+}
+
+async fn sparse_get(registry: &RegistryDefinition, url: &str, config: &NetworkConfig) -> Result<String> {
+    let client = build_client(config, registry.token.as_deref())?;
+    let response = retry_request(
+        &client,
+        url,
+        &registry.name,
+        config.max_retries,
+        config.request_delay(),
+        client.ttl(),
+    )
+    .await?;
+
+    if !response.status().is_success() {
+        return Err(AuditError::api(
+            registry.name.clone(),
+            format!("HTTP {}: {}", response.status(), url),
+        ));
+    }
+
+    Ok(response.text())
+}
+
+fn sparse_base(registry: &RegistryDefinition) -> &str {
+    registry
+        .index_url
+        .strip_prefix("sparse+")
+        .unwrap_or(&registry.index_url)
+        .trim_end_matches('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CacheConfig;
+
+    #[tokio::test]
+    async fn test_fetch_sparse_registry_with_token() {
+        let mut server = mockito::Server::new_async().await;
+
+        let index = server
+            .mock("GET", "/index/fo/ob/foobar")
+            .match_header("authorization", "secret")
+            .with_body(concat!(
+                r#"{"name":"foobar","vers":"0.1.0","pubtime":"2023-01-01T00:00:00Z"}"#,
+                "\n",
+                r#"{"name":"foobar","vers":"0.2.0","pubtime":"2024-01-01T00:00:00Z"}"#,
+                "\n"
+            ))
+            .create_async()
+            .await;
+        let config_json = server
+            .mock("GET", "/index/config.json")
+            .with_body(r#"{"dl":"https://example.invalid/dl"}"#)
+            .create_async()
+            .await;
+
+        let registry = RegistryDefinition {
+            name: "corp".to_string(),
+            index_url: format!("sparse+{}/index/", server.url()),
+            token: Some("secret".to_string()),
+        };
+        let config = NetworkConfig {
+            max_retries: 0,
+            cache: CacheConfig {
+                enabled: false,
+                ..CacheConfig::default()
+            },
+            ..NetworkConfig::default()
+        };
+
+        let meta = fetch_registry_metadata(&registry, "foobar", "0.1.0", &config)
+            .await
+            .unwrap();
+        assert_eq!(meta.version_count, 2);
+        assert_eq!(meta.latest_version.as_deref(), Some("0.2.0"));
+        assert_eq!(meta.repository, None);
+
+        index.assert_async().await;
+        config_json.assert_async().await;
+    }
+}
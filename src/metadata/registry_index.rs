@@ -0,0 +1,456 @@
+//! Read crate version data from the local Cargo registry index
+//!
+//! Cargo keeps a copy of every index file it has fetched under
+//! `$CARGO_HOME/registry/index/<registry>-<hash>/`. For the sparse protocol
+//! (and for git indexes since Cargo 1.x) the entries live in `.cache/` in a
+//! small binary format; plain JSON-lines files are also read when present
+//! (local registries, old checked-out git indexes).
+
+use super::{CrateMetadata, CrateUsage};
+use crate::config::{cargo_home, LocalSourcesConfig};
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use semver::Version;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Version of the `.cache` file format written by Cargo
+const CACHE_VERSION: u8 = 3;
+
+/// Index directory name prefixes used for crates.io (sparse and git protocol)
+const CRATES_IO_INDEX_PREFIXES: [&str; 2] = ["index.crates.io-", "github.com-"];
+
+/// One published version, as recorded in the registry index
+#[derive(Debug, Clone, Deserialize)]
+pub struct IndexVersion {
+    pub vers: String,
+    #[serde(default)]
+    pub yanked: bool,
+    /// Publish time; only present in recent index entries
+    pub pubtime: Option<DateTime<Utc>>,
+    /// Declared `rust-version`; only present for versions published since Cargo 1.70
+    #[serde(default)]
+    pub rust_version: Option<String>,
+}
+
+/// Crate-level facts derived from the index for one resolved version
+#[derive(Debug, Clone)]
+pub struct IndexSummary {
+    pub version_count: u32,
+    pub is_yanked: bool,
+    pub latest_version: Option<String>,
+    pub first_published: Option<DateTime<Utc>>,
+    pub version_published: Option<DateTime<Utc>>,
+    pub latest_published: Option<DateTime<Utc>>,
+}
+
+/// Offline metadata provider backed by the local Cargo registry index
+#[derive(Debug, Clone, Default)]
+pub struct RegistryIndexProvider {
+    index_dirs: Vec<PathBuf>,
+}
+
+impl RegistryIndexProvider {
+    /// Create a provider from configuration, discovering crates.io index
+    /// directories under `$CARGO_HOME` unless explicit paths are given
+    pub fn new(config: &LocalSourcesConfig) -> Self {
+        if !config.use_registry_index {
+            return Self::default();
+        }
+
+        let index_dirs = if config.registry_index_paths.is_empty() {
+            discover_crates_io_indexes()
+        } else {
+            config.registry_index_paths.clone()
+        };
+
+        debug!("Using registry index directories: {:?}", index_dirs);
+        Self { index_dirs }
+    }
+
+    /// Load every known version of a crate.
+    ///
+    /// When several index directories know the crate, the most complete one wins.
+    pub fn versions(&self, name: &str) -> Result<Option<Vec<IndexVersion>>> {
+        let rel_path = index_path(name);
+        let mut best: Option<Vec<IndexVersion>> = None;
+
+        for dir in &self.index_dirs {
+            let versions = match read_index_entry(dir, &rel_path)? {
+                Some(versions) => versions,
+                None => continue,
+            };
+            if best.as_ref().is_none_or(|b| versions.len() > b.len()) {
+                best = Some(versions);
+            }
+        }
+
+        Ok(best)
+    }
+
+    /// Create a provider for an alternative registry's local index cache.
+    ///
+    /// Cargo names index directories `<host>-<hash>`; the hash can't be
+    /// recomputed reliably, so directories are matched by host.
+    pub fn for_registry(index_url: &str) -> Self {
+        let Some(host) = index_host(index_url) else {
+            return Self::default();
+        };
+        let prefix = format!("{}-", host);
+        Self {
+            index_dirs: discover_index_dirs(|name| name.starts_with(&prefix)),
+        }
+    }
+
+    /// Summarize the index entry of a crate for the version in use
+    pub fn summary(&self, name: &str, version: &str) -> Option<IndexSummary> {
+        match self.versions(name) {
+            Ok(Some(versions)) => summarize(&versions, version),
+            Ok(None) => None,
+            Err(e) => {
+                warn!("Failed to read registry index entry for {}: {}", name, e);
+                None
+            }
+        }
+    }
+
+    /// Read the registry's `config.json`, if the index cache has one
+    pub fn index_config(&self) -> Option<IndexConfig> {
+        self.index_dirs.iter().find_map(|dir| {
+            let content = fs::read(dir.join("config.json")).ok()?;
+            serde_json::from_slice(&content).ok()
+        })
+    }
+}
+
+/// The `config.json` at the root of a registry index
+#[derive(Debug, Clone, Deserialize)]
+pub struct IndexConfig {
+    /// Base URL of the registry web API, if it implements one
+    pub api: Option<String>,
+}
+
+/// Summarize index versions for the version in use
+pub fn summarize(versions: &[IndexVersion], version: &str) -> Option<IndexSummary> {
+    if versions.is_empty() {
+        return None;
+    }
+
+    let current = versions.iter().find(|v| v.vers == version);
+    let latest = latest_version(versions);
+    let latest_published = latest
+        .as_ref()
+        .and_then(|latest| versions.iter().find(|v| &v.vers == latest))
+        .and_then(|v| v.pubtime);
+
+    Some(IndexSummary {
+        version_count: versions.len() as u32,
+        is_yanked: current.map(|v| v.yanked).unwrap_or(false),
+        latest_version: latest,
+        first_published: versions.iter().filter_map(|v| v.pubtime).min(),
+        version_published: current.and_then(|v| v.pubtime),
+        latest_published,
+    })
+}
+
+impl IndexSummary {
+    /// Merge local index data into API metadata without going backwards.
+    ///
+    /// The index is only as fresh as the last `cargo update`, so it can add a
+    /// yank or a release the (possibly cached) API response lacks, but never
+    /// undo one.
+    pub fn apply_to(&self, meta: &mut CrateMetadata) {
+        meta.version_count = meta.version_count.max(self.version_count);
+        meta.is_yanked |= self.is_yanked;
+
+        let parse = |v: Option<&String>| v.and_then(|v| Version::parse(v).ok());
+        let index_latest = parse(self.latest_version.as_ref());
+        if index_latest.is_some() && index_latest > parse(meta.latest_version.as_ref()) {
+            meta.latest_version = self.latest_version.clone();
+            // Without a pubtime, the older release's date still bounds the libyears
+            meta.latest_published_at = self.latest_published.or(meta.latest_published_at);
+        }
+        meta.published_at = meta.published_at.or(self.version_published);
+        meta.created_at = meta.created_at.or(self.first_published);
+    }
+
+    /// Build metadata from the index alone, when the API is unavailable.
+    ///
+    /// Publish dates are only known for entries with `pubtime`. Fields the
+    /// index doesn't carry (downloads, repository, license) are left empty.
+    pub fn to_crate_metadata(&self, name: &str, version: &str) -> CrateMetadata {
+        CrateMetadata {
+            name: name.to_string(),
+            version: version.to_string(),
+            description: None,
+            license: None,
+            repository: None,
+            homepage: None,
+            downloads: 0,
+            recent_downloads: None,
+            created_at: self.first_published,
+            updated_at: self.version_published,
+            version_count: self.version_count,
+            crate_size: None,
+            owners: None,
+            published_by: None,
+            previous_publishers: Vec::new(),
+            is_yanked: self.is_yanked,
+            latest_version: self.latest_version.clone(),
+            published_at: self.version_published,
+            latest_published_at: self.latest_published,
+            usage: CrateUsage::default(),
+        }
+    }
+}
+
+/// Find crates.io index directories under `$CARGO_HOME/registry/index`
+fn discover_crates_io_indexes() -> Vec<PathBuf> {
+    discover_index_dirs(|name| CRATES_IO_INDEX_PREFIXES.iter().any(|p| name.starts_with(p)))
+}
+
+/// List index directories under `$CARGO_HOME/registry/index` whose name matches
+fn discover_index_dirs(matches: impl Fn(&str) -> bool) -> Vec<PathBuf> {
+    let Some(index_root) = cargo_home().map(|home| home.join("registry").join("index")) else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(&index_root) else {
+        return Vec::new();
+    };
+
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| path.file_name().and_then(|n| n.to_str()).is_some_and(&matches))
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Host part of an index URL (`sparse+https://host/path` -> `host`)
+fn index_host(index_url: &str) -> Option<&str> {
+    let url = index_url.strip_prefix("sparse+").unwrap_or(index_url);
+    let url = url.strip_prefix("registry+").unwrap_or(url);
+    let (_, rest) = url.split_once("://")?;
+    rest.split(['/', ':']).next().filter(|h| !h.is_empty())
+}
+
+/// Relative path of a crate's file inside an index, per Cargo's layout
+pub fn index_path(name: &str) -> PathBuf {
+    PathBuf::from(index_url_path(name))
+}
+
+/// Same as [`index_path`], `/`-separated for use in sparse index URLs
+pub fn index_url_path(name: &str) -> String {
+    let name = name.to_lowercase();
+    match name.len() {
+        1 => format!("1/{}", name),
+        2 => format!("2/{}", name),
+        3 => format!("3/{}/{}", &name[..1], name),
+        _ => format!("{}/{}/{}", &name[..2], &name[2..4], name),
+    }
+}
+
+/// Read a crate's entry from one index directory, trying the cache first
+fn read_index_entry(dir: &Path, rel_path: &Path) -> Result<Option<Vec<IndexVersion>>> {
+    let cache_path = dir.join(".cache").join(rel_path);
+    if cache_path.is_file() {
+        let bytes = fs::read(&cache_path)?;
+        if let Some(versions) = parse_cache_file(&bytes) {
+            return Ok(Some(versions));
+        }
+        debug!("Unrecognized index cache format: {}", cache_path.display());
+    }
+
+    let plain_path = dir.join(rel_path);
+    if plain_path.is_file() {
+        let content = fs::read_to_string(&plain_path)?;
+        return Ok(Some(parse_json_lines(&content)));
+    }
+
+    Ok(None)
+}
+
+/// Parse Cargo's index cache format:
+/// `u8 cache version | u32 index version | index revision \0 | (version \0 json \0)*`
+fn parse_cache_file(bytes: &[u8]) -> Option<Vec<IndexVersion>> {
+    if bytes.len() < 5 || bytes[0] != CACHE_VERSION {
+        return None;
+    }
+
+    let mut chunks = bytes[5..].split(|&b| b == 0);
+    chunks.next()?; // index revision (etag / commit)
+
+    let mut versions = Vec::new();
+    while let (Some(_version), Some(json)) = (chunks.next(), chunks.next()) {
+        if json.is_empty() {
+            continue;
+        }
+        match serde_json::from_slice::<IndexVersion>(json) {
+            Ok(v) => versions.push(v),
+            Err(e) => debug!("Skipping malformed index entry: {}", e),
+        }
+    }
+
+    Some(versions)
+}
+
+/// Parse a plain index file: one JSON object per line
+pub fn parse_json_lines(content: &str) -> Vec<IndexVersion> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Highest non-yanked stable version, falling back to the highest version overall
+fn latest_version(versions: &[IndexVersion]) -> Option<String> {
+    let parsed: Vec<(Version, &IndexVersion)> = versions
+        .iter()
+        .filter_map(|v| Version::parse(&v.vers).ok().map(|parsed| (parsed, v)))
+        .collect();
+
+    parsed
+        .iter()
+        .filter(|(ver, v)| !v.yanked && ver.pre.is_empty())
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .or_else(|| parsed.iter().max_by(|a, b| a.0.cmp(&b.0)))
+        .map(|(ver, _)| ver.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_bytes(entries: &[&str]) -> Vec<u8> {
+        let mut bytes = vec![CACHE_VERSION, 2, 0, 0, 0];
+        bytes.extend_from_slice(b"etag\0");
+        for json in entries {
+            let v: serde_json::Value = serde_json::from_str(json).unwrap();
+            bytes.extend_from_slice(v["vers"].as_str().unwrap().as_bytes());
+            bytes.push(0);
+            bytes.extend_from_slice(json.as_bytes());
+            bytes.push(0);
+        }
+        bytes
+    }
+
+    fn provider_for(dir: &Path) -> RegistryIndexProvider {
+        RegistryIndexProvider::new(&LocalSourcesConfig {
+            registry_index_paths: vec![dir.to_path_buf()],
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_index_path() {
+        assert_eq!(index_path("a"), PathBuf::from("1/a"));
+        assert_eq!(index_path("cc"), PathBuf::from("2/cc"));
+        assert_eq!(index_path("syn"), PathBuf::from("3/s/syn"));
+        assert_eq!(index_path("Serde"), PathBuf::from("se/rd/serde"));
+    }
+
+    #[test]
+    fn test_index_host() {
+        assert_eq!(index_host("sparse+https://cargo.corp.example/index/"), Some("cargo.corp.example"));
+        assert_eq!(index_host("https://git.corp.example:8443/index.git"), Some("git.corp.example"));
+        assert_eq!(index_host("not a url"), None);
+    }
+
+    #[test]
+    fn test_summary_from_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_dir = dir.path().join(".cache").join("fo").join("ob");
+        fs::create_dir_all(&cache_dir).unwrap();
+        fs::write(
+            cache_dir.join("foobar"),
+            cache_bytes(&[
+                r#"{"name":"foobar","vers":"1.0.0","yanked":false,"pubtime":"2020-01-01T00:00:00Z"}"#,
+                r#"{"name":"foobar","vers":"1.1.0","yanked":true,"pubtime":"2021-01-01T00:00:00Z"}"#,
+                r#"{"name":"foobar","vers":"2.0.0-rc.1","yanked":false}"#,
+            ]),
+        )
+        .unwrap();
+
+        let provider = provider_for(dir.path());
+        let summary = provider.summary("foobar", "1.1.0").unwrap();
+
+        assert_eq!(summary.version_count, 3);
+        assert!(summary.is_yanked);
+        assert_eq!(summary.latest_version.as_deref(), Some("1.0.0"));
+        assert_eq!(
+            summary.first_published.unwrap().to_rfc3339(),
+            "2020-01-01T00:00:00+00:00"
+        );
+
+        let meta = summary.to_crate_metadata("foobar", "1.1.0");
+        assert_eq!(meta.version_count, 3);
+        assert!(meta.updated_at.is_some());
+        assert!(provider.summary("missing", "1.0.0").is_none());
+    }
+
+    #[test]
+    fn test_plain_index_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let crate_dir = dir.path().join("3").join("f");
+        fs::create_dir_all(&crate_dir).unwrap();
+        fs::write(
+            crate_dir.join("foo"),
+            "{\"name\":\"foo\",\"vers\":\"0.1.0\"}\n{\"name\":\"foo\",\"vers\":\"0.2.0\"}\n",
+        )
+        .unwrap();
+
+        let provider = provider_for(dir.path());
+        let summary = provider.summary("foo", "0.1.0").unwrap();
+        assert_eq!(summary.version_count, 2);
+        assert_eq!(summary.latest_version.as_deref(), Some("0.2.0"));
+        // Entries without pubtime still provide version data, just no dates
+        let meta = summary.to_crate_metadata("foo", "0.1.0");
+        assert_eq!(meta.version_count, 2);
+        assert_eq!(meta.latest_version.as_deref(), Some("0.2.0"));
+        assert!(meta.created_at.is_none() && meta.updated_at.is_none());
+    }
+
+    #[test]
+    fn test_apply_to_never_goes_backwards() {
+        let published = Utc::now();
+        let api = CrateMetadata {
+            version_count: 5,
+            is_yanked: true,
+            latest_version: Some("1.4.0".to_string()),
+            latest_published_at: Some(published),
+            ..CrateMetadata::fixture("foo", "1.2.0")
+        };
+        let stale = IndexSummary {
+            version_count: 3,
+            is_yanked: false,
+            latest_version: Some("1.3.0".to_string()),
+            first_published: None,
+            version_published: None,
+            latest_published: None,
+        };
+
+        // A stale index keeps the API's yank, count and newer release
+        let mut meta = api.clone();
+        stale.apply_to(&mut meta);
+        assert_eq!(meta.version_count, 5);
+        assert!(meta.is_yanked);
+        assert_eq!(meta.latest_version.as_deref(), Some("1.4.0"));
+        assert_eq!(meta.latest_published_at, Some(published));
+
+        // A newer index adds its release without dropping the known date
+        let newer = IndexSummary {
+            version_count: 6,
+            latest_version: Some("1.10.0".to_string()),
+            ..stale
+        };
+        let mut meta = api;
+        newer.apply_to(&mut meta);
+        assert_eq!(meta.version_count, 6);
+        assert_eq!(meta.latest_version.as_deref(), Some("1.10.0"));
+        assert_eq!(meta.latest_published_at, Some(published));
+    }
+}
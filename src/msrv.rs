@@ -0,0 +1,183 @@
+//! Minimum supported Rust version (MSRV) compatibility
+//!
+//! A library's `rust-version` is only a promise if every dependency it pulls in
+//! builds with that compiler too. Dependencies declare theirs in the manifest;
+//! crates that predate the field (or whose manifest isn't on disk) may still
+//! have it recorded in the registry index.
+
+use crate::metadata::IndexVersion;
+use crate::types::{Msrv, MsrvSource};
+use cargo_metadata::{Metadata, Package};
+use semver::Version;
+
+/// The project's MSRV: the root package's `rust-version`, or the lowest one
+/// declared by a workspace member when there is no root package
+pub fn project_rust_version(metadata: &Metadata) -> Option<Version> {
+    if let Some(root) = metadata.root_package() {
+        return root.rust_version.clone();
+    }
+    metadata
+        .workspace_packages()
+        .into_iter()
+        .filter_map(|p| p.rust_version.clone())
+        .min()
+}
+
+/// Parse a `rust-version` value ("1.70" or "1.70.0")
+pub fn parse_rust_version(value: &str) -> Option<Version> {
+    let value = value.trim();
+    match value.matches('.').count() {
+        0 => Version::parse(&format!("{}.0.0", value)).ok(),
+        1 => Version::parse(&format!("{}.0", value)).ok(),
+        _ => Version::parse(value).ok(),
+    }
+}
+
+/// Determine a dependency's MSRV and compare it with the project's.
+///
+/// `index_versions` is the dependency's registry index entry, used when the
+/// manifest declares no `rust-version` and to find a compatible version.
+pub fn check_msrv(
+    package: Option<&Package>,
+    version: &str,
+    index_versions: Option<&[IndexVersion]>,
+    project: Option<&Version>,
+) -> Option<Msrv> {
+    let from_manifest = package
+        .and_then(|p| p.rust_version.clone())
+        .map(|v| (v, MsrvSource::Manifest));
+    let from_index = || {
+        index_versions?
+            .iter()
+            .find(|v| v.vers == version)?
+            .rust_version
+            .as_deref()
+            .and_then(parse_rust_version)
+            .map(|v| (v, MsrvSource::RegistryIndex))
+    };
+    let (rust_version, source) = from_manifest.or_else(from_index)?;
+
+    let exceeds_project = project.is_some_and(|project| &rust_version > project);
+    let compatible_version = match (exceeds_project, project, index_versions) {
+        (true, Some(project), Some(versions)) => newest_compatible(versions, version, project),
+        _ => None,
+    };
+
+    Some(Msrv {
+        rust_version: format_rust_version(&rust_version),
+        source,
+        exceeds_project,
+        compatible_version,
+    })
+}
+
+/// Newest published version whose declared `rust-version` fits the project's.
+///
+/// Versions semver-compatible with the one in use are preferred, since
+/// `cargo update --precise` can switch to them without touching manifests.
+/// Versions that don't declare a `rust-version` are skipped: their MSRV is unknown.
+pub fn newest_compatible(versions: &[IndexVersion], current: &str, project: &Version) -> Option<String> {
+    let current = Version::parse(current).ok()?;
+    let candidates: Vec<Version> = versions
+        .iter()
+        .filter(|v| !v.yanked)
+        .filter(|v| {
+            v.rust_version
+                .as_deref()
+                .and_then(parse_rust_version)
+                .is_some_and(|required| &required <= project)
+        })
+        .filter_map(|v| Version::parse(&v.vers).ok())
+        .filter(|v| v.pre.is_empty())
+        .collect();
+
+    candidates
+        .iter()
+        .filter(|v| semver_compatible(v, &current))
+        .max()
+        .or_else(|| candidates.iter().max())
+        .map(Version::to_string)
+}
+
+/// Whether Cargo's default (caret) requirement on `current` would accept `other`
+fn semver_compatible(other: &Version, current: &Version) -> bool {
+    match (current.major, current.minor) {
+        (0, 0) => other.major == 0 && other.minor == 0 && other.patch == current.patch,
+        (0, minor) => other.major == 0 && other.minor == minor,
+        (major, _) => other.major == major,
+    }
+}
+
+/// Shortest form of a Rust version ("1.70" rather than "1.70.0")
+pub fn format_rust_version(version: &Version) -> String {
+    if version.patch == 0 {
+        format!("{}.{}", version.major, version.minor)
+    } else {
+        version.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_version(vers: &str, rust_version: Option<&str>) -> IndexVersion {
+        IndexVersion {
+            vers: vers.to_string(),
+            yanked: false,
+            pubtime: None,
+            rust_version: rust_version.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_parse_rust_version() {
+        assert_eq!(parse_rust_version("1.70"), Some(Version::new(1, 70, 0)));
+        assert_eq!(parse_rust_version("1.70.1"), Some(Version::new(1, 70, 1)));
+        assert_eq!(parse_rust_version("1"), Some(Version::new(1, 0, 0)));
+        assert_eq!(parse_rust_version("latest"), None);
+    }
+
+    #[test]
+    fn test_check_msrv_from_index() {
+        let versions = vec![
+            index_version("1.0.0", None),
+            index_version("1.1.0", Some("1.60")),
+            index_version("1.2.0", Some("1.65")),
+            index_version("1.3.0", Some("1.74")),
+            index_version("2.0.0", Some("1.63")),
+        ];
+        let project = Version::new(1, 70, 0);
+
+        let msrv = check_msrv(None, "1.3.0", Some(&versions), Some(&project)).unwrap();
+        assert_eq!(msrv.rust_version, "1.74");
+        assert_eq!(msrv.source, MsrvSource::RegistryIndex);
+        assert!(msrv.exceeds_project);
+        assert_eq!(msrv.compatible_version.as_deref(), Some("1.2.0"));
+
+        let msrv = check_msrv(None, "1.2.0", Some(&versions), Some(&project)).unwrap();
+        assert!(!msrv.exceeds_project);
+        assert_eq!(msrv.compatible_version, None);
+
+        // Without a project MSRV nothing is flagged
+        assert!(!check_msrv(None, "1.3.0", Some(&versions), None).unwrap().exceeds_project);
+        // Unknown MSRV
+        assert!(check_msrv(None, "1.0.0", Some(&versions), Some(&project)).is_none());
+    }
+
+    #[test]
+    fn test_newest_compatible() {
+        let versions = vec![
+            index_version("0.4.0", Some("1.56")),
+            index_version("0.4.9", Some("1.63")),
+            index_version("0.5.0", Some("1.60")),
+            index_version("0.5.3", Some("1.80")),
+        ];
+        let project = Version::new(1, 65, 0);
+        assert_eq!(newest_compatible(&versions, "0.4.2", &project).as_deref(), Some("0.4.9"));
+        assert_eq!(newest_compatible(&versions, "0.5.3", &project).as_deref(), Some("0.5.0"));
+        // Nothing semver-compatible fits: fall back to any compatible release
+        assert_eq!(newest_compatible(&versions, "0.6.0", &project).as_deref(), Some("0.5.0"));
+        assert_eq!(newest_compatible(&versions, "0.4.2", &Version::new(1, 50, 0)), None);
+    }
+}
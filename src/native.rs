@@ -0,0 +1,246 @@
+//! Native library and bundled C/C++ code detection
+//!
+//! `-sys` crates and crates with a `links` key bind to native libraries, and
+//! many of them (or companion `-src` crates) vendor the library's sources.
+//! That code keeps its upstream license, which the Cargo `license` field of
+//! the wrapper crate doesn't describe. License files next to the bundled
+//! sources and the sources' own headers are scanned to recover it.
+
+use crate::error::Result;
+use crate::types::{BundledLicense, NativeCode};
+use cargo_metadata::Package;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+/// Extensions of C, C++ and assembly sources and headers
+const NATIVE_EXTENSIONS: &[&str] = &["c", "h", "cc", "cpp", "cxx", "hh", "hpp", "hxx", "s", "asm"];
+
+/// File name prefixes of license texts
+const LICENSE_FILE_PREFIXES: &[&str] = &["copying", "license", "licence", "copyright", "notice"];
+
+/// Source headers are only read for this many files (large vendored libraries have thousands)
+const MAX_HEADER_SCANS: usize = 1000;
+
+/// How much of each file is read when looking for a license
+const HEADER_BYTES: u64 = 8 * 1024;
+const LICENSE_FILE_BYTES: u64 = 64 * 1024;
+
+/// Files listed per bundled license
+const MAX_EVIDENCE_FILES: usize = 3;
+
+/// Detect native linking and bundled C/C++ code in a package.
+///
+/// Returns `None` for packages without a `links` key, `-sys` suffix or native sources.
+pub fn analyze_native_code(package: &Package) -> Result<Option<NativeCode>> {
+    let is_sys = package.name.ends_with("-sys") || package.name.ends_with("_sys");
+    let mut native = NativeCode {
+        links: package.links.clone(),
+        is_sys,
+        ..NativeCode::default()
+    };
+
+    let Some(package_dir) = package.manifest_path.parent().map(|p| p.as_std_path()) else {
+        return Ok(None);
+    };
+    let mut sources = Vec::new();
+    let mut license_files = Vec::new();
+    if package_dir.is_dir() {
+        collect_files(package_dir, package_dir, &mut sources, &mut license_files)?;
+    }
+    sources.sort();
+    license_files.sort();
+    native.source_files = sources.len();
+
+    if native.links.is_none() && !is_sys && sources.is_empty() {
+        return Ok(None);
+    }
+
+    let mut licenses: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    // The package root holds the Rust crate's own license files; only nested
+    // ones (next to vendored code) describe bundled sources
+    let nested_license_files = license_files.iter().filter(|path| path.contains('/'));
+    let files = nested_license_files
+        .map(|path| (path, LICENSE_FILE_BYTES))
+        .chain(sources.iter().take(MAX_HEADER_SCANS).map(|path| (path, HEADER_BYTES)));
+    for (path, limit) in files {
+        let text = read_prefix(&package_dir.join(path), limit)?;
+        if let Some(license) = detect_license(&text) {
+            licenses.entry(license).or_default().push(path.clone());
+        }
+    }
+
+    native.bundled_licenses = licenses
+        .into_iter()
+        .map(|(license, mut files)| {
+            files.truncate(MAX_EVIDENCE_FILES);
+            BundledLicense { license, files }
+        })
+        .collect();
+    Ok(Some(native))
+}
+
+fn collect_files(
+    root: &Path,
+    dir: &Path,
+    sources: &mut Vec<String>,
+    license_files: &mut Vec<String>,
+) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if path.is_dir() {
+            if !name.starts_with('.') && name != "target" {
+                collect_files(root, &path, sources, license_files)?;
+            }
+            continue;
+        }
+        let Ok(relative) = path.strip_prefix(root) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let extension = name.rsplit_once('.').map(|(_, ext)| ext).unwrap_or_default();
+        if NATIVE_EXTENSIONS.contains(&extension) {
+            sources.push(relative);
+        } else if extension != "rs" && LICENSE_FILE_PREFIXES.iter().any(|prefix| name.starts_with(prefix)) {
+            license_files.push(relative);
+        }
+    }
+    Ok(())
+}
+
+fn read_prefix(path: &Path, limit: u64) -> Result<String> {
+    let mut bytes = Vec::new();
+    File::open(path)?.take(limit).read_to_end(&mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Identify the license in a license file or source header.
+///
+/// An `SPDX-License-Identifier` tag wins; otherwise well-known license
+/// wording is matched after stripping comment markers.
+pub fn detect_license(text: &str) -> Option<String> {
+    if let Some(position) = text.find("SPDX-License-Identifier:") {
+        let rest = &text[position + "SPDX-License-Identifier:".len()..];
+        let expression = rest
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .trim_end_matches("*/")
+            .trim_end_matches("-->")
+            .trim();
+        if !expression.is_empty() {
+            return Some(expression.to_string());
+        }
+    }
+
+    let text = normalize(text);
+    let has = |phrase: &str| text.contains(phrase);
+
+    let license = if has("gnu lesser general public license") || has("gnu library general public license") {
+        if has("version 3") {
+            "LGPL-3.0"
+        } else {
+            "LGPL-2.1"
+        }
+    } else if has("gnu affero general public license") {
+        "AGPL-3.0"
+    } else if has("gnu general public license") {
+        match (has("version 3"), has("linking exception")) {
+            (_, true) => "GPL-2.0 WITH linking-exception",
+            (true, false) => "GPL-3.0",
+            (false, false) => "GPL-2.0",
+        }
+    } else if has("mozilla public license") {
+        "MPL-2.0"
+    } else if has("apache license") {
+        "Apache-2.0"
+    } else if has("boost software license") {
+        "BSL-1.0"
+    } else if has("openssl license") {
+        "OpenSSL"
+    } else if has("permission is hereby granted, free of charge") {
+        "MIT"
+    } else if has("permission to use, copy, modify, and distribute this software for any purpose")
+        || has("permission to use, copy, modify, and/or distribute this software for any purpose")
+    {
+        "ISC"
+    } else if has("redistribution and use in source and binary forms") {
+        if has("neither the name") {
+            "BSD-3-Clause"
+        } else {
+            "BSD-2-Clause"
+        }
+    } else if has("this software is provided 'as-is'") {
+        "Zlib"
+    } else if has("disclaims copyright") || has("placed in the public domain") || has("released into the public domain") {
+        "public-domain"
+    } else {
+        return None;
+    };
+    Some(license.to_string())
+}
+
+/// Lowercase, drop comment markers and collapse whitespace so wrapped
+/// license text matches regardless of the comment style
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .replace(['*', '#', '/', ';'], " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_license() {
+        assert_eq!(
+            detect_license("/* SPDX-License-Identifier: GPL-2.0-only */\nint x;").as_deref(),
+            Some("GPL-2.0-only")
+        );
+        assert_eq!(
+            detect_license(
+                "/*\n * This library is free software; you can redistribute it under the terms of the GNU Lesser\n * General Public License version 2.1\n */"
+            )
+            .as_deref(),
+            Some("LGPL-2.1")
+        );
+        assert_eq!(
+            detect_license(" * Licensed under the Apache License 2.0 (the \"License\").").as_deref(),
+            Some("Apache-2.0")
+        );
+        assert_eq!(
+            detect_license("Redistribution and use in source and binary forms, with or without\n * modification, are permitted. Neither the name of the project").as_deref(),
+            Some("BSD-3-Clause")
+        );
+        assert_eq!(detect_license("int main(void) { return 0; }"), None);
+    }
+
+    #[test]
+    fn test_collect_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("vendor/lib/src")).unwrap();
+        fs::write(root.join("LICENSE-MIT"), "Permission is hereby granted, free of charge").unwrap();
+        fs::write(root.join("vendor/lib/COPYING"), "GNU General Public License version 3").unwrap();
+        fs::write(root.join("vendor/lib/src/lib.c"), "/* SPDX-License-Identifier: Zlib */").unwrap();
+        fs::write(root.join("vendor/lib/src/lib.h"), "int f(void);").unwrap();
+        fs::write(root.join("build.rs"), "fn main() {}").unwrap();
+
+        let mut sources = Vec::new();
+        let mut license_files = Vec::new();
+        collect_files(root, root, &mut sources, &mut license_files).unwrap();
+        sources.sort();
+        license_files.sort();
+        assert_eq!(sources, vec!["vendor/lib/src/lib.c", "vendor/lib/src/lib.h"]);
+        assert_eq!(license_files, vec!["LICENSE-MIT", "vendor/lib/COPYING"]);
+    }
+}
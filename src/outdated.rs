@@ -0,0 +1,103 @@
+//! Outdated-version and libyear analysis
+//!
+//! Compares the version in use with the latest stable release the registry
+//! reports. The semver distance counts releases in the highest component that
+//! differs (1.2.3 -> 2.0.1 is one major release behind); the libyear figure is
+//! the time between the two publish dates.
+
+use crate::metadata::CrateMetadata;
+use crate::types::Outdated;
+use semver::Version;
+
+/// Compare the version in use with the latest stable release.
+///
+/// Returns `None` when the registry didn't report a parseable latest version.
+pub fn check_outdated(version: &str, meta: &CrateMetadata) -> Option<Outdated> {
+    let latest_version = meta.latest_version.as_deref()?;
+    let latest = Version::parse(latest_version).ok()?;
+    let current = Version::parse(version).ok()?;
+
+    let (major_behind, minor_behind, patch_behind) = semver_distance(&current, &latest);
+    // A prerelease or yanked-over version newer than the latest stable one isn't behind
+    let behind = current < latest;
+    let days_behind = match (meta.published_at, meta.latest_published_at) {
+        (Some(published), Some(latest_published)) if behind => {
+            Some((latest_published - published).num_days().max(0))
+        }
+        (Some(_), Some(_)) => Some(0),
+        _ => None,
+    };
+
+    Some(Outdated {
+        latest_version: latest_version.to_string(),
+        major_behind,
+        minor_behind,
+        patch_behind,
+        days_behind,
+    })
+}
+
+/// Releases between two versions in the highest component that differs
+fn semver_distance(current: &Version, latest: &Version) -> (u64, u64, u64) {
+    if latest <= current {
+        (0, 0, 0)
+    } else if latest.major != current.major {
+        (latest.major - current.major, 0, 0)
+    } else if latest.minor != current.minor {
+        (0, latest.minor - current.minor, 0)
+    } else {
+        // Same major.minor: a stable release after a prerelease of the same
+        // version counts as one patch behind
+        (0, 0, (latest.patch - current.patch).max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn crate_meta(latest: &str, published: (i32, u32, u32), latest_published: (i32, u32, u32)) -> CrateMetadata {
+        let date = |(y, m, d)| Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap();
+        CrateMetadata {
+            version_count: 10,
+            latest_version: Some(latest.to_string()),
+            published_at: Some(date(published)),
+            latest_published_at: Some(date(latest_published)),
+            ..CrateMetadata::fixture("demo", "1.2.3")
+        }
+    }
+
+    #[test]
+    fn test_semver_distance() {
+        let v = |s: &str| Version::parse(s).unwrap();
+        assert_eq!(semver_distance(&v("1.2.3"), &v("3.0.1")), (2, 0, 0));
+        assert_eq!(semver_distance(&v("1.2.3"), &v("1.5.0")), (0, 3, 0));
+        assert_eq!(semver_distance(&v("1.2.3"), &v("1.2.7")), (0, 0, 4));
+        assert_eq!(semver_distance(&v("1.2.3-rc.1"), &v("1.2.3")), (0, 0, 1));
+        assert_eq!(semver_distance(&v("1.2.3"), &v("1.2.3")), (0, 0, 0));
+        assert_eq!(semver_distance(&v("2.0.0-beta.1"), &v("1.9.0")), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_check_outdated() {
+        let meta = crate_meta("2.1.0", (2022, 1, 1), (2023, 7, 2));
+        let outdated = check_outdated("1.2.3", &meta).unwrap();
+        assert_eq!(outdated.latest_version, "2.1.0");
+        assert_eq!((outdated.major_behind, outdated.minor_behind, outdated.patch_behind), (1, 0, 0));
+        assert_eq!(outdated.days_behind, Some(547));
+        assert!(outdated.is_behind());
+        assert_eq!(outdated.describe(), "1 major");
+        assert!((outdated.libyears() - 1.4976).abs() < 0.001);
+
+        let current = check_outdated("2.1.0", &meta).unwrap();
+        assert!(!current.is_behind());
+        assert_eq!(current.days_behind, Some(0));
+
+        let mut unknown = meta.clone();
+        unknown.published_at = None;
+        assert_eq!(check_outdated("1.2.3", &unknown).unwrap().days_behind, None);
+        unknown.latest_version = None;
+        assert!(check_outdated("1.2.3", &unknown).is_none());
+    }
+}
@@ -0,0 +1,174 @@
+//! Detect crate ownership changes against a previous audit
+
+use crate::metadata::CrateMetadata;
+use crate::types::{DependencyMetrics, OwnershipChange, OwnershipChangeKind, Severity};
+
+/// Compare a crate's owners and publisher with the registry history and the
+/// metrics recorded for it by the baseline report
+pub fn detect_ownership_changes(
+    crate_meta: &CrateMetadata,
+    previous: Option<&DependencyMetrics>,
+) -> Vec<OwnershipChange> {
+    let mut changes = Vec::new();
+
+    // Only compare owner sets that were known on both runs
+    let previous_owners = previous
+        .filter(|p| p.maintainer_count.is_some())
+        .map(|p| owner_list(&p.owners, &p.team_owners));
+    let current_owners = crate_meta
+        .owners
+        .as_ref()
+        .map(|o| owner_list(&o.users, &o.teams));
+
+    if let (Some(before), Some(after)) = (&previous_owners, &current_owners) {
+        if before != after {
+            let added: Vec<&str> = after.iter().filter(|o| !before.contains(o)).map(String::as_str).collect();
+            let removed: Vec<&str> = before.iter().filter(|o| !after.contains(o)).map(String::as_str).collect();
+            changes.push(OwnershipChange {
+                kind: OwnershipChangeKind::OwnersChanged,
+                severity: Severity::High,
+                message: format!(
+                    "Owners of {} changed since the last audit (added: [{}], removed: [{}])",
+                    crate_meta.name,
+                    added.join(", "),
+                    removed.join(", ")
+                ),
+                previous_owners: before.clone(),
+                current_owners: after.clone(),
+                publisher: None,
+            });
+        }
+    }
+
+    if let Some(publisher) = &crate_meta.published_by {
+        let seen = seen_publishers(crate_meta, previous);
+        // With no earlier publisher on record there is nothing to compare against
+        if !seen.is_empty() && !seen.contains(publisher) {
+            changes.push(OwnershipChange {
+                kind: OwnershipChangeKind::NewPublisher,
+                severity: Severity::High,
+                message: format!(
+                    "{} v{} was published by {}, who never published {} before (previous publishers: {})",
+                    crate_meta.name,
+                    crate_meta.version,
+                    publisher,
+                    crate_meta.name,
+                    seen.join(", ")
+                ),
+                previous_owners: previous_owners.unwrap_or_default(),
+                current_owners: current_owners.unwrap_or_default(),
+                publisher: Some(publisher.clone()),
+            });
+        }
+    }
+
+    changes
+}
+
+/// Every user known to have published the crate, including the audited version,
+/// for the next run to compare against
+pub fn known_publishers(crate_meta: &CrateMetadata, previous: Option<&DependencyMetrics>) -> Vec<String> {
+    let mut publishers = seen_publishers(crate_meta, previous);
+    if let Some(publisher) = &crate_meta.published_by {
+        if !publishers.contains(publisher) {
+            publishers.push(publisher.clone());
+            publishers.sort();
+        }
+    }
+    publishers
+}
+
+/// Publishers of earlier versions, plus those recorded by the baseline report
+fn seen_publishers(crate_meta: &CrateMetadata, previous: Option<&DependencyMetrics>) -> Vec<String> {
+    let mut seen = crate_meta.previous_publishers.clone();
+    if let Some(previous) = previous {
+        seen.extend(previous.publishers.iter().cloned());
+    }
+    seen.sort();
+    seen.dedup();
+    seen
+}
+
+fn owner_list(users: &[String], teams: &[String]) -> Vec<String> {
+    let mut owners: Vec<String> = users.iter().chain(teams).cloned().collect();
+    owners.sort();
+    owners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::CrateOwners;
+    use crate::types::ComponentScores;
+
+    fn crate_meta(users: &[&str], published_by: Option<&str>, previous_publishers: &[&str]) -> CrateMetadata {
+        CrateMetadata {
+            version_count: 3,
+            owners: Some(CrateOwners {
+                users: users.iter().map(|u| u.to_string()).collect(),
+                teams: Vec::new(),
+            }),
+            published_by: published_by.map(String::from),
+            previous_publishers: previous_publishers.iter().map(|p| p.to_string()).collect(),
+            ..CrateMetadata::fixture("demo", "1.1.0")
+        }
+    }
+
+    fn baseline(owners: &[&str], publishers: &[&str]) -> DependencyMetrics {
+        DependencyMetrics {
+            days_since_last_update: None,
+            version_count: None,
+            maintainer_count: Some(owners.len() as u32),
+            owners: owners.iter().map(|o| o.to_string()).collect(),
+            team_owners: Vec::new(),
+            published_by: None,
+            publishers: publishers.iter().map(|p| p.to_string()).collect(),
+            reverse_dependencies: None,
+            download_trend: None,
+            daily_downloads: Vec::new(),
+            version_downloads: Vec::new(),
+            repository: None,
+            openssf_score: None,
+            openssf_checks: Vec::new(),
+            unsafe_usage: None,
+            scores: ComponentScores {
+                recency: 0.0,
+                maintenance: 0.0,
+                community: 0.0,
+                stability: 0.0,
+                security: 0.0,
+                unsafe_code: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_unchanged_ownership() {
+        let meta = crate_meta(&["alice"], Some("alice"), &["alice"]);
+        assert!(detect_ownership_changes(&meta, Some(&baseline(&["alice"], &["alice"]))).is_empty());
+        assert!(detect_ownership_changes(&meta, None).is_empty());
+    }
+
+    #[test]
+    fn test_owner_added_and_new_publisher() {
+        let meta = crate_meta(&["alice", "mallory"], Some("mallory"), &["alice"]);
+        let previous = baseline(&["alice"], &["alice"]);
+
+        let changes = detect_ownership_changes(&meta, Some(&previous));
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].kind, OwnershipChangeKind::OwnersChanged);
+        assert_eq!(changes[0].severity, Severity::High);
+        assert_eq!(changes[0].previous_owners, vec!["alice"]);
+        assert_eq!(changes[0].current_owners, vec!["alice", "mallory"]);
+        assert_eq!(changes[1].kind, OwnershipChangeKind::NewPublisher);
+        assert_eq!(changes[1].publisher.as_deref(), Some("mallory"));
+
+        assert_eq!(known_publishers(&meta, Some(&previous)), vec!["alice", "mallory"]);
+    }
+
+    #[test]
+    fn test_first_publisher_is_not_flagged() {
+        let meta = crate_meta(&["alice"], Some("alice"), &[]);
+        assert!(detect_ownership_changes(&meta, None).is_empty());
+    }
+}
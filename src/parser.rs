@@ -1,6 +1,7 @@
 //! Parser for Cargo.toml and Cargo.lock to extract dependency information
 
 use crate::error::{AuditError, Result};
+use crate::registries::{is_crates_io_index, Registries};
 use crate::types::DependencySource;
 use cargo_metadata::{CargoOpt, Metadata, MetadataCommand, Package, PackageId};
 use std::collections::HashSet;
@@ -17,13 +18,17 @@ pub struct ParsedDependency {
 }
 
 /// Parse a Rust project and extract all dependencies
-pub fn parse_project(project_path: &Path) -> Result<Vec<ParsedDependency>> {
-    let metadata = get_cargo_metadata(project_path)?;
-    extract_dependencies(&metadata)
+pub fn parse_project(
+    project_path: &Path,
+    registries: &Registries,
+    offline: bool,
+) -> Result<Vec<ParsedDependency>> {
+    let metadata = get_cargo_metadata(project_path, offline)?;
+    extract_dependencies(&metadata, registries)
 }
 
-/// Get cargo metadata for a project
-fn get_cargo_metadata(project_path: &Path) -> Result<Metadata> {
+/// Get cargo metadata for a project (without updating the index when offline)
+fn get_cargo_metadata(project_path: &Path, offline: bool) -> Result<Metadata> {
     let manifest_path = project_path.join("Cargo.toml");
     
     if !manifest_path.exists() {
@@ -33,16 +38,17 @@ fn get_cargo_metadata(project_path: &Path) -> Result<Metadata> {
         )));
     }
 
-    let metadata = MetadataCommand::new()
-        .manifest_path(&manifest_path)
-        .features(CargoOpt::AllFeatures)
-        .exec()?;
+    let mut command = MetadataCommand::new();
+    command.manifest_path(&manifest_path).features(CargoOpt::AllFeatures);
+    if offline {
+        command.other_options(vec!["--offline".to_string()]);
+    }
 
-    Ok(metadata)
+    Ok(command.exec()?)
 }
 
 /// Extract all dependencies from cargo metadata
-fn extract_dependencies(metadata: &Metadata) -> Result<Vec<ParsedDependency>> {
+fn extract_dependencies(metadata: &Metadata, registries: &Registries) -> Result<Vec<ParsedDependency>> {
     let mut dependencies = Vec::new();
     
     // Get the root package(s) - handle workspace projects
@@ -87,7 +93,7 @@ fn extract_dependencies(metadata: &Metadata) -> Result<Vec<ParsedDependency>> {
 
             if let Some(pkg) = metadata.packages.iter().find(|p| p.id == node.id) {
                 let is_direct = direct_deps.contains(&pkg.id);
-                let source = determine_source(pkg);
+                let source = determine_source(pkg, registries);
 
                 dependencies.push(ParsedDependency {
                     name: pkg.name.clone(),
@@ -104,30 +110,9 @@ fn extract_dependencies(metadata: &Metadata) -> Result<Vec<ParsedDependency>> {
 }
 
 /// Determine the source of a package
-fn determine_source(package: &Package) -> DependencySource {
+fn determine_source(package: &Package, registries: &Registries) -> DependencySource {
     if let Some(source) = &package.source {
-        let source_str = source.repr.as_str();
-        
-        if source_str.starts_with("registry+") {
-            DependencySource::CratesIo
-        } else if source_str.starts_with("git+") {
-            // Extract git URL
-            let url = source_str
-                .strip_prefix("git+")
-                .and_then(|s| s.split('?').next())
-                .unwrap_or(source_str)
-                .to_string();
-            DependencySource::Git { url }
-        } else if source_str.starts_with("path+") {
-            let path = source_str
-                .strip_prefix("path+file://")
-                .or_else(|| source_str.strip_prefix("path+"))
-                .unwrap_or(source_str)
-                .to_string();
-            DependencySource::Path { path }
-        } else {
-            DependencySource::Unknown
-        }
+        source_from_repr(source.repr.as_str(), registries)
     } else {
         // No source usually means it's a path dependency or workspace member
         DependencySource::Path {
@@ -138,9 +123,66 @@ fn determine_source(package: &Package) -> DependencySource {
     }
 }
 
+/// Map a `cargo metadata` source string to a dependency source
+fn source_from_repr(source_str: &str, registries: &Registries) -> DependencySource {
+    if source_str.starts_with("registry+") || source_str.starts_with("sparse+") {
+        if is_crates_io_index(source_str) {
+            DependencySource::CratesIo
+        } else {
+            let index_url = source_str
+                .strip_prefix("registry+")
+                .unwrap_or(source_str)
+                .to_string();
+            let name = registries
+                .find_by_index(&index_url)
+                .map(|r| r.name.clone());
+            DependencySource::Registry { index_url, name }
+        }
+    } else if let Some(git) = source_str.strip_prefix("git+") {
+        parse_git_source(git)
+    } else if source_str.starts_with("path+") {
+        let path = source_str
+            .strip_prefix("path+file://")
+            .or_else(|| source_str.strip_prefix("path+"))
+            .unwrap_or(source_str)
+            .to_string();
+        DependencySource::Path { path }
+    } else {
+        DependencySource::Unknown
+    }
+}
+
+/// Split `url?branch=main#sha` into the repository URL, reference and pinned commit
+fn parse_git_source(git: &str) -> DependencySource {
+    let (rest, commit) = match git.split_once('#') {
+        Some((rest, commit)) => (rest, Some(commit.to_string())),
+        None => (git, None),
+    };
+    let (url, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+    let mut branch = None;
+    let mut tag = None;
+    let mut rev = None;
+    for pair in query.split('&') {
+        match pair.split_once('=') {
+            Some(("branch", value)) => branch = Some(value.to_string()),
+            Some(("tag", value)) => tag = Some(value.to_string()),
+            Some(("rev", value)) => rev = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    DependencySource::Git {
+        url: url.to_string(),
+        branch,
+        tag,
+        rev: commit.or(rev),
+    }
+}
+
 /// Get the name of the project from its Cargo.toml
-pub fn get_project_name(project_path: &Path) -> Result<String> {
-    let metadata = get_cargo_metadata(project_path)?;
+pub fn get_project_name(project_path: &Path, offline: bool) -> Result<String> {
+    let metadata = get_cargo_metadata(project_path, offline)?;
     
     if let Some(resolve) = &metadata.resolve {
         if let Some(root_id) = &resolve.root {
@@ -164,7 +206,42 @@ mod tests {
 
     #[test]
     fn test_determine_source() {
-        // Test would require creating mock Package instances
-        // For now, this is a placeholder
+        let registries = Registries::default();
+
+        assert!(matches!(
+            source_from_repr("registry+https://github.com/rust-lang/crates.io-index", &registries),
+            DependencySource::CratesIo
+        ));
+        assert!(matches!(
+            source_from_repr("sparse+https://index.crates.io/", &registries),
+            DependencySource::CratesIo
+        ));
+
+        match source_from_repr("sparse+https://cargo.corp.example/index/", &registries) {
+            DependencySource::Registry { index_url, name } => {
+                assert_eq!(index_url, "sparse+https://cargo.corp.example/index/");
+                assert_eq!(name, None);
+            }
+            other => panic!("unexpected source: {:?}", other),
+        }
+
+        match source_from_repr("git+https://github.com/foo/bar?branch=main#abc123", &registries) {
+            DependencySource::Git { url, branch, tag, rev } => {
+                assert_eq!(url, "https://github.com/foo/bar");
+                assert_eq!(branch.as_deref(), Some("main"));
+                assert_eq!(tag, None);
+                assert_eq!(rev.as_deref(), Some("abc123"));
+            }
+            other => panic!("unexpected source: {:?}", other),
+        }
+
+        match source_from_repr("git+https://gitlab.com/foo/bar?rev=def456", &registries) {
+            DependencySource::Git { url, branch, rev, .. } => {
+                assert_eq!(url, "https://gitlab.com/foo/bar");
+                assert_eq!(branch, None);
+                assert_eq!(rev.as_deref(), Some("def456"));
+            }
+            other => panic!("unexpected source: {:?}", other),
+        }
     }
 }
@@ -0,0 +1,168 @@
+//! Evaluate configured policy rules against dependency findings
+
+use crate::config::PolicyConfig;
+use crate::types::{BuildCapability, BuildScriptAnalysis, OpenSSFCheck, PolicyViolation};
+
+/// Check OpenSSF Scorecard results against the per-check minimums
+pub fn check_scorecard(checks: &[OpenSSFCheck], policy: &PolicyConfig) -> Vec<PolicyViolation> {
+    let mut violations = Vec::new();
+
+    for (name, minimum) in &policy.min_scorecard_checks {
+        let Some(check) = checks.iter().find(|c| c.name.eq_ignore_ascii_case(name)) else {
+            continue;
+        };
+        // -1 means Scorecard could not evaluate the check
+        if check.score >= 0 && check.score < *minimum {
+            violations.push(PolicyViolation {
+                rule: format!("scorecard:{}", check.name),
+                message: format!(
+                    "OpenSSF check {} scored {} (minimum {}): {}",
+                    check.name, check.score, minimum, check.reason
+                ),
+            });
+        }
+    }
+
+    violations.sort_by(|a, b| a.rule.cmp(&b.rule));
+    violations
+}
+
+/// Check build script rules.
+///
+/// `previous` is the crate's `has_build_script` and `build_script` from the
+/// baseline report, `(false, None)` for crates added since. The rules about
+/// *new* build scripts and capabilities only apply when a baseline was loaded,
+/// and capabilities only when the baseline could analyze the build script too.
+pub fn check_build_script(
+    has_build_script: bool,
+    analysis: Option<&BuildScriptAnalysis>,
+    previous: Option<(bool, Option<&BuildScriptAnalysis>)>,
+    policy: &PolicyConfig,
+) -> Vec<PolicyViolation> {
+    let mut violations = Vec::new();
+
+    if policy.deny_new_build_scripts
+        && has_build_script
+        && previous.is_some_and(|(had_build_script, _)| !had_build_script)
+    {
+        violations.push(PolicyViolation {
+            rule: "new-build-script".to_string(),
+            message: "Build script added since the baseline report".to_string(),
+        });
+    }
+
+    // Capabilities the baseline knew of, or `None` when there is nothing to compare with
+    let previous_capabilities = previous.and_then(|(had_build_script, previous)| {
+        if had_build_script {
+            previous.map(|p| p.capabilities.as_slice())
+        } else {
+            Some(&[][..])
+        }
+    });
+
+    let Some(analysis) = analysis else {
+        return violations;
+    };
+    for &capability in &analysis.capabilities {
+        if policy.deny_build_script_capabilities.contains(&capability) {
+            violations.push(PolicyViolation {
+                rule: format!("build-script:{}", capability),
+                message: format!("Build script {}", describe(capability, analysis)),
+            });
+        } else if policy.deny_new_build_script_capabilities.contains(&capability)
+            && previous_capabilities.as_ref().is_some_and(|p| !p.contains(&capability))
+        {
+            violations.push(PolicyViolation {
+                rule: format!("new-build-script:{}", capability),
+                message: format!("Build script newly {}", describe(capability, analysis)),
+            });
+        }
+    }
+
+    violations
+}
+
+fn describe(capability: BuildCapability, analysis: &BuildScriptAnalysis) -> String {
+    match capability {
+        BuildCapability::SpawnsProcess => format!("spawns processes: {}", analysis.commands.join(", ")),
+        BuildCapability::Network => format!("uses the network: {}", analysis.network_crates.join(", ")),
+        BuildCapability::ReadsEnv => format!("reads environment variables: {}", analysis.env_vars.join(", ")),
+        BuildCapability::WritesOutsideOutDir => {
+            format!("writes outside OUT_DIR: {}", analysis.writes.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(name: &str, score: i32) -> OpenSSFCheck {
+        OpenSSFCheck {
+            name: name.to_string(),
+            score,
+            reason: "reason".to_string(),
+            details: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_check_scorecard() {
+        let mut policy = PolicyConfig::default();
+        policy.min_scorecard_checks.insert("dangerous-workflow".to_string(), 10);
+        policy.min_scorecard_checks.insert("Code-Review".to_string(), 5);
+        policy.min_scorecard_checks.insert("Fuzzing".to_string(), 5);
+
+        let checks = vec![
+            check("Dangerous-Workflow", 0),
+            check("Code-Review", 8),
+            check("Fuzzing", -1),
+        ];
+
+        let violations = check_scorecard(&checks, &policy);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, "scorecard:Dangerous-Workflow");
+        assert!(check_scorecard(&[], &policy).is_empty());
+    }
+
+    #[test]
+    fn test_check_build_script() {
+        let analysis = BuildScriptAnalysis {
+            capabilities: vec![BuildCapability::SpawnsProcess, BuildCapability::ReadsEnv],
+            commands: vec!["git".to_string()],
+            env_vars: vec!["HOME".to_string()],
+            ..BuildScriptAnalysis::default()
+        };
+        let policy = PolicyConfig {
+            deny_build_script_capabilities: vec![BuildCapability::ReadsEnv],
+            deny_new_build_script_capabilities: vec![BuildCapability::SpawnsProcess],
+            deny_new_build_scripts: true,
+            ..PolicyConfig::default()
+        };
+
+        let rules = |previous: Option<(bool, Option<&BuildScriptAnalysis>)>| -> Vec<String> {
+            check_build_script(true, Some(&analysis), previous, &policy)
+                .into_iter()
+                .map(|v| v.rule)
+                .collect()
+        };
+
+        // Without a baseline nothing is new
+        assert_eq!(rules(None), vec!["build-script:reads-env"]);
+        // A dependency added since the baseline, or one that had no build script
+        assert_eq!(
+            rules(Some((false, None))),
+            vec!["new-build-script", "new-build-script:spawns-process", "build-script:reads-env"]
+        );
+        // Spawning processes was already there last time
+        assert_eq!(rules(Some((true, Some(&analysis)))), vec!["build-script:reads-env"]);
+        let quiet = BuildScriptAnalysis::default();
+        assert_eq!(
+            rules(Some((true, Some(&quiet)))),
+            vec!["new-build-script:spawns-process", "build-script:reads-env"]
+        );
+        // The baseline couldn't read the build script sources
+        assert_eq!(rules(Some((true, None))), vec!["build-script:reads-env"]);
+        assert!(check_build_script(false, None, Some((false, None)), &policy).is_empty());
+    }
+}
@@ -0,0 +1,555 @@
+//! Verify that a crate's declared repository actually contains it
+//!
+//! The `repository` field in a crate's manifest is self-declared. A crate can
+//! point at a popular project it has nothing to do with and inherit its stars
+//! and activity. This check looks for a `Cargo.toml` with the crate's name in
+//! the repository and, when the published package records its commit in
+//! `.cargo_vcs_info.json` (or a release tag exists), compares the published
+//! Rust sources and original manifest with the repository at that revision.
+//!
+//! Repository trees come from Cargo's bare clones in `$CARGO_HOME/git/db`
+//! when available, otherwise from the forge API. Files are compared by git
+//! blob hash, so each revision costs a single tree request.
+
+use crate::config::{ForgeKind, NetworkConfig};
+use crate::error::Result;
+use crate::metadata::{
+    fetch_gitea_file, fetch_gitea_tree, fetch_github_file, fetch_github_tree, fetch_gitlab_file,
+    fetch_gitlab_tree, find_git_database, read_git_file, read_git_tree, resolve_forge, RepoTree,
+};
+use crate::types::{Provenance, ProvenanceStatus};
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Metadata file Cargo adds to packages published from a git checkout
+const VCS_INFO_FILE: &str = ".cargo_vcs_info.json";
+
+/// Manifests fetched while searching a repository without VCS info
+const MAX_MANIFEST_CANDIDATES: usize = 10;
+
+/// The parts of a published package that are compared with the repository
+#[derive(Debug)]
+struct PublishedCrate {
+    /// Git blob hashes of the Rust sources and the original `Cargo.toml`, keyed by package path
+    blobs: BTreeMap<String, String>,
+    vcs: Option<VcsInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VcsInfo {
+    git: VcsGit,
+    #[serde(default)]
+    path_in_vcs: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct VcsGit {
+    sha1: String,
+    #[serde(default)]
+    dirty: bool,
+}
+
+/// Where repository trees and files are read from
+#[derive(Debug)]
+enum RepoAccess<'a> {
+    /// Cargo's bare clone of the repository
+    Local(PathBuf),
+    /// The forge API serving the repository URL
+    Forge { url: &'a str, kind: ForgeKind },
+}
+
+impl RepoAccess<'_> {
+    async fn tree(&self, rev: &str, config: &NetworkConfig) -> Result<Option<RepoTree>> {
+        match self {
+            Self::Local(git_dir) => read_git_tree(git_dir, rev),
+            Self::Forge { url, kind: ForgeKind::GitHub } => fetch_github_tree(url, rev, config).await,
+            Self::Forge { url, kind: ForgeKind::GitLab } => fetch_gitlab_tree(url, rev, config).await,
+            Self::Forge { url, kind: ForgeKind::Gitea } => fetch_gitea_tree(url, rev, config).await,
+        }
+    }
+
+    async fn file(&self, rev: &str, path: &str, config: &NetworkConfig) -> Result<Option<Vec<u8>>> {
+        match self {
+            Self::Local(git_dir) => read_git_file(git_dir, rev, path),
+            Self::Forge { url, kind: ForgeKind::GitHub } => fetch_github_file(url, rev, path, config).await,
+            Self::Forge { url, kind: ForgeKind::GitLab } => fetch_gitlab_file(url, rev, path, config).await,
+            Self::Forge { url, kind: ForgeKind::Gitea } => fetch_gitea_file(url, rev, path, config).await,
+        }
+    }
+}
+
+/// A repository revision the package was found at
+struct Revision {
+    name: String,
+    tree: RepoTree,
+    /// Whether the revision is the one the package was published from (VCS sha or release tag)
+    exact: bool,
+}
+
+/// Check a registry crate's declared repository against its published package.
+///
+/// `manifest_path` is the package's manifest in Cargo's registry source cache;
+/// the `.crate` archive next to it in the registry cache is preferred when present.
+pub async fn verify_provenance(
+    name: &str,
+    version: &str,
+    manifest_path: Option<&Path>,
+    repo_url: Option<&str>,
+    default_branch: Option<&str>,
+    config: &NetworkConfig,
+) -> Provenance {
+    let Some(repo_url) = repo_url else {
+        return provenance(ProvenanceStatus::RepositoryNotFound, "No repository is declared");
+    };
+
+    let published = match manifest_path.map(read_published_crate) {
+        Some(Ok(Some(published))) => published,
+        Some(Err(e)) => {
+            return provenance(
+                ProvenanceStatus::Unverified,
+                format!("Could not read the published package: {}", e),
+            )
+        }
+        _ => {
+            return provenance(
+                ProvenanceStatus::Unverified,
+                "Published package not found in the local registry cache",
+            )
+        }
+    };
+
+    let mut accesses = Vec::new();
+    if let Some(git_dir) = find_git_database(repo_url) {
+        accesses.push(RepoAccess::Local(git_dir));
+    }
+    if let Some(forge) = resolve_forge(repo_url, config) {
+        accesses.push(RepoAccess::Forge {
+            url: repo_url,
+            kind: forge.kind,
+        });
+    }
+    if accesses.is_empty() {
+        return provenance(
+            ProvenanceStatus::Unverified,
+            format!("No local clone or supported forge for {}", repo_url),
+        );
+    }
+
+    match check_repository(name, version, &published, &accesses, default_branch, repo_url, config).await {
+        Ok(result) => result,
+        Err(e) => provenance(
+            ProvenanceStatus::Unverified,
+            format!("Could not read {}: {}", repo_url, e),
+        ),
+    }
+}
+
+async fn check_repository(
+    name: &str,
+    version: &str,
+    published: &PublishedCrate,
+    accesses: &[RepoAccess<'_>],
+    default_branch: Option<&str>,
+    repo_url: &str,
+    config: &NetworkConfig,
+) -> Result<Provenance> {
+    let Some((access, revision)) = find_revision(name, version, published, accesses, default_branch, config).await?
+    else {
+        // Local clones miss tags and forks, so only a forge can tell us the repository is gone
+        let status = if accesses.iter().any(|a| matches!(a, RepoAccess::Forge { .. })) {
+            ProvenanceStatus::RepositoryNotFound
+        } else {
+            ProvenanceStatus::Unverified
+        };
+        return Ok(provenance(status, format!("Repository {} could not be found", repo_url)));
+    };
+
+    let Some(manifest) = find_manifest(name, published, access, &revision, config).await? else {
+        let mut result = provenance(
+            ProvenanceStatus::PackageNotFound,
+            format!("{} contains no package named {} at {}", repo_url, name, revision.name),
+        );
+        result.revision = Some(revision.name);
+        return Ok(result);
+    };
+    let package_dir = manifest
+        .strip_suffix("Cargo.toml")
+        .unwrap_or_default()
+        .trim_end_matches('/')
+        .to_string();
+
+    let mut result = Provenance {
+        status: ProvenanceStatus::Verified,
+        revision: Some(revision.name.clone()),
+        path_in_vcs: Some(package_dir.clone()),
+        mismatched_files: Vec::new(),
+        missing_files: Vec::new(),
+        message: String::new(),
+    };
+
+    if !revision.exact {
+        result.message = format!(
+            "Package found on {}; no VCS info or release tag to compare the published files against",
+            revision.name
+        );
+        return Ok(result);
+    }
+
+    for (path, blob) in &published.blobs {
+        let repo_path = if package_dir.is_empty() {
+            path.clone()
+        } else {
+            format!("{}/{}", package_dir, path)
+        };
+        match revision.tree.get(&repo_path) {
+            Some(repo_blob) if repo_blob == blob => {}
+            Some(_) => result.mismatched_files.push(path.clone()),
+            None => result.missing_files.push(path.clone()),
+        }
+    }
+
+    if result.mismatched_files.is_empty() && result.missing_files.is_empty() {
+        result.message = format!("Published files match {} at {}", repo_url, revision.name);
+    } else {
+        result.status = ProvenanceStatus::ContentMismatch;
+        let dirty = published.vcs.as_ref().is_some_and(|v| v.git.dirty);
+        result.message = format!(
+            "{} published file(s) differ from {} at {} and {} are missing from it{}",
+            result.mismatched_files.len(),
+            repo_url,
+            revision.name,
+            result.missing_files.len(),
+            if dirty { " (published from a dirty working tree)" } else { "" }
+        );
+    }
+
+    Ok(result)
+}
+
+/// Find the first revision any source can serve: the recorded commit, a
+/// release tag, then the default branch
+async fn find_revision<'a, 'b>(
+    name: &str,
+    version: &str,
+    published: &PublishedCrate,
+    accesses: &'a [RepoAccess<'b>],
+    default_branch: Option<&str>,
+    config: &NetworkConfig,
+) -> Result<Option<(&'a RepoAccess<'b>, Revision)>> {
+    let mut candidates = Vec::new();
+    if let Some(vcs) = &published.vcs {
+        candidates.push((vcs.git.sha1.clone(), true));
+    }
+    for tag in [
+        format!("v{}", version),
+        version.to_string(),
+        format!("{}-v{}", name, version),
+        format!("{}-{}", name, version),
+    ] {
+        candidates.push((tag, true));
+    }
+    candidates.push((default_branch.unwrap_or("HEAD").to_string(), false));
+
+    for (rev, exact) in &candidates {
+        for access in accesses {
+            if let Some(tree) = access.tree(rev, config).await? {
+                debug!("Found {} in {:?} at {}", name, access, rev);
+                return Ok(Some((
+                    access,
+                    Revision {
+                        name: rev.clone(),
+                        tree,
+                        exact: *exact,
+                    },
+                )));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Find the manifest of the package named `name` in the revision
+async fn find_manifest(
+    name: &str,
+    published: &PublishedCrate,
+    access: &RepoAccess<'_>,
+    revision: &Revision,
+    config: &NetworkConfig,
+) -> Result<Option<String>> {
+    let candidates = match &published.vcs {
+        Some(vcs) if vcs.path_in_vcs.is_empty() => vec!["Cargo.toml".to_string()],
+        Some(vcs) => vec![format!("{}/Cargo.toml", vcs.path_in_vcs.trim_end_matches('/'))],
+        None => manifest_candidates(name, &revision.tree),
+    };
+
+    for path in candidates {
+        let Some(repo_blob) = revision.tree.get(&path) else {
+            continue;
+        };
+        // An identical manifest needs no download
+        if published.blobs.get("Cargo.toml") == Some(repo_blob) {
+            return Ok(Some(path));
+        }
+        let Some(content) = access.file(&revision.name, &path, config).await? else {
+            continue;
+        };
+        if manifest_package_name(&content).is_some_and(|n| same_crate_name(&n, name)) {
+            return Ok(Some(path));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Manifests worth checking, most likely first: directories named after the
+/// crate, then the shallowest
+fn manifest_candidates(name: &str, tree: &RepoTree) -> Vec<String> {
+    let mut manifests: Vec<&String> = tree
+        .keys()
+        .filter(|path| *path == "Cargo.toml" || path.ends_with("/Cargo.toml"))
+        .collect();
+    manifests.sort_by_key(|path| {
+        let dir_name = Path::new(path.as_str())
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .map(|dir| dir.to_string_lossy().into_owned());
+        let named_after_crate = dir_name.is_some_and(|dir| same_crate_name(&dir, name));
+        (!named_after_crate, path.matches('/').count(), (*path).clone())
+    });
+    manifests
+        .into_iter()
+        .take(MAX_MANIFEST_CANDIDATES)
+        .cloned()
+        .collect()
+}
+
+fn manifest_package_name(content: &[u8]) -> Option<String> {
+    let manifest: toml::Value = toml::from_str(std::str::from_utf8(content).ok()?).ok()?;
+    manifest
+        .get("package")?
+        .get("name")?
+        .as_str()
+        .map(String::from)
+}
+
+/// crates.io treats `-` and `_` as equivalent
+fn same_crate_name(a: &str, b: &str) -> bool {
+    a.replace('_', "-").eq_ignore_ascii_case(&b.replace('_', "-"))
+}
+
+/// Read the published package for `manifest_path` (`.../registry/src/<index>/<name>-<version>/Cargo.toml`).
+///
+/// Prefers the downloaded `.crate` archive in `registry/cache`, which can't have
+/// been edited in place, over the extracted sources.
+fn read_published_crate(manifest_path: &Path) -> Result<Option<PublishedCrate>> {
+    let Some(package_dir) = manifest_path.parent() else {
+        return Ok(None);
+    };
+    let (Some(package_name), Some(index_dir)) = (package_dir.file_name(), package_dir.parent()) else {
+        return Ok(None);
+    };
+    let Some(src_dir) = index_dir.parent().filter(|dir| dir.file_name().is_some_and(|n| n == "src")) else {
+        // Not a registry package (path or git source)
+        return Ok(None);
+    };
+    let Some(index_name) = index_dir.file_name() else {
+        return Ok(None);
+    };
+
+    let mut archive_name = package_name.to_os_string();
+    archive_name.push(".crate");
+    let archive = src_dir
+        .with_file_name("cache")
+        .join(index_name)
+        .join(archive_name);
+
+    let mut files = BTreeMap::new();
+    if archive.is_file() {
+        let prefix = format!("{}/", package_name.to_string_lossy());
+        let mut tar = tar::Archive::new(GzDecoder::new(File::open(&archive)?));
+        for entry in tar.entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.to_string_lossy().into_owned();
+            let Some(path) = path.strip_prefix(&prefix).map(String::from) else {
+                continue;
+            };
+            if is_compared(&path) {
+                let mut content = Vec::new();
+                entry.read_to_end(&mut content)?;
+                files.insert(path, content);
+            }
+        }
+    } else if package_dir.is_dir() {
+        collect_files(package_dir, package_dir, &mut files)?;
+    } else {
+        return Ok(None);
+    }
+
+    let vcs = files
+        .remove(VCS_INFO_FILE)
+        .and_then(|content| serde_json::from_slice(&content).ok());
+    let blobs = files
+        .into_iter()
+        .map(|(path, content)| {
+            // Compare the manifest as written, not the normalized one Cargo publishes
+            let path = if path == "Cargo.toml.orig" { "Cargo.toml".to_string() } else { path };
+            (path, git_blob_hash(&content))
+        })
+        .collect();
+
+    Ok(Some(PublishedCrate { blobs, vcs }))
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut BTreeMap<String, Vec<u8>>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+            continue;
+        }
+        let Ok(relative) = path.strip_prefix(root) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        if is_compared(&relative) {
+            files.insert(relative, fs::read(&path)?);
+        }
+    }
+    Ok(())
+}
+
+/// Rust sources, the original manifest and the VCS info are compared; other
+/// files (README copied from the workspace root, generated lockfiles) often differ legitimately
+fn is_compared(path: &str) -> bool {
+    path.ends_with(".rs") || path == "Cargo.toml.orig" || path == VCS_INFO_FILE
+}
+
+/// Hash of a file as git stores it (`sha1("blob <len>\0" + content)`)
+fn git_blob_hash(content: &[u8]) -> String {
+    let mut context = ring::digest::Context::new(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY);
+    context.update(format!("blob {}\0", content.len()).as_bytes());
+    context.update(content);
+    context
+        .finish()
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn provenance(status: ProvenanceStatus, message: impl Into<String>) -> Provenance {
+    Provenance {
+        status,
+        revision: None,
+        path_in_vcs: None,
+        mismatched_files: Vec::new(),
+        missing_files: Vec::new(),
+        message: message.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .env("GIT_AUTHOR_NAME", "Test")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_COMMITTER_NAME", "Test")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[test]
+    fn test_git_blob_hash() {
+        assert_eq!(git_blob_hash(b""), "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+    }
+
+    #[test]
+    fn test_manifest_candidates() {
+        let tree: RepoTree = ["Cargo.toml", "crates/other/Cargo.toml", "crates/my_crate/Cargo.toml", "src/lib.rs"]
+            .into_iter()
+            .map(|p| (p.to_string(), String::new()))
+            .collect();
+        assert_eq!(
+            manifest_candidates("my-crate", &tree),
+            vec!["crates/my_crate/Cargo.toml", "Cargo.toml", "crates/other/Cargo.toml"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        fs::create_dir_all(repo.join("demo/src")).unwrap();
+        let manifest = "[package]\nname = \"demo\"\nversion = \"1.0.0\"\n";
+        fs::write(repo.join("Cargo.toml"), "[workspace]\nmembers = [\"demo\"]\n").unwrap();
+        fs::write(repo.join("demo/Cargo.toml"), manifest).unwrap();
+        fs::write(repo.join("demo/src/lib.rs"), "pub fn demo() {}\n").unwrap();
+        git(&repo, &["init", "-q"]);
+        git(&repo, &["add", "-A"]);
+        git(&repo, &["commit", "-q", "-m", "release"]);
+        let sha = git(&repo, &["rev-parse", "HEAD"]);
+
+        // Lay the published package out the way Cargo extracts it
+        let package = dir.path().join("registry/src/index.crates.io-0000/demo-1.0.0");
+        fs::create_dir_all(package.join("src")).unwrap();
+        fs::write(package.join("Cargo.toml"), "# normalized\n").unwrap();
+        fs::write(package.join("Cargo.toml.orig"), manifest).unwrap();
+        fs::write(package.join("src/lib.rs"), "pub fn demo() {}\n").unwrap();
+        fs::write(
+            package.join(VCS_INFO_FILE),
+            format!(r#"{{"git":{{"sha1":"{}"}},"path_in_vcs":"demo"}}"#, sha),
+        )
+        .unwrap();
+
+        let accesses = [RepoAccess::Local(repo.join(".git"))];
+        let config = NetworkConfig::default();
+        let url = "https://example.com/team/repo";
+
+        let published = read_published_crate(&package.join("Cargo.toml")).unwrap().unwrap();
+        let result = check_repository("demo", "1.0.0", &published, &accesses, None, url, &config)
+            .await
+            .unwrap();
+        assert_eq!(result.status, ProvenanceStatus::Verified, "{}", result.message);
+        assert_eq!(result.revision.as_deref(), Some(sha.as_str()));
+        assert_eq!(result.path_in_vcs.as_deref(), Some("demo"));
+
+        // Code that never went through the repository
+        fs::write(package.join("src/lib.rs"), "pub fn demo() { steal() }\n").unwrap();
+        fs::write(package.join("src/extra.rs"), "fn steal() {}\n").unwrap();
+        let published = read_published_crate(&package.join("Cargo.toml")).unwrap().unwrap();
+        let result = check_repository("demo", "1.0.0", &published, &accesses, None, url, &config)
+            .await
+            .unwrap();
+        assert_eq!(result.status, ProvenanceStatus::ContentMismatch);
+        assert_eq!(result.mismatched_files, vec!["src/lib.rs"]);
+        assert_eq!(result.missing_files, vec!["src/extra.rs"]);
+
+        // A crate borrowing someone else's repository
+        let impostor = dir.path().join("registry/src/index.crates.io-0000/impostor-1.0.0");
+        fs::create_dir_all(impostor.join("src")).unwrap();
+        fs::write(impostor.join("Cargo.toml.orig"), "[package]\nname = \"impostor\"\n").unwrap();
+        fs::write(impostor.join("src/lib.rs"), "pub fn demo() {}\n").unwrap();
+        let published = read_published_crate(&impostor.join("Cargo.toml")).unwrap().unwrap();
+        let result = check_repository("impostor", "1.0.0", &published, &accesses, None, url, &config)
+            .await
+            .unwrap();
+        assert_eq!(result.status, ProvenanceStatus::PackageNotFound);
+        assert_eq!(result.revision.as_deref(), Some("HEAD"));
+    }
+}
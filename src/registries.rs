@@ -0,0 +1,243 @@
+//! Alternative registry definitions from Cargo configuration
+//!
+//! Reads `[registries.<name>]` tables from `.cargo/config.toml` files (the
+//! project directory and its ancestors, then `$CARGO_HOME`) and per-registry
+//! tokens from `$CARGO_HOME/credentials.toml`, mirroring Cargo's own lookup.
+
+use crate::config::cargo_home;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Index URLs Cargo uses for crates.io
+const CRATES_IO_INDEX_URLS: [&str; 2] = [
+    "https://github.com/rust-lang/crates.io-index",
+    "sparse+https://index.crates.io",
+];
+
+/// A registry declared in Cargo configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryDefinition {
+    /// Name used in `Cargo.toml` (`registry = "<name>"`)
+    pub name: String,
+    /// Index URL, including the `sparse+` prefix for sparse registries
+    pub index_url: String,
+    /// Token from the credentials file or `CARGO_REGISTRIES_<NAME>_TOKEN`
+    pub token: Option<String>,
+}
+
+impl RegistryDefinition {
+    /// Whether the registry uses the sparse HTTP protocol
+    pub fn is_sparse(&self) -> bool {
+        self.index_url.starts_with("sparse+")
+    }
+}
+
+/// All alternative registries known for a project
+#[derive(Debug, Clone, Default)]
+pub struct Registries {
+    definitions: Vec<RegistryDefinition>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CargoConfigFile {
+    #[serde(default)]
+    registries: HashMap<String, RegistryTable>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RegistryTable {
+    index: Option<String>,
+    token: Option<String>,
+}
+
+impl Registries {
+    /// Discover registries from the Cargo configuration that applies to a project
+    pub fn discover(project_path: &Path) -> Self {
+        let home = cargo_home();
+
+        let mut config_files = Vec::new();
+        let start = project_path
+            .canonicalize()
+            .unwrap_or_else(|_| project_path.to_path_buf());
+        for dir in start.ancestors() {
+            config_files.extend(config_candidates(&dir.join(".cargo")));
+        }
+        if let Some(home) = &home {
+            config_files.extend(config_candidates(home));
+        }
+
+        let credential_files = home
+            .iter()
+            .flat_map(|home| [home.join("credentials.toml"), home.join("credentials")])
+            .collect::<Vec<_>>();
+
+        let mut registries = Self::from_files(&config_files, &credential_files);
+        registries.apply_env(std::env::vars());
+        registries
+    }
+
+    /// Build from explicit config and credential files, nearest config first
+    pub fn from_files(config_files: &[PathBuf], credential_files: &[PathBuf]) -> Self {
+        let mut definitions: Vec<RegistryDefinition> = Vec::new();
+
+        for path in config_files.iter().filter(|p| p.is_file()) {
+            for (name, table) in read_config(path).registries {
+                if definitions.iter().any(|d| d.name == name) {
+                    continue; // a closer config file already defined it
+                }
+                if let Some(index) = table.index {
+                    definitions.push(RegistryDefinition {
+                        name,
+                        index_url: index,
+                        token: table.token,
+                    });
+                }
+            }
+        }
+
+        for path in credential_files.iter().filter(|p| p.is_file()) {
+            for (name, table) in read_config(path).registries {
+                if let Some(def) = definitions.iter_mut().find(|d| d.name == name) {
+                    if def.token.is_none() {
+                        def.token = table.token;
+                    }
+                }
+            }
+        }
+
+        debug!("Discovered {} alternative registries", definitions.len());
+        Self { definitions }
+    }
+
+    /// Apply `CARGO_REGISTRIES_<NAME>_INDEX` / `_TOKEN` overrides
+    fn apply_env(&mut self, vars: impl Iterator<Item = (String, String)>) {
+        for (key, value) in vars {
+            let Some(rest) = key.strip_prefix("CARGO_REGISTRIES_") else {
+                continue;
+            };
+            if let Some(env_name) = rest.strip_suffix("_INDEX") {
+                match self.find_by_env_name(env_name) {
+                    Some(def) => def.index_url = value,
+                    None => self.definitions.push(RegistryDefinition {
+                        name: env_name.to_lowercase().replace('_', "-"),
+                        index_url: value,
+                        token: None,
+                    }),
+                }
+            } else if let Some(env_name) = rest.strip_suffix("_TOKEN") {
+                if let Some(def) = self.find_by_env_name(env_name) {
+                    def.token = Some(value);
+                }
+            }
+        }
+    }
+
+    fn find_by_env_name(&mut self, env_name: &str) -> Option<&mut RegistryDefinition> {
+        self.definitions
+            .iter_mut()
+            .find(|d| d.name.to_uppercase().replace('-', "_") == env_name)
+    }
+
+    /// Find the registry definition for an index URL from a package source
+    pub fn find_by_index(&self, index_url: &str) -> Option<&RegistryDefinition> {
+        let wanted = normalize_index_url(index_url);
+        self.definitions
+            .iter()
+            .find(|d| normalize_index_url(&d.index_url) == wanted)
+    }
+}
+
+/// Whether an index URL (with or without `registry+`) refers to crates.io
+pub fn is_crates_io_index(index_url: &str) -> bool {
+    let url = normalize_index_url(index_url);
+    CRATES_IO_INDEX_URLS.contains(&url)
+}
+
+/// Strip `registry+` and trailing slashes so config and lockfile URLs compare equal
+pub fn normalize_index_url(index_url: &str) -> &str {
+    index_url
+        .strip_prefix("registry+")
+        .unwrap_or(index_url)
+        .trim_end_matches('/')
+}
+
+fn config_candidates(dir: &Path) -> [PathBuf; 2] {
+    [dir.join("config.toml"), dir.join("config")]
+}
+
+fn read_config(path: &Path) -> CargoConfigFile {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            warn!("Failed to read Cargo config {}: {}", path.display(), e);
+            return CargoConfigFile::default();
+        }
+    };
+    toml::from_str(&content).unwrap_or_else(|e| {
+        warn!("Failed to parse Cargo config {}: {}", path.display(), e);
+        CargoConfigFile::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registries_from_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let project_config = dir.path().join("project.toml");
+        let home_config = dir.path().join("home.toml");
+        let credentials = dir.path().join("credentials.toml");
+
+        fs::write(
+            &project_config,
+            "[registries.corp]\nindex = \"sparse+https://cargo.corp.example/index/\"\n",
+        )
+        .unwrap();
+        fs::write(
+            &home_config,
+            "[registries.corp]\nindex = \"sparse+https://ignored.example/\"\n\
+             [registries.legacy]\nindex = \"https://git.corp.example/index.git\"\n",
+        )
+        .unwrap();
+        fs::write(&credentials, "[registries.corp]\ntoken = \"secret\"\n").unwrap();
+
+        let mut registries = Registries::from_files(&[project_config, home_config], &[credentials]);
+
+        let corp = registries
+            .find_by_index("sparse+https://cargo.corp.example/index")
+            .unwrap();
+        assert_eq!(corp.name, "corp");
+        assert_eq!(corp.token.as_deref(), Some("secret"));
+        assert!(corp.is_sparse());
+
+        let legacy = registries
+            .find_by_index("registry+https://git.corp.example/index.git")
+            .unwrap();
+        assert_eq!(legacy.name, "legacy");
+        assert!(!legacy.is_sparse());
+
+        registries.apply_env(
+            vec![("CARGO_REGISTRIES_LEGACY_TOKEN".to_string(), "t0k".to_string())].into_iter(),
+        );
+        assert_eq!(
+            registries
+                .find_by_index("https://git.corp.example/index.git")
+                .unwrap()
+                .token
+                .as_deref(),
+            Some("t0k")
+        );
+    }
+
+    #[test]
+    fn test_is_crates_io_index() {
+        assert!(is_crates_io_index("registry+https://github.com/rust-lang/crates.io-index"));
+        assert!(is_crates_io_index("sparse+https://index.crates.io/"));
+        assert!(!is_crates_io_index("sparse+https://cargo.corp.example/index/"));
+    }
+}
@@ -1,26 +1,36 @@
 //! Health scoring algorithms for dependencies
 
 use crate::config::AuditConfig;
-use crate::metadata::{CrateMetadata, GitHubMetadata, GitLabMetadata};
-use crate::types::{ComponentScores, DependencyMetrics, HealthStatus, RepositoryMetrics};
+use crate::metadata::{CrateMetadata, GitHubMetadata, GitLabMetadata, GiteaMetadata, LocalGitMetadata};
+use crate::types::{
+    ComponentScores, DailyDownloads, DependencyMetrics, HealthStatus, RepositoryMetrics, UnsafeUsage,
+};
 use chrono::Utc;
 
 /// Calculate overall health score for a dependency
+#[allow(clippy::too_many_arguments)]
 pub fn calculate_health_score(
     crate_meta: Option<&CrateMetadata>,
     github_meta: Option<&GitHubMetadata>,
     gitlab_meta: Option<&GitLabMetadata>,
+    gitea_meta: Option<&GiteaMetadata>,
+    local_git: Option<&LocalGitMetadata>,
     openssf_score: Option<f32>,
+    unsafe_usage: Option<&UnsafeUsage>,
     config: &AuditConfig,
 ) -> (u8, ComponentScores, Option<DependencyMetrics>) {
     let weights = &config.scoring_weights;
     
     // Calculate component scores
-    let recency_score = calculate_recency_score(crate_meta, github_meta, gitlab_meta, config);
-    let maintenance_score = calculate_maintenance_score(github_meta, gitlab_meta);
-    let community_score = calculate_community_score(crate_meta, github_meta, gitlab_meta);
+    let recency_score =
+        calculate_recency_score(crate_meta, github_meta, gitlab_meta, gitea_meta, local_git, config);
+    let maintenance_score =
+        calculate_maintenance_score(github_meta, gitlab_meta, gitea_meta, local_git);
+    let community_score =
+        calculate_community_score(crate_meta, github_meta, gitlab_meta, gitea_meta, local_git);
     let stability_score = calculate_stability_score(crate_meta);
-    let security_score = calculate_security_score(crate_meta, github_meta, openssf_score);
+    let security_score = calculate_security_score(crate_meta, github_meta, gitea_meta, openssf_score);
+    let unsafe_score = unsafe_usage.map(calculate_unsafe_score);
     
     let scores = ComponentScores {
         recency: recency_score,
@@ -28,6 +38,7 @@ pub fn calculate_health_score(
         community: community_score,
         stability: stability_score,
         security: security_score,
+        unsafe_code: unsafe_score,
     };
     
     // Calculate weighted overall score
@@ -37,6 +48,11 @@ pub fn calculate_health_score(
         + stability_score * weights.stability
         + security_score * weights.security)
         .round();
+
+    // Unsafe usage takes its share only when the sources could be analyzed
+    if let Some(unsafe_score) = unsafe_score {
+        overall = (overall * (1.0 - weights.unsafe_code) + unsafe_score * weights.unsafe_code).round();
+    }
         
     // Penalize yanked crates heavily
     if let Some(meta) = crate_meta {
@@ -48,11 +64,39 @@ pub fn calculate_health_score(
     let overall = overall.clamp(0.0, 100.0) as u8;
     
     // Build metrics
-    let metrics = build_metrics(crate_meta, github_meta, gitlab_meta, openssf_score, &scores);
+    let mut metrics = build_metrics(
+        crate_meta,
+        github_meta,
+        gitlab_meta,
+        gitea_meta,
+        local_git,
+        openssf_score,
+        &scores,
+    );
+    if let Some(metrics) = metrics.as_mut() {
+        metrics.unsafe_usage = unsafe_usage.cloned();
+    }
     
     (overall, scores, metrics)
 }
 
+/// Score `unsafe` usage by its density in the library sources.
+///
+/// No `unsafe` at all scores 90, each item per 1000 lines costs 10 points,
+/// and `#![forbid(unsafe_code)]` adds 10.
+pub fn calculate_unsafe_score(usage: &UnsafeUsage) -> f32 {
+    let density = if usage.lines > 0 {
+        usage.total() as f32 * 1000.0 / usage.lines as f32
+    } else {
+        0.0
+    };
+    let mut score = (90.0 - density * 10.0).max(0.0);
+    if usage.forbids_unsafe {
+        score += 10.0;
+    }
+    score.min(100.0)
+}
+
 /// Determine health status from score
 pub fn determine_status(score: u8, _config: &AuditConfig) -> HealthStatus {
     if score >= 80 {
@@ -66,11 +110,52 @@ pub fn determine_status(score: u8, _config: &AuditConfig) -> HealthStatus {
     }
 }
 
+/// Check the crate's owner count against `min_maintainers`.
+///
+/// Returns a warning when fewer owners than required are known. Crates whose
+/// owners the registry didn't report are not flagged.
+pub fn check_maintainers(crate_meta: Option<&CrateMetadata>, config: &AuditConfig) -> Option<String> {
+    let owners = crate_meta?.owners.as_ref()?;
+    let minimum = config.staleness_thresholds.min_maintainers;
+    if owners.count() >= minimum {
+        return None;
+    }
+    Some(format!(
+        "Only {} crate owner(s), below the minimum of {}",
+        owners.count(),
+        minimum
+    ))
+}
+
+/// Data sources a score had to do without.
+///
+/// Without registry metadata stability falls back to a flat 50 points, and
+/// without repository activity (from a forge or a local checkout) so does
+/// maintenance.
+pub fn missing_data(
+    crate_meta: Option<&CrateMetadata>,
+    github_meta: Option<&GitHubMetadata>,
+    gitlab_meta: Option<&GitLabMetadata>,
+    gitea_meta: Option<&GiteaMetadata>,
+    local_git: Option<&LocalGitMetadata>,
+) -> Vec<&'static str> {
+    let mut missing = Vec::new();
+    if crate_meta.is_none() {
+        missing.push("registry metadata");
+    }
+    if github_meta.is_none() && gitlab_meta.is_none() && gitea_meta.is_none() && local_git.is_none() {
+        missing.push("repository activity");
+    }
+    missing
+}
+
 /// Calculate recency score based on last update
 fn calculate_recency_score(
     crate_meta: Option<&CrateMetadata>,
     github_meta: Option<&GitHubMetadata>,
     gitlab_meta: Option<&GitLabMetadata>,
+    gitea_meta: Option<&GiteaMetadata>,
+    local_git: Option<&LocalGitMetadata>,
     config: &AuditConfig,
 ) -> f32 {
     let now = Utc::now();
@@ -80,8 +165,12 @@ fn calculate_recency_score(
         gh.pushed_at
     } else if let Some(gl) = gitlab_meta {
         gl.last_activity_at
-    } else if let Some(cr) = crate_meta {
-        cr.updated_at
+    } else if let Some(gt) = gitea_meta {
+        gt.pushed_at
+    } else if let Some(lg) = local_git {
+        lg.last_commit_at
+    } else if let Some(updated_at) = crate_meta.and_then(|cr| cr.updated_at) {
+        updated_at
     } else {
         return 0.0; // No data
     };
@@ -111,6 +200,8 @@ fn calculate_recency_score(
 fn calculate_maintenance_score(
     github_meta: Option<&GitHubMetadata>,
     gitlab_meta: Option<&GitLabMetadata>,
+    gitea_meta: Option<&GiteaMetadata>,
+    local_git: Option<&LocalGitMetadata>,
 ) -> f32 {
     // Base score if we have repository data
     let mut score: f32 = 50.0;
@@ -158,6 +249,46 @@ fn calculate_maintenance_score(
         } else if days_since_activity > 365 {
             score -= 20.0;
         }
+    } else if let Some(gt) = gitea_meta {
+        if gt.is_archived {
+            return 0.0;
+        }
+        
+        if gt.open_issues < 10 {
+            score += 25.0;
+        } else if gt.open_issues < 50 {
+            score += 10.0;
+        } else if gt.open_issues > 200 {
+            score -= 10.0;
+        }
+        
+        let days_since_push = Utc::now().signed_duration_since(gt.pushed_at).num_days();
+        if days_since_push <= 30 {
+            score += 25.0;
+        } else if days_since_push <= 90 {
+            score += 15.0;
+        } else if days_since_push > 365 {
+            score -= 20.0;
+        }
+    } else if let Some(lg) = local_git {
+        // Local history has no issue tracker, so use commit frequency instead
+        let commits_per_month = lg.commits_per_month();
+        if commits_per_month >= 2.0 {
+            score += 25.0;
+        } else if commits_per_month >= 0.5 {
+            score += 10.0;
+        } else if lg.commits_last_year == 0 {
+            score -= 10.0;
+        }
+        
+        let days_since_commit = Utc::now().signed_duration_since(lg.last_commit_at).num_days();
+        if days_since_commit <= 30 {
+            score += 25.0;
+        } else if days_since_commit <= 90 {
+            score += 15.0;
+        } else if days_since_commit > 365 {
+            score -= 20.0;
+        }
     } else {
         // No repo data, moderate score
         return 50.0;
@@ -171,13 +302,14 @@ fn calculate_community_score(
     crate_meta: Option<&CrateMetadata>,
     github_meta: Option<&GitHubMetadata>,
     gitlab_meta: Option<&GitLabMetadata>,
+    gitea_meta: Option<&GiteaMetadata>,
+    local_git: Option<&LocalGitMetadata>,
 ) -> f32 {
     let mut score: f32 = 0.0;
     
-    // Author/maintainer count from crates.io
-    if let Some(crate_meta) = crate_meta {
-        let author_count = crate_meta.authors.len() as u32;
-        score += match author_count {
+    // Owner count from crates.io; a team counts as a single owner
+    if let Some(owners) = crate_meta.and_then(|m| m.owners.as_ref()) {
+        score += match owners.count() {
             0 => 0.0,
             1 => 30.0,
             2..=5 => 50.0,
@@ -214,12 +346,28 @@ fn calculate_community_score(
             201..=1000 => 30.0,
             _ => 40.0,
         };
+    } else if let Some(gt) = gitea_meta {
+        score += match gt.stars {
+            0..=10 => 0.0,
+            11..=50 => 10.0,
+            51..=200 => 20.0,
+            201..=1000 => 30.0,
+            _ => 40.0,
+        };
+    } else if let Some(lg) = local_git {
+        // Active authors are the only community signal in a local checkout
+        score += match lg.authors_last_year {
+            0..=1 => 0.0,
+            2..=5 => 20.0,
+            6..=20 => 35.0,
+            _ => 50.0,
+        };
     }
     
     score.clamp(0.0, 100.0)
 }
 
-/// Calculate stability score from version history
+/// Calculate stability score from version history, adoption and download trend
 fn calculate_stability_score(crate_meta: Option<&CrateMetadata>) -> f32 {
     if let Some(meta) = crate_meta {
         // More versions generally indicates active maintenance
@@ -239,17 +387,55 @@ fn calculate_stability_score(crate_meta: Option<&CrateMetadata>) -> f32 {
         } else {
             0.0
         };
+
+        // Crates many others depend on are unlikely to be abandoned quietly
+        let reverse_dependency_bonus = match meta.usage.reverse_dependencies {
+            Some(1000..) => 10.0,
+            Some(100..=999) => 5.0,
+            _ => 0.0,
+        };
+
+        // Users migrating away shows up in downloads before anywhere else
+        let trend_adjustment = match download_trend(&meta.usage.daily_downloads) {
+            Some(trend) if trend <= -0.5 => -20.0,
+            Some(trend) if trend <= -0.2 => -10.0,
+            Some(trend) if trend >= 0.2 => 5.0,
+            _ => 0.0,
+        };
         
-        (score + download_bonus).clamp(0.0, 100.0)
+        (score + download_bonus + reverse_dependency_bonus + trend_adjustment).clamp(0.0, 100.0)
     } else {
         50.0 // Unknown
     }
 }
 
+/// Relative change in average daily downloads between the last 30 days and
+/// the days before them. `None` without enough history to compare.
+pub fn download_trend(daily: &[DailyDownloads]) -> Option<f32> {
+    let latest = daily.last()?.date;
+    let cutoff = latest - chrono::Duration::days(30);
+
+    let (recent, earlier): (Vec<&DailyDownloads>, Vec<&DailyDownloads>) =
+        daily.iter().partition(|d| d.date > cutoff);
+    if recent.is_empty() || earlier.is_empty() {
+        return None;
+    }
+
+    let average = |days: &[&DailyDownloads]| {
+        days.iter().map(|d| d.downloads).sum::<u64>() as f32 / days.len() as f32
+    };
+    let before = average(&earlier);
+    if before == 0.0 {
+        return None;
+    }
+    Some(average(&recent) / before - 1.0)
+}
+
 /// Calculate security score based on policy and OpenSSF
 fn calculate_security_score(
     crate_meta: Option<&CrateMetadata>,
     github_meta: Option<&GitHubMetadata>,
+    gitea_meta: Option<&GiteaMetadata>,
     openssf_score: Option<f32>,
 ) -> f32 {
     let mut score: f32 = 50.0; // Base score
@@ -260,13 +446,14 @@ fn calculate_security_score(
     }
     
     // Fallback heuristics if no OpenSSF score
-    if let Some(gh) = github_meta {
-        if let Some(has_policy) = gh.has_security_policy {
-            if has_policy {
-                score += 20.0;
-            } else {
-                score -= 10.0;
-            }
+    let has_security_policy = github_meta
+        .and_then(|gh| gh.has_security_policy)
+        .or_else(|| gitea_meta.and_then(|gt| gt.has_security_policy));
+    if let Some(has_policy) = has_security_policy {
+        if has_policy {
+            score += 20.0;
+        } else {
+            score -= 10.0;
         }
     }
     
@@ -284,6 +471,8 @@ fn build_metrics(
     crate_meta: Option<&CrateMetadata>,
     github_meta: Option<&GitHubMetadata>,
     gitlab_meta: Option<&GitLabMetadata>,
+    gitea_meta: Option<&GiteaMetadata>,
+    local_git: Option<&LocalGitMetadata>,
     openssf_score: Option<f32>,
     scores: &ComponentScores,
 ) -> Option<DependencyMetrics> {
@@ -292,8 +481,16 @@ fn build_metrics(
     let days_since_last_update = github_meta
         .map(|gh| now.signed_duration_since(gh.pushed_at).num_days() as u32)
         .or_else(|| gitlab_meta.map(|gl| now.signed_duration_since(gl.last_activity_at).num_days() as u32))
-        .or_else(|| crate_meta.map(|cr| now.signed_duration_since(cr.updated_at).num_days() as u32));
+        .or_else(|| gitea_meta.map(|gt| now.signed_duration_since(gt.pushed_at).num_days() as u32))
+        .or_else(|| local_git.map(|lg| now.signed_duration_since(lg.last_commit_at).num_days() as u32))
+        .or_else(|| {
+            crate_meta
+                .and_then(|cr| cr.updated_at)
+                .map(|updated_at| now.signed_duration_since(updated_at).num_days() as u32)
+        });
     
+    let owners = crate_meta.and_then(|m| m.owners.as_ref());
+
     let repository = github_meta.map(|gh| RepositoryMetrics {
         open_issues: Some(gh.open_issues),
         contributor_count: gh.contributors_count,
@@ -301,6 +498,7 @@ fn build_metrics(
         stars: Some(gh.stars),
         is_archived: Some(gh.is_archived),
         has_security_policy: gh.has_security_policy,
+        commits_last_year: local_git.map(|lg| lg.commits_last_year),
     }).or_else(|| gitlab_meta.map(|gl| RepositoryMetrics {
         open_issues: Some(gl.open_issues),
         contributor_count: None,
@@ -308,14 +506,41 @@ fn build_metrics(
         stars: Some(gl.stars),
         is_archived: Some(gl.is_archived),
         has_security_policy: None,
+        commits_last_year: local_git.map(|lg| lg.commits_last_year),
+    })).or_else(|| gitea_meta.map(|gt| RepositoryMetrics {
+        open_issues: Some(gt.open_issues),
+        contributor_count: local_git.map(|lg| lg.authors_last_year),
+        days_since_last_commit: Some(now.signed_duration_since(gt.pushed_at).num_days() as u32),
+        stars: Some(gt.stars),
+        is_archived: Some(gt.is_archived),
+        has_security_policy: gt.has_security_policy,
+        commits_last_year: local_git.map(|lg| lg.commits_last_year),
+    })).or_else(|| local_git.map(|lg| RepositoryMetrics {
+        open_issues: None,
+        contributor_count: Some(lg.authors_last_year),
+        days_since_last_commit: Some(now.signed_duration_since(lg.last_commit_at).num_days() as u32),
+        stars: None,
+        is_archived: None,
+        has_security_policy: None,
+        commits_last_year: Some(lg.commits_last_year),
     }));
     
     Some(DependencyMetrics {
         days_since_last_update,
         version_count: crate_meta.map(|m| m.version_count),
-        maintainer_count: crate_meta.map(|m| m.authors.len() as u32),
+        maintainer_count: owners.map(|o| o.count()),
+        owners: owners.map(|o| o.users.clone()).unwrap_or_default(),
+        team_owners: owners.map(|o| o.teams.clone()).unwrap_or_default(),
+        published_by: crate_meta.and_then(|m| m.published_by.clone()),
+        publishers: Vec::new(),
+        reverse_dependencies: crate_meta.and_then(|m| m.usage.reverse_dependencies),
+        download_trend: crate_meta.and_then(|m| download_trend(&m.usage.daily_downloads)),
+        daily_downloads: crate_meta.map(|m| m.usage.daily_downloads.clone()).unwrap_or_default(),
+        version_downloads: crate_meta.map(|m| m.usage.version_downloads.clone()).unwrap_or_default(),
         repository,
         openssf_score,
+        openssf_checks: Vec::new(),
+        unsafe_usage: None,
         scores: scores.clone(),
     })
 }
@@ -323,31 +548,142 @@ fn build_metrics(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::metadata::CrateOwners;
     use chrono::Duration;
 
     #[test]
     fn test_recency_score_recent() {
-        let mut config = AuditConfig::default();
+        let config = AuditConfig::default();
         let crate_meta = CrateMetadata {
-            name: "test".to_string(),
-            version: "1.0.0".to_string(),
-            description: None,
-            license: None,
-            repository: None,
-            homepage: None,
             downloads: 1000,
-            recent_downloads: None,
-            created_at: Utc::now() - Duration::days(365),
-            updated_at: Utc::now() - Duration::days(15),
+            created_at: Some(Utc::now() - Duration::days(365)),
+            updated_at: Some(Utc::now() - Duration::days(15)),
             version_count: 10,
-            authors: vec![],
-            is_yanked: false,
+            ..CrateMetadata::fixture("test", "1.0.0")
         };
         
-        let score = calculate_recency_score(Some(&crate_meta), None, None, &config);
+        let score = calculate_recency_score(Some(&crate_meta), None, None, None, None, &config);
         assert!(score >= 90.0, "Recent update should score high");
     }
 
+    #[test]
+    fn test_local_git_scores() {
+        let local_git = LocalGitMetadata {
+            last_commit_at: Utc::now() - Duration::days(10),
+            commits_last_year: 40,
+            authors_last_year: 4,
+        };
+
+        assert_eq!(calculate_maintenance_score(None, None, None, None), 50.0);
+        assert_eq!(calculate_maintenance_score(None, None, None, Some(&local_git)), 100.0);
+        assert_eq!(calculate_community_score(None, None, None, None, Some(&local_git)), 20.0);
+        assert_eq!(
+            missing_data(None, None, None, None, None),
+            vec!["registry metadata", "repository activity"]
+        );
+        assert_eq!(missing_data(None, None, None, None, Some(&local_git)), vec!["registry metadata"]);
+
+        let metrics = build_metrics(
+            None,
+            None,
+            None,
+            None,
+            Some(&local_git),
+            None,
+            &ComponentScores {
+                recency: 0.0,
+                maintenance: 0.0,
+                community: 0.0,
+                stability: 0.0,
+                security: 0.0,
+                unsafe_code: None,
+            },
+        )
+        .unwrap();
+        let repo = metrics.repository.unwrap();
+        assert_eq!(repo.commits_last_year, Some(40));
+        assert_eq!(repo.contributor_count, Some(4));
+    }
+
+    #[test]
+    fn test_owner_scoring() {
+        let mut config = AuditConfig::default();
+        config.staleness_thresholds.min_maintainers = 2;
+        let mut crate_meta = CrateMetadata::fixture("test", "1.0.0");
+
+        assert_eq!(calculate_community_score(Some(&crate_meta), None, None, None, None), 0.0);
+        assert!(check_maintainers(Some(&crate_meta), &config).is_none());
+
+        crate_meta.owners = Some(CrateOwners {
+            users: vec!["alice".to_string()],
+            teams: vec![],
+        });
+        assert_eq!(calculate_community_score(Some(&crate_meta), None, None, None, None), 30.0);
+        assert!(check_maintainers(Some(&crate_meta), &config).is_some());
+
+        crate_meta.owners.as_mut().unwrap().teams.push("github:org:publish".to_string());
+        assert_eq!(calculate_community_score(Some(&crate_meta), None, None, None, None), 50.0);
+        assert!(check_maintainers(Some(&crate_meta), &config).is_none());
+    }
+
+    #[test]
+    fn test_unsafe_score() {
+        let mut usage = UnsafeUsage {
+            files: 4,
+            lines: 2000,
+            ..UnsafeUsage::default()
+        };
+        assert_eq!(calculate_unsafe_score(&usage), 90.0);
+
+        usage.forbids_unsafe = true;
+        assert_eq!(calculate_unsafe_score(&usage), 100.0);
+
+        usage.forbids_unsafe = false;
+        usage.unsafe_blocks = 6;
+        usage.unsafe_impls = 2;
+        assert_eq!(calculate_unsafe_score(&usage), 50.0);
+
+        usage.unsafe_functions = 100;
+        assert_eq!(calculate_unsafe_score(&usage), 0.0);
+
+        let config = AuditConfig::default();
+        let (without, scores, _) = calculate_health_score(None, None, None, None, None, None, None, &config);
+        assert_eq!(scores.unsafe_code, None);
+        let (with, scores, _) = calculate_health_score(None, None, None, None, None, None, Some(&usage), &config);
+        assert_eq!(scores.unsafe_code, Some(0.0));
+        assert!(with < without);
+    }
+
+    #[test]
+    fn test_download_trend() {
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let series = |earlier: u64, recent: u64| -> Vec<DailyDownloads> {
+            (0..90)
+                .map(|day| DailyDownloads {
+                    date: start + Duration::days(day),
+                    downloads: if day >= 60 { recent } else { earlier },
+                })
+                .collect()
+        };
+
+        assert_eq!(download_trend(&series(100, 100)), Some(0.0));
+        assert_eq!(download_trend(&series(100, 50)), Some(-0.5));
+        assert_eq!(download_trend(&series(100, 150)), Some(0.5));
+        assert_eq!(download_trend(&[]), None);
+
+        let mut crate_meta = CrateMetadata {
+            version_count: 12,
+            ..CrateMetadata::fixture("test", "1.0.0")
+        };
+        assert_eq!(calculate_stability_score(Some(&crate_meta)), 80.0);
+
+        crate_meta.usage.reverse_dependencies = Some(2500);
+        assert_eq!(calculate_stability_score(Some(&crate_meta)), 90.0);
+
+        crate_meta.usage.daily_downloads = series(100, 40);
+        assert_eq!(calculate_stability_score(Some(&crate_meta)), 70.0);
+    }
+
     #[test]
     fn test_determine_status() {
         let config = AuditConfig::default();
@@ -0,0 +1,258 @@
+//! Typosquatting, low-adoption and placeholder crate detection
+//!
+//! Dependency names are compared against a bundled list of the most-downloaded
+//! crates (`data/top-crates.txt`): separator swaps (`serde-json`), dropped
+//! separators (`serdejson`), look-alike characters (`rnio`, `t0kio`) and small
+//! edit distances (`reqwset`). Registry metadata adds two more signals: crates
+//! almost nobody downloads that don't link a repository, and near-empty
+//! uploads that only reserve a name.
+
+use crate::config::SquattingConfig;
+use crate::metadata::CrateMetadata;
+use crate::types::{Severity, SquattingFinding, SquattingKind};
+use std::sync::OnceLock;
+
+const TOP_CRATES: &str = include_str!("../data/top-crates.txt");
+
+/// Names shorter than this differ from each other by one edit too often to judge
+const MIN_EDIT_DISTANCE_LEN: usize = 5;
+
+/// Names at least this long may be two edits away from the crate they imitate
+const LONG_NAME_LEN: usize = 12;
+
+/// Descriptions that announce a name reservation
+const PLACEHOLDER_PHRASES: &[&str] = &["placeholder", "reserved", "name reservation", "squat", "coming soon"];
+
+struct TopCrate {
+    name: &'static str,
+    normalized: String,
+    stripped: String,
+    skeleton: String,
+}
+
+fn top_crates() -> &'static [TopCrate] {
+    static CRATES: OnceLock<Vec<TopCrate>> = OnceLock::new();
+    CRATES.get_or_init(|| {
+        TOP_CRATES
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|name| TopCrate {
+                name,
+                normalized: normalize(name),
+                stripped: strip_separators(name),
+                skeleton: skeleton(name),
+            })
+            .collect()
+    })
+}
+
+/// Check a registry dependency for signs of typosquatting or name squatting
+pub fn check_squatting(
+    name: &str,
+    crate_meta: Option<&CrateMetadata>,
+    config: &SquattingConfig,
+) -> Vec<SquattingFinding> {
+    if !config.enabled || config.allowed_crates.contains(name) {
+        return Vec::new();
+    }
+
+    let mut findings = Vec::new();
+    let low_adoption = crate_meta.and_then(|meta| check_adoption(meta, config));
+    let placeholder = crate_meta.and_then(|meta| check_placeholder(meta, config));
+
+    if let Some((target, reason)) = find_lookalike(name) {
+        // A near-miss name is far more suspicious when nobody uses the crate
+        let severity = if low_adoption.is_some() || placeholder.is_some() {
+            Severity::High
+        } else {
+            Severity::Medium
+        };
+        findings.push(SquattingFinding {
+            kind: SquattingKind::Typosquat,
+            severity,
+            message: format!("Name resembles the popular crate `{}` ({})", target, reason),
+            similar_to: Some(target.to_string()),
+        });
+    }
+    findings.extend(low_adoption);
+    findings.extend(placeholder);
+    findings
+}
+
+/// Find a popular crate that `name` imitates, with the reason it looks alike
+fn find_lookalike(name: &str) -> Option<(&'static str, String)> {
+    let crates = top_crates();
+    if crates.iter().any(|c| c.name == name) {
+        return None;
+    }
+
+    let normalized = normalize(name);
+    if let Some(c) = crates.iter().find(|c| c.normalized == normalized) {
+        return Some((c.name, "hyphen/underscore swap".to_string()));
+    }
+    let stripped = strip_separators(name);
+    if let Some(c) = crates.iter().find(|c| c.stripped == stripped) {
+        return Some((c.name, "added or dropped separator".to_string()));
+    }
+    let skeleton = skeleton(name);
+    if let Some(c) = crates.iter().find(|c| c.skeleton == skeleton) {
+        return Some((c.name, "look-alike characters".to_string()));
+    }
+
+    if normalized.len() < MIN_EDIT_DISTANCE_LEN {
+        return None;
+    }
+    let max_distance = if normalized.len() >= LONG_NAME_LEN { 2 } else { 1 };
+    crates
+        .iter()
+        .filter(|c| c.normalized.len() >= MIN_EDIT_DISTANCE_LEN)
+        .filter(|c| c.normalized.len().abs_diff(normalized.len()) <= max_distance)
+        .map(|c| (c, strsim::damerau_levenshtein(&normalized, &c.normalized)))
+        .filter(|(_, distance)| *distance <= max_distance)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(c, distance)| (c.name, format!("edit distance {}", distance)))
+}
+
+/// Hardly downloaded and nowhere to review the source.
+///
+/// Only judged when the registry reported download statistics; index-only
+/// metadata has no download counts.
+fn check_adoption(meta: &CrateMetadata, config: &SquattingConfig) -> Option<SquattingFinding> {
+    meta.recent_downloads?;
+    if meta.downloads >= config.min_downloads || meta.repository.is_some() {
+        return None;
+    }
+    Some(SquattingFinding {
+        kind: SquattingKind::LowAdoption,
+        severity: Severity::Medium,
+        message: format!(
+            "Only {} downloads and no repository (minimum {})",
+            meta.downloads, config.min_downloads
+        ),
+        similar_to: None,
+    })
+}
+
+/// Near-empty upload: tiny archive plus a single version or no description,
+/// or a description announcing a name reservation
+fn check_placeholder(meta: &CrateMetadata, config: &SquattingConfig) -> Option<SquattingFinding> {
+    let description = meta.description.as_deref().map(str::trim).filter(|d| !d.is_empty());
+    let mut reasons = Vec::new();
+
+    if let Some(phrase) = description.and_then(|d| {
+        let lower = d.to_lowercase();
+        PLACEHOLDER_PHRASES.iter().find(|p| lower.contains(*p))
+    }) {
+        reasons.push(format!("description mentions \"{}\"", phrase));
+    }
+
+    if let Some(size) = meta.crate_size.filter(|size| *size <= config.max_placeholder_size) {
+        let mut signals = vec![format!("{} byte package", size)];
+        if meta.version_count <= 1 {
+            signals.push("single version".to_string());
+        }
+        if description.is_none() {
+            signals.push("no description".to_string());
+        }
+        if signals.len() > 1 {
+            reasons.extend(signals);
+        }
+    }
+
+    if reasons.is_empty() {
+        return None;
+    }
+    Some(SquattingFinding {
+        kind: SquattingKind::Placeholder,
+        severity: Severity::Medium,
+        message: format!("Looks like a placeholder crate: {}", reasons.join(", ")),
+        similar_to: None,
+    })
+}
+
+/// Lowercase with `-` and `_` treated alike, as crates.io compares names
+fn normalize(name: &str) -> String {
+    name.to_ascii_lowercase().replace('-', "_")
+}
+
+fn strip_separators(name: &str) -> String {
+    name.to_ascii_lowercase().replace(['-', '_'], "")
+}
+
+/// Collapse characters that read alike (`rn`/`m`, `vv`/`w`, `0`/`o`, `1`/`l`, `5`/`s`)
+fn skeleton(name: &str) -> String {
+    strip_separators(name)
+        .replace("rn", "m")
+        .replace("vv", "w")
+        .replace('0', "o")
+        .replace('1', "l")
+        .replace('5', "s")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crate_meta(downloads: u64, repository: Option<&str>) -> CrateMetadata {
+        CrateMetadata {
+            description: Some("A demo crate".to_string()),
+            repository: repository.map(String::from),
+            downloads,
+            recent_downloads: Some(downloads),
+            version_count: 3,
+            crate_size: Some(40_000),
+            ..CrateMetadata::fixture("demo", "0.1.0")
+        }
+    }
+
+    #[test]
+    fn test_find_lookalike() {
+        assert!(find_lookalike("serde_json").is_none());
+        assert!(find_lookalike("my-internal-crate").is_none());
+        assert_eq!(find_lookalike("serde-json").unwrap().0, "serde_json");
+        assert_eq!(find_lookalike("serdejson").unwrap().0, "serde_json");
+        assert_eq!(find_lookalike("t0kio").unwrap().0, "tokio");
+        assert_eq!(find_lookalike("reqwset").unwrap().0, "reqwest");
+        assert_eq!(find_lookalike("rustls-pemfiel").unwrap().0, "rustls-pemfile");
+        // Short names are left alone
+        assert!(find_lookalike("lag").is_none());
+    }
+
+    #[test]
+    fn test_check_squatting() {
+        let config = SquattingConfig::default();
+
+        let popular = crate_meta(5_000_000, Some("https://github.com/x/y"));
+        assert!(check_squatting("reqwest", Some(&popular), &config).is_empty());
+
+        let findings = check_squatting("reqwset", Some(&popular), &config);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, Severity::Medium);
+
+        let mut unknown = crate_meta(12, None);
+        unknown.crate_size = Some(600);
+        unknown.version_count = 1;
+        let findings = check_squatting("reqwset", Some(&unknown), &config);
+        let kinds: Vec<_> = findings.iter().map(|f| f.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![SquattingKind::Typosquat, SquattingKind::LowAdoption, SquattingKind::Placeholder]
+        );
+        assert_eq!(findings[0].severity, Severity::High);
+
+        // Index-only metadata carries no download counts
+        unknown.recent_downloads = None;
+        unknown.crate_size = None;
+        assert!(check_squatting("obscure-crate", Some(&unknown), &config).is_empty());
+
+        let mut reserved = crate_meta(5_000, Some("https://github.com/x/y"));
+        reserved.description = Some("Reserved for future use".to_string());
+        let findings = check_squatting("future-thing", Some(&reserved), &config);
+        assert_eq!(findings[0].kind, SquattingKind::Placeholder);
+
+        let mut allowed = SquattingConfig::default();
+        allowed.allowed_crates.insert("reqwset".to_string());
+        assert!(check_squatting("reqwset", Some(&unknown), &allowed).is_empty());
+    }
+}
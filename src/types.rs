@@ -1,6 +1,6 @@
 //! Core data types for dependency health reporting
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
 /// Complete audit report for a Rust project
@@ -12,8 +12,14 @@ pub struct AuditReport {
     pub project_path: String,
     /// Timestamp when audit was performed
     pub timestamp: DateTime<Utc>,
+    /// The project's `rust-version` (MSRV), if it declares one
+    #[serde(default)]
+    pub rust_version: Option<String>,
     /// Health information for all dependencies
     pub dependencies: Vec<DependencyHealth>,
+    /// Crates resolved at more than one version
+    #[serde(default)]
+    pub duplicates: Vec<DuplicateCrate>,
     /// Summary statistics
     pub summary: AuditSummary,
 }
@@ -29,6 +35,49 @@ pub struct AuditSummary {
     pub average_health_score: f32,
     pub license_issues: usize,
     pub high_footprint_count: usize,
+    /// Total number of known vulnerabilities across all dependencies
+    #[serde(default)]
+    pub vulnerabilities: usize,
+    /// Total number of policy violations across all dependencies
+    #[serde(default)]
+    pub policy_violations: usize,
+    /// Total number of ownership changes since the baseline report
+    #[serde(default)]
+    pub ownership_changes: usize,
+    /// Dependencies whose declared repository failed provenance verification
+    #[serde(default)]
+    pub provenance_findings: usize,
+    /// Dependencies with a build script
+    #[serde(default)]
+    pub build_scripts: usize,
+    /// Procedural macro dependencies
+    #[serde(default)]
+    pub proc_macros: usize,
+    /// Dependencies with typosquatting, low-adoption or placeholder findings
+    #[serde(default)]
+    pub squatting_findings: usize,
+    /// Dependencies that link a native library or bundle C/C++ code
+    #[serde(default)]
+    pub native_dependencies: usize,
+    /// Dependencies abandoned or superseded by their authors, including ones
+    /// also counted in `insufficient_data`
+    #[serde(default)]
+    pub deprecated: usize,
+    /// Dependencies whose `rust-version` exceeds the project's
+    #[serde(default)]
+    pub msrv_incompatible: usize,
+    /// Dependencies behind their latest stable release
+    #[serde(default)]
+    pub outdated: usize,
+    /// Total time between the releases in use and the latest ones, in years
+    #[serde(default)]
+    pub libyears: f32,
+    /// Crates resolved at more than one version
+    #[serde(default)]
+    pub duplicate_crates: usize,
+    /// Dependencies that couldn't be scored offline for lack of data
+    #[serde(default)]
+    pub insufficient_data: usize,
 }
 
 /// Health information for a single dependency
@@ -58,6 +107,406 @@ pub struct DependencyHealth {
     pub warnings: Vec<String>,
     /// Whether the crate version is yanked
     pub is_yanked: bool,
+    /// Known vulnerabilities affecting this version
+    #[serde(default)]
+    pub vulnerabilities: Vec<Advisory>,
+    /// Pinned commit status (git dependencies only)
+    #[serde(default)]
+    pub git_pin: Option<GitPin>,
+    /// Rules from the configured policy that this dependency breaks
+    #[serde(default)]
+    pub policy_violations: Vec<PolicyViolation>,
+    /// Ownership changes since the baseline report
+    #[serde(default)]
+    pub ownership_changes: Vec<OwnershipChange>,
+    /// Whether the declared repository contains the published crate
+    #[serde(default)]
+    pub provenance: Option<Provenance>,
+    /// The package has a build script (`build.rs`), which runs at compile time
+    #[serde(default)]
+    pub has_build_script: bool,
+    /// The package is a procedural macro, which runs inside the compiler
+    #[serde(default)]
+    pub is_proc_macro: bool,
+    /// What the build script can do, from a static scan of its sources
+    #[serde(default)]
+    pub build_script: Option<BuildScriptAnalysis>,
+    /// Signs that the crate is a typosquat, an unvetted upload or a placeholder
+    #[serde(default)]
+    pub squatting: Vec<SquattingFinding>,
+    /// Native library linking and bundled C/C++ code
+    #[serde(default)]
+    pub native_code: Option<NativeCode>,
+    /// Explicit signs that the crate is unmaintained or superseded
+    #[serde(default)]
+    pub deprecation: Option<Deprecation>,
+    /// Declared minimum supported Rust version, compared with the project's
+    #[serde(default)]
+    pub msrv: Option<Msrv>,
+    /// How far the version in use is behind the latest stable release
+    #[serde(default)]
+    pub outdated: Option<Outdated>,
+}
+
+/// Distance between the version in use and the latest stable release
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Outdated {
+    /// Latest stable (non-yanked, non-prerelease) version
+    pub latest_version: String,
+    /// Major releases behind (minor and patch are 0 when this is set)
+    pub major_behind: u64,
+    /// Minor releases behind within the same major version
+    pub minor_behind: u64,
+    /// Patch releases behind within the same minor version
+    pub patch_behind: u64,
+    /// Days between the publication of the version in use and of the latest
+    /// version, when both dates are known
+    pub days_behind: Option<i64>,
+}
+
+impl Outdated {
+    /// Whether a newer stable release exists
+    pub fn is_behind(&self) -> bool {
+        self.major_behind > 0 || self.minor_behind > 0 || self.patch_behind > 0
+    }
+
+    /// Short description of the semver distance ("2 major", "3 minor", "1 patch")
+    pub fn describe(&self) -> String {
+        if self.major_behind > 0 {
+            format!("{} major", self.major_behind)
+        } else if self.minor_behind > 0 {
+            format!("{} minor", self.minor_behind)
+        } else if self.patch_behind > 0 {
+            format!("{} patch", self.patch_behind)
+        } else {
+            "up to date".to_string()
+        }
+    }
+
+    /// Release lag in years ("libyears")
+    pub fn libyears(&self) -> f32 {
+        self.days_behind.unwrap_or(0) as f32 / 365.25
+    }
+}
+
+/// A crate resolved at more than one version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DuplicateCrate {
+    pub name: String,
+    /// Resolved versions, oldest first
+    pub versions: Vec<DuplicateVersion>,
+    /// Packages pulled in only through the older versions (including
+    /// themselves), i.e. what unifying on the newest version would remove
+    pub duplicated_crates: usize,
+}
+
+/// One resolved version of a duplicated crate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DuplicateVersion {
+    pub version: String,
+    /// Packages depending on this version ("name version")
+    pub dependents: Vec<String>,
+}
+
+/// A dependency's minimum supported Rust version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Msrv {
+    /// Declared `rust-version` ("1.70")
+    pub rust_version: String,
+    /// Where the value was read from
+    pub source: MsrvSource,
+    /// Higher than the project's `rust-version`
+    pub exceeds_project: bool,
+    /// Newest published version that builds with the project's `rust-version`
+    /// (only looked up when this version doesn't)
+    pub compatible_version: Option<String>,
+}
+
+/// Origin of a dependency's `rust-version`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MsrvSource {
+    /// The package manifest (`Cargo.toml`)
+    Manifest,
+    /// The registry index entry of the version
+    RegistryIndex,
+}
+
+impl std::fmt::Display for MsrvSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MsrvSource::Manifest => write!(f, "manifest"),
+            MsrvSource::RegistryIndex => write!(f, "registry index"),
+        }
+    }
+}
+
+/// Why a crate is considered deprecated, and what to use instead
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Deprecation {
+    /// Every signal that fired
+    pub signals: Vec<DeprecationSignal>,
+    /// Suggested replacement crate (or path), when a signal names one
+    pub replacement: Option<String>,
+}
+
+/// A single deprecation signal
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeprecationSignal {
+    pub kind: DeprecationKind,
+    /// Human-readable description, with the matched wording or advisory ID
+    pub message: String,
+}
+
+/// Where a deprecation signal came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DeprecationKind {
+    /// RustSec `informational = "unmaintained"` advisory
+    UnmaintainedAdvisory,
+    /// `[badges] maintenance = { status = "deprecated" }` in the manifest
+    MaintenanceBadge,
+    /// Deprecation notice in the crate description
+    Description,
+    /// Deprecation notice at the top of the README
+    Readme,
+    /// The source repository is archived
+    ArchivedRepository,
+}
+
+impl std::fmt::Display for DeprecationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeprecationKind::UnmaintainedAdvisory => write!(f, "unmaintained-advisory"),
+            DeprecationKind::MaintenanceBadge => write!(f, "maintenance-badge"),
+            DeprecationKind::Description => write!(f, "description"),
+            DeprecationKind::Readme => write!(f, "readme"),
+            DeprecationKind::ArchivedRepository => write!(f, "archived-repository"),
+        }
+    }
+}
+
+/// Native code a package links to or compiles
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NativeCode {
+    /// Native library named by the package's `links` key
+    pub links: Option<String>,
+    /// The package follows the `-sys` naming convention
+    pub is_sys: bool,
+    /// C/C++ source and header files shipped in the package
+    pub source_files: usize,
+    /// Licenses found in bundled license files and source headers
+    #[serde(default)]
+    pub bundled_licenses: Vec<BundledLicense>,
+}
+
+/// A license covering bundled native code, which may differ from the Cargo `license` field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundledLicense {
+    /// License identifier (SPDX where recognised)
+    pub license: String,
+    /// Files the license was found in, relative to the package root (first few only)
+    pub files: Vec<String>,
+}
+
+/// A sign that a dependency may not be the crate its author meant to add
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SquattingFinding {
+    /// What was detected
+    pub kind: SquattingKind,
+    /// How urgently the dependency should be reviewed
+    pub severity: Severity,
+    /// Human-readable explanation
+    pub message: String,
+    /// Popular crate the name resembles, for `Typosquat`
+    #[serde(default)]
+    pub similar_to: Option<String>,
+}
+
+/// Kind of squatting finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SquattingKind {
+    /// The name is a near-miss of a popular crate
+    Typosquat,
+    /// Hardly downloaded and without a repository
+    LowAdoption,
+    /// Published without meaningful content
+    Placeholder,
+}
+
+impl std::fmt::Display for SquattingKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SquattingKind::Typosquat => write!(f, "typosquat"),
+            SquattingKind::LowAdoption => write!(f, "low-adoption"),
+            SquattingKind::Placeholder => write!(f, "placeholder"),
+        }
+    }
+}
+
+/// Capabilities found in a build script's sources
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildScriptAnalysis {
+    /// Capabilities the build script uses, sorted
+    pub capabilities: Vec<BuildCapability>,
+    /// Programs started through `std::process::Command` (`<dynamic>` when not a literal)
+    #[serde(default)]
+    pub commands: Vec<String>,
+    /// Networking crates (or `std::net`) the build script uses or depends on
+    #[serde(default)]
+    pub network_crates: Vec<String>,
+    /// Environment variables read that Cargo doesn't set for build scripts
+    #[serde(default)]
+    pub env_vars: Vec<String>,
+    /// File system writes whose destination isn't derived from `OUT_DIR`
+    #[serde(default)]
+    pub writes: Vec<String>,
+}
+
+impl BuildScriptAnalysis {
+    pub fn has(&self, capability: BuildCapability) -> bool {
+        self.capabilities.contains(&capability)
+    }
+}
+
+/// Something a build script does beyond generating code into `OUT_DIR`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BuildCapability {
+    /// Spawns processes with `std::process::Command`
+    SpawnsProcess,
+    /// Uses a networking crate or `std::net`
+    Network,
+    /// Reads environment variables other than the ones Cargo provides
+    ReadsEnv,
+    /// Writes, copies or removes files outside `OUT_DIR`
+    WritesOutsideOutDir,
+}
+
+impl std::fmt::Display for BuildCapability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildCapability::SpawnsProcess => write!(f, "spawns-process"),
+            BuildCapability::Network => write!(f, "network"),
+            BuildCapability::ReadsEnv => write!(f, "reads-env"),
+            BuildCapability::WritesOutsideOutDir => write!(f, "writes-outside-out-dir"),
+        }
+    }
+}
+
+/// Result of checking a crate's declared repository against the published package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Outcome of the check
+    pub status: ProvenanceStatus,
+    /// Commit, tag or branch the package was compared against
+    pub revision: Option<String>,
+    /// Directory of the package inside the repository (empty for the root)
+    pub path_in_vcs: Option<String>,
+    /// Published files whose contents differ from the repository
+    #[serde(default)]
+    pub mismatched_files: Vec<String>,
+    /// Published files that don't exist in the repository
+    #[serde(default)]
+    pub missing_files: Vec<String>,
+    /// Human-readable explanation
+    pub message: String,
+}
+
+/// Outcome of a provenance check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProvenanceStatus {
+    /// The repository contains the package and the published files match it
+    Verified,
+    /// The check could not be completed (no local package, unsupported host, network error)
+    Unverified,
+    /// No repository is declared, or the declared one does not exist
+    RepositoryNotFound,
+    /// The repository exists but contains no package with the crate's name
+    PackageNotFound,
+    /// Published files differ from the repository at the matching revision
+    ContentMismatch,
+}
+
+impl ProvenanceStatus {
+    /// Whether the outcome should be reported as a finding
+    pub fn is_finding(self) -> bool {
+        matches!(
+            self,
+            Self::RepositoryNotFound | Self::PackageNotFound | Self::ContentMismatch
+        )
+    }
+}
+
+impl std::fmt::Display for ProvenanceStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Verified => write!(f, "Verified"),
+            Self::Unverified => write!(f, "Unverified"),
+            Self::RepositoryNotFound => write!(f, "Repository not found"),
+            Self::PackageNotFound => write!(f, "Package not found"),
+            Self::ContentMismatch => write!(f, "Content mismatch"),
+        }
+    }
+}
+
+/// A change in who controls a crate, detected against a previous audit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnershipChange {
+    /// What changed
+    pub kind: OwnershipChangeKind,
+    /// How urgently the change should be reviewed
+    pub severity: Severity,
+    /// Human-readable explanation
+    pub message: String,
+    /// Owners recorded by the previous audit (users and teams)
+    pub previous_owners: Vec<String>,
+    /// Owners now (users and teams)
+    pub current_owners: Vec<String>,
+    /// Publisher of the audited version, for `NewPublisher`
+    #[serde(default)]
+    pub publisher: Option<String>,
+}
+
+/// Kind of ownership change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OwnershipChangeKind {
+    /// Users or teams were added to or removed from the crate's owners
+    OwnersChanged,
+    /// The audited version was published by someone who never published the crate before
+    NewPublisher,
+}
+
+impl std::fmt::Display for OwnershipChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OwnersChanged => write!(f, "Owners changed"),
+            Self::NewPublisher => write!(f, "New publisher"),
+        }
+    }
+}
+
+/// A configured policy rule broken by a dependency
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyViolation {
+    /// Rule identifier (e.g. `scorecard:Dangerous-Workflow`)
+    pub rule: String,
+    /// Human-readable explanation
+    pub message: String,
+}
+
+/// How a git dependency's pinned commit relates to the branch it tracks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitPin {
+    /// Commit pinned by the lockfile
+    pub rev: String,
+    /// Branch compared against (the requested branch, or the default branch)
+    pub branch: String,
+    /// Commits on the branch that the pinned commit doesn't include
+    pub commits_behind: Option<u32>,
 }
 
 /// Health status categories
@@ -70,8 +519,13 @@ pub enum HealthStatus {
     Warning,
     /// Stale: not updated recently, limited activity
     Stale,
-    /// Risky: deprecated, unmaintained, or high risk
+    /// Risky: low score or high risk
     Risky,
+    /// Deprecated: explicitly unmaintained or superseded, whatever the score
+    Deprecated,
+    /// Insufficient data: offline, and the registry or repository data wasn't available locally
+    #[serde(rename = "insufficient_data")]
+    InsufficientData,
 }
 
 impl std::fmt::Display for HealthStatus {
@@ -81,6 +535,8 @@ impl std::fmt::Display for HealthStatus {
             Self::Warning => write!(f, "Warning"),
             Self::Stale => write!(f, "Stale"),
             Self::Risky => write!(f, "Risky"),
+            Self::Deprecated => write!(f, "Deprecated"),
+            Self::InsufficientData => write!(f, "Insufficient data"),
         }
     }
 }
@@ -110,14 +566,112 @@ impl std::fmt::Display for LicenseRisk {
     }
 }
 
+/// Severity level of an advisory or finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Severity could not be determined
+    Unknown,
+    /// CVSS 0.0
+    None,
+    /// CVSS 0.1-3.9
+    Low,
+    /// CVSS 4.0-6.9
+    Medium,
+    /// CVSS 7.0-8.9
+    High,
+    /// CVSS 9.0-10.0
+    Critical,
+}
+
+impl Severity {
+    /// Map a CVSS base score (0.0-10.0) to a severity rating
+    pub fn from_cvss_score(score: f32) -> Self {
+        if score >= 9.0 {
+            Self::Critical
+        } else if score >= 7.0 {
+            Self::High
+        } else if score >= 4.0 {
+            Self::Medium
+        } else if score > 0.0 {
+            Self::Low
+        } else {
+            Self::None
+        }
+    }
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unknown => write!(f, "Unknown"),
+            Self::None => write!(f, "None"),
+            Self::Low => write!(f, "Low"),
+            Self::Medium => write!(f, "Medium"),
+            Self::High => write!(f, "High"),
+            Self::Critical => write!(f, "Critical"),
+        }
+    }
+}
+
+/// Database an advisory was loaded from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AdvisorySource {
+    /// RustSec advisory-db
+    RustSec,
+    /// OSV-format records (osv.dev exports, internal advisories)
+    Osv,
+}
+
+/// A known vulnerability affecting a dependency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Advisory {
+    /// Advisory identifier (e.g. RUSTSEC-2023-0001)
+    pub id: String,
+    /// Database the advisory came from
+    pub source: AdvisorySource,
+    /// Short human-readable summary
+    pub title: Option<String>,
+    /// Severity derived from the CVSS score
+    pub severity: Severity,
+    /// CVSS vector string, if provided
+    pub cvss: Option<String>,
+    /// CVSS base score (0.0-10.0), if a vector was provided
+    pub cvss_score: Option<f32>,
+    /// Other identifiers for the same issue (CVE, GHSA, ...)
+    pub aliases: Vec<String>,
+    /// Link to more information
+    pub url: Option<String>,
+    /// Version requirements that contain the fix
+    pub patched_versions: Vec<String>,
+}
+
 /// Source of a dependency
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum DependencySource {
     /// From crates.io registry
     CratesIo,
+    /// From an alternative or private registry
+    Registry {
+        index_url: String,
+        /// Registry name from Cargo configuration, if declared there
+        name: Option<String>,
+    },
     /// From a git repository
-    Git { url: String },
+    Git {
+        url: String,
+        /// Branch requested in `Cargo.toml` (`?branch=`)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
+        /// Tag requested in `Cargo.toml` (`?tag=`)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tag: Option<String>,
+        /// Commit pinned by the lockfile (`#sha`, falling back to `?rev=`)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        rev: Option<String>,
+    },
     /// From a local path
     Path { path: String },
     /// Unknown source
@@ -131,16 +685,103 @@ pub struct DependencyMetrics {
     pub days_since_last_update: Option<u32>,
     /// Number of crate versions published
     pub version_count: Option<u32>,
-    /// Number of authors/maintainers
+    /// Number of crates.io owners (users plus teams)
     pub maintainer_count: Option<u32>,
+    /// Logins of the users owning the crate
+    #[serde(default)]
+    pub owners: Vec<String>,
+    /// Teams owning the crate (e.g. `github:serde-rs:publish`)
+    #[serde(default)]
+    pub team_owners: Vec<String>,
+    /// User who published the audited version
+    #[serde(default)]
+    pub published_by: Option<String>,
+    /// Every user known to have published a version of the crate
+    #[serde(default)]
+    pub publishers: Vec<String>,
+    /// Number of crates depending on this crate
+    #[serde(default)]
+    pub reverse_dependencies: Option<u64>,
+    /// Relative change in daily downloads between the last 30 days and the 60 before
+    /// (0.25 = 25% growth, -0.5 = halved)
+    #[serde(default)]
+    pub download_trend: Option<f32>,
+    /// Daily downloads over the last 90 days, oldest first
+    #[serde(default)]
+    pub daily_downloads: Vec<DailyDownloads>,
+    /// Per-version downloads over the last 90 days, most downloaded first
+    #[serde(default)]
+    pub version_downloads: Vec<VersionDownloads>,
     /// Repository metrics (if available)
     pub repository: Option<RepositoryMetrics>,
     /// OpenSSF Scorecard score (0.0-10.0)
     pub openssf_score: Option<f32>,
+    /// Individual OpenSSF Scorecard checks
+    #[serde(default)]
+    pub openssf_checks: Vec<OpenSSFCheck>,
+    /// `unsafe` usage measured from the unpacked sources
+    #[serde(default)]
+    pub unsafe_usage: Option<UnsafeUsage>,
     /// Individual component scores
     pub scores: ComponentScores,
 }
 
+/// `unsafe` usage in a package's library sources (tests, benches and examples excluded)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnsafeUsage {
+    /// Rust source files parsed
+    pub files: u64,
+    /// Non-blank lines in the parsed files
+    pub lines: u64,
+    /// Source files that could not be parsed (and were not counted)
+    pub unparsed_files: u64,
+    /// `unsafe { ... }` blocks
+    pub unsafe_blocks: u64,
+    /// `unsafe fn` declarations
+    pub unsafe_functions: u64,
+    /// `unsafe impl` items
+    pub unsafe_impls: u64,
+    /// `unsafe trait` declarations
+    pub unsafe_traits: u64,
+    /// The library root declares `#![forbid(unsafe_code)]`
+    pub forbids_unsafe: bool,
+}
+
+impl UnsafeUsage {
+    /// Total number of `unsafe` blocks, functions, impls and traits
+    pub fn total(&self) -> u64 {
+        self.unsafe_blocks + self.unsafe_functions + self.unsafe_impls + self.unsafe_traits
+    }
+}
+
+/// Downloads of a crate (all versions) on one day
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyDownloads {
+    pub date: NaiveDate,
+    pub downloads: u64,
+}
+
+/// Downloads of one version over the crates.io 90-day window
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionDownloads {
+    pub version: String,
+    pub downloads: u64,
+}
+
+/// Result of a single OpenSSF Scorecard check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenSSFCheck {
+    /// Check name (e.g. `Dangerous-Workflow`)
+    pub name: String,
+    /// Score from 0 to 10, or -1 when the check was inconclusive
+    pub score: i32,
+    /// Short explanation of the score
+    pub reason: String,
+    /// Findings behind the score
+    #[serde(default)]
+    pub details: Vec<String>,
+}
+
 /// Repository-specific metrics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RepositoryMetrics {
@@ -156,6 +797,9 @@ pub struct RepositoryMetrics {
     pub is_archived: Option<bool>,
     /// Whether the repository has a SECURITY.md policy
     pub has_security_policy: Option<bool>,
+    /// Commits in the last 12 months (from a local checkout)
+    #[serde(default)]
+    pub commits_last_year: Option<u32>,
 }
 
 /// Individual component scores (0-100 scale)
@@ -171,6 +815,9 @@ pub struct ComponentScores {
     pub stability: f32,
     /// Score based on security practices (OpenSSF, SECURITY.md)
     pub security: f32,
+    /// Score based on `unsafe` usage, when the sources were analyzed
+    #[serde(default)]
+    pub unsafe_code: Option<f32>,
 }
 
 impl AuditReport {
@@ -180,7 +827,9 @@ impl AuditReport {
             project_name,
             project_path,
             timestamp: Utc::now(),
+            rust_version: None,
             dependencies: Vec::new(),
+            duplicates: Vec::new(),
             summary: AuditSummary::default(),
         }
     }
@@ -195,6 +844,19 @@ impl AuditReport {
         let mut total_score = 0u32;
         let mut license_issues = 0;
         let mut high_footprint = 0;
+        let mut vulnerabilities = 0;
+        let mut policy_violations = 0;
+        let mut ownership_changes = 0;
+        let mut provenance_findings = 0;
+        let mut build_scripts = 0;
+        let mut proc_macros = 0;
+        let mut squatting_findings = 0;
+        let mut native_dependencies = 0;
+        let mut deprecated = 0;
+        let mut insufficient_data = 0;
+        let mut msrv_incompatible = 0;
+        let mut outdated = 0;
+        let mut libyears = 0.0;
 
         for dep in &self.dependencies {
             match dep.status {
@@ -202,9 +864,18 @@ impl AuditReport {
                 HealthStatus::Warning => warning += 1,
                 HealthStatus::Stale => stale += 1,
                 HealthStatus::Risky => risky += 1,
+                HealthStatus::Deprecated => {}
+                HealthStatus::InsufficientData => insufficient_data += 1,
+            }
+            // Unscored dependencies keep their status but are deprecated all the same
+            if dep.deprecation.is_some() || dep.status == HealthStatus::Deprecated {
+                deprecated += 1;
             }
 
-            total_score += dep.health_score as u32;
+            // Scores built from default components would skew the average
+            if dep.status != HealthStatus::InsufficientData {
+                total_score += dep.health_score as u32;
+            }
 
             if matches!(
                 dep.license_risk,
@@ -218,6 +889,32 @@ impl AuditReport {
                     high_footprint += 1;
                 }
             }
+
+            vulnerabilities += dep.vulnerabilities.len();
+            policy_violations += dep.policy_violations.len();
+            ownership_changes += dep.ownership_changes.len();
+            if dep.provenance.as_ref().is_some_and(|p| p.status.is_finding()) {
+                provenance_findings += 1;
+            }
+            if dep.has_build_script {
+                build_scripts += 1;
+            }
+            if dep.is_proc_macro {
+                proc_macros += 1;
+            }
+            if !dep.squatting.is_empty() {
+                squatting_findings += 1;
+            }
+            if dep.native_code.is_some() {
+                native_dependencies += 1;
+            }
+            if dep.msrv.as_ref().is_some_and(|m| m.exceeds_project) {
+                msrv_incompatible += 1;
+            }
+            if let Some(lag) = dep.outdated.as_ref().filter(|o| o.is_behind()) {
+                outdated += 1;
+                libyears += lag.libyears();
+            }
         }
 
         self.summary = AuditSummary {
@@ -226,13 +923,27 @@ impl AuditReport {
             warning,
             stale,
             risky,
-            average_health_score: if total > 0 {
-                total_score as f32 / total as f32
+            average_health_score: if total > insufficient_data {
+                total_score as f32 / (total - insufficient_data) as f32
             } else {
                 0.0
             },
             license_issues,
             high_footprint_count: high_footprint,
+            vulnerabilities,
+            policy_violations,
+            ownership_changes,
+            provenance_findings,
+            build_scripts,
+            proc_macros,
+            squatting_findings,
+            native_dependencies,
+            deprecated,
+            msrv_incompatible,
+            outdated,
+            libyears,
+            duplicate_crates: self.duplicates.len(),
+            insufficient_data,
         };
     }
 }
@@ -248,6 +959,20 @@ impl Default for AuditSummary {
             average_health_score: 0.0,
             license_issues: 0,
             high_footprint_count: 0,
+            vulnerabilities: 0,
+            policy_violations: 0,
+            ownership_changes: 0,
+            provenance_findings: 0,
+            build_scripts: 0,
+            proc_macros: 0,
+            squatting_findings: 0,
+            native_dependencies: 0,
+            deprecated: 0,
+            msrv_incompatible: 0,
+            outdated: 0,
+            libyears: 0.0,
+            duplicate_crates: 0,
+            insufficient_data: 0,
         }
     }
 }
@@ -0,0 +1,217 @@
+//! Measure `unsafe` usage in a dependency's unpacked sources
+//!
+//! Sources are parsed with `syn` rather than searched for the keyword, so
+//! comments, strings and identifiers like `unsafe_code` are not counted.
+//! Code generated by macros is not visible to the parser.
+
+use crate::config::cargo_home;
+use crate::error::Result;
+use crate::types::UnsafeUsage;
+use std::fs;
+use std::path::{Path, PathBuf};
+use syn::visit::{self, Visit};
+use tracing::debug;
+
+/// Directories that don't end up in the dependency's compiled library
+const SKIPPED_DIRS: &[&str] = &["tests", "benches", "examples", "target"];
+
+/// Binary targets' directory, relative to the package root
+const BIN_DIR: &str = "src/bin";
+
+/// Count `unsafe` blocks, functions, impls and traits in the package at `manifest_path`.
+///
+/// Returns `None` when the package has no sources on disk.
+pub fn analyze_unsafe(manifest_path: &Path) -> Result<Option<UnsafeUsage>> {
+    let Some(package_dir) = manifest_path.parent().filter(|dir| dir.is_dir()) else {
+        return Ok(None);
+    };
+
+    let manifest = read_manifest(manifest_path);
+    let build_script = build_script_path(manifest.as_ref()).map(|path| package_dir.join(path));
+    let bin_dir = package_dir.join(BIN_DIR);
+
+    // Only library sources: binaries and the build script aren't linked into dependents
+    let mut files = Vec::new();
+    collect_sources(package_dir, package_dir, &mut files)?;
+    files.retain(|path| !path.starts_with(&bin_dir) && Some(path) != build_script.as_ref());
+    files.sort();
+    if files.is_empty() {
+        return Ok(None);
+    }
+
+    let lib_root = package_dir.join(lib_path(manifest.as_ref()));
+    let mut usage = UnsafeUsage::default();
+    for path in files {
+        let content = fs::read_to_string(&path)?;
+        let file = match syn::parse_file(&content) {
+            Ok(file) => file,
+            Err(e) => {
+                debug!("Could not parse {}: {}", path.display(), e);
+                usage.unparsed_files += 1;
+                continue;
+            }
+        };
+
+        usage.files += 1;
+        usage.lines += content.lines().filter(|line| !line.trim().is_empty()).count() as u64;
+        if path == lib_root {
+            usage.forbids_unsafe = forbids_unsafe_code(&file.attrs);
+        }
+
+        let mut counter = UnsafeCounter::default();
+        counter.visit_file(&file);
+        usage.unsafe_blocks += counter.blocks;
+        usage.unsafe_functions += counter.functions;
+        usage.unsafe_impls += counter.impls;
+        usage.unsafe_traits += counter.traits;
+    }
+
+    Ok(Some(usage))
+}
+
+/// Manifest of `name-version` in the unpacked registry sources (`$CARGO_HOME/registry/src/*/`)
+pub fn find_registry_source(name: &str, version: &str) -> Option<PathBuf> {
+    let src_dir = cargo_home()?.join("registry").join("src");
+    let package = format!("{}-{}", name, version);
+    fs::read_dir(src_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join(&package).join("Cargo.toml"))
+        .find(|manifest| manifest.is_file())
+}
+
+fn collect_sources(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+            let skipped = name.starts_with('.') || (dir == root && SKIPPED_DIRS.contains(&name.as_ref()));
+            if !skipped {
+                collect_sources(root, &path, files)?;
+            }
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn read_manifest(manifest_path: &Path) -> Option<toml::Table> {
+    fs::read_to_string(manifest_path).ok()?.parse().ok()
+}
+
+/// Library root declared in the manifest (`[lib] path`), or the default `src/lib.rs`
+fn lib_path(manifest: Option<&toml::Table>) -> PathBuf {
+    manifest
+        .and_then(|manifest| manifest.get("lib")?.get("path")?.as_str())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("src/lib.rs"))
+}
+
+/// Build script declared in the manifest (`[package] build`), or the default `build.rs`
+fn build_script_path(manifest: Option<&toml::Table>) -> Option<PathBuf> {
+    match manifest.and_then(|manifest| manifest.get("package")?.get("build")) {
+        Some(toml::Value::String(path)) => Some(PathBuf::from(path)),
+        Some(toml::Value::Boolean(false)) => None,
+        _ => Some(PathBuf::from("build.rs")),
+    }
+}
+
+/// Whether the crate-level attributes contain `#![forbid(unsafe_code)]`
+fn forbids_unsafe_code(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        if !matches!(attr.style, syn::AttrStyle::Inner(_)) || !attr.path().is_ident("forbid") {
+            return false;
+        }
+        let mut found = false;
+        let _ = attr.parse_nested_meta(|meta| {
+            found |= meta.path.is_ident("unsafe_code");
+            Ok(())
+        });
+        found
+    })
+}
+
+#[derive(Default)]
+struct UnsafeCounter {
+    blocks: u64,
+    functions: u64,
+    impls: u64,
+    traits: u64,
+}
+
+impl<'ast> Visit<'ast> for UnsafeCounter {
+    fn visit_expr_unsafe(&mut self, node: &'ast syn::ExprUnsafe) {
+        self.blocks += 1;
+        visit::visit_expr_unsafe(self, node);
+    }
+
+    fn visit_signature(&mut self, node: &'ast syn::Signature) {
+        if node.unsafety.is_some() {
+            self.functions += 1;
+        }
+        visit::visit_signature(self, node);
+    }
+
+    fn visit_item_impl(&mut self, node: &'ast syn::ItemImpl) {
+        if node.unsafety.is_some() {
+            self.impls += 1;
+        }
+        visit::visit_item_impl(self, node);
+    }
+
+    fn visit_item_trait(&mut self, node: &'ast syn::ItemTrait) {
+        if node.unsafety.is_some() {
+            self.traits += 1;
+        }
+        visit::visit_item_trait(self, node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_unsafe() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = dir.path();
+        fs::create_dir_all(package.join("src/sys")).unwrap();
+        fs::create_dir_all(package.join("tests")).unwrap();
+        fs::write(package.join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+        fs::write(
+            package.join("src/lib.rs"),
+            "//! unsafe in docs doesn't count\nmod sys;\npub fn get(v: &[u8]) -> u8 { unsafe { *v.get_unchecked(0) } }\n",
+        )
+        .unwrap();
+        fs::write(
+            package.join("src/sys/mod.rs"),
+            "pub unsafe trait Raw {}\nunsafe impl Raw for u8 {}\npub struct S;\nimpl S { pub unsafe fn f() { unsafe {} } }\n",
+        )
+        .unwrap();
+        fs::write(package.join("tests/it.rs"), "unsafe fn ignored() {}\n").unwrap();
+        // Neither the build script nor binaries are part of the library
+        fs::write(package.join("build.rs"), "fn main() { unsafe { std::env::set_var(\"A\", \"1\") } }\n").unwrap();
+        fs::create_dir_all(package.join("src/bin")).unwrap();
+        fs::write(package.join("src/bin/tool.rs"), "fn main() { unsafe {} }\n").unwrap();
+
+        let usage = analyze_unsafe(&package.join("Cargo.toml")).unwrap().unwrap();
+        assert_eq!(usage.files, 2);
+        assert_eq!(usage.unsafe_blocks, 2);
+        assert_eq!(usage.unsafe_functions, 1);
+        assert_eq!(usage.unsafe_impls, 1);
+        assert_eq!(usage.unsafe_traits, 1);
+        assert_eq!(usage.total(), 5);
+        assert!(!usage.forbids_unsafe);
+
+        fs::write(
+            package.join("Cargo.toml"),
+            "[package]\nname = \"demo\"\n[lib]\npath = \"src/root.rs\"\n",
+        )
+        .unwrap();
+        fs::write(package.join("src/root.rs"), "#![forbid(unsafe_code)]\npub fn safe() {}\n").unwrap();
+        let usage = analyze_unsafe(&package.join("Cargo.toml")).unwrap().unwrap();
+        assert!(usage.forbids_unsafe);
+        assert!(analyze_unsafe(&package.join("missing/Cargo.toml")).unwrap().is_none());
+    }
+}